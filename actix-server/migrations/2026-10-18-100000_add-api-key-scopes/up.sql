@@ -0,0 +1,4 @@
+-- Your SQL goes here
+-- Existing keys keep the full power they had before scopes existed.
+ALTER TABLE api_keys ADD COLUMN scopes TEXT[] NOT NULL DEFAULT ARRAY['api_key:read', 'api_key:write', 'user:read', 'user:write'];
+ALTER TABLE api_keys ALTER COLUMN scopes DROP DEFAULT;
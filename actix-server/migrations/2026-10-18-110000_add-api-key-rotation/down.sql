@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS api_keys_previous_blake3_hash_idx;
+
+ALTER TABLE api_keys DROP COLUMN previous_hash_expires_at;
+ALTER TABLE api_keys DROP COLUMN previous_blake3_hash;
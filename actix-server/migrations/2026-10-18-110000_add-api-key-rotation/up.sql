@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE api_keys ADD COLUMN previous_blake3_hash TEXT DEFAULT NULL;
+ALTER TABLE api_keys ADD COLUMN previous_hash_expires_at TIMESTAMP DEFAULT NULL;
+
+CREATE INDEX api_keys_previous_blake3_hash_idx ON api_keys(previous_blake3_hash);
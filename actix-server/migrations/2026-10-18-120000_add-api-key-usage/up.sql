@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE api_keys ADD COLUMN last_used_at TIMESTAMP DEFAULT NULL;
+ALTER TABLE api_keys ADD COLUMN last_used_ip TEXT DEFAULT NULL;
+ALTER TABLE api_keys ADD COLUMN usage_count BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS api_keys_key_id_idx;
+
+ALTER TABLE api_keys DROP COLUMN key_id;
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE api_keys ADD COLUMN key_id TEXT DEFAULT NULL;
+
+CREATE UNIQUE INDEX api_keys_key_id_idx ON api_keys(key_id);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS api_key_revocations;
+
+ALTER TABLE api_keys DROP COLUMN revoked_at;
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+ALTER TABLE api_keys ADD COLUMN revoked_at TIMESTAMP DEFAULT NULL;
+
+-- Kept when the revoked api key is deleted, the audit trail outlives the key
+CREATE TABLE api_key_revocations (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  api_key_id UUID DEFAULT NULL,
+  user_id UUID NOT NULL,
+  source TEXT DEFAULT NULL,
+  reported_url TEXT DEFAULT NULL,
+  reporter_ip TEXT DEFAULT NULL,
+  reporter_user_agent TEXT DEFAULT NULL,
+  created_at TIMESTAMP NOT NULL,
+  FOREIGN KEY (api_key_id) REFERENCES api_keys(id) ON DELETE SET NULL,
+  FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX api_key_revocations_api_key_id_idx ON api_key_revocations(api_key_id);
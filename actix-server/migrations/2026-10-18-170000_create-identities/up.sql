@@ -0,0 +1,35 @@
+-- Your SQL goes here
+-- Every way a user can log in. A user can link identities of several OIDC providers.
+CREATE TABLE identities (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  user_id UUID NOT NULL,
+  provider TEXT NOT NULL,
+  subject TEXT NOT NULL,
+  email TEXT DEFAULT NULL,
+  created_at TIMESTAMP NOT NULL,
+  updated_at TIMESTAMP NOT NULL,
+  FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX identities_provider_subject_idx ON identities(provider, subject);
+CREATE INDEX identities_user_id_idx ON identities(user_id);
+
+-- Users created before this table existed use the subject of the default OIDC provider as their
+-- id. The server sets actix_server.default_oidc_provider to the name of its default provider
+-- before running migrations. When running migrations by hand, pass the name of the first provider
+-- in OIDC_PROVIDERS, e.g. `PGOPTIONS="-c actix_server.default_oidc_provider=keycloak"`.
+DO $$
+DECLARE
+  default_oidc_provider TEXT := NULLIF(current_setting('actix_server.default_oidc_provider', true), '');
+BEGIN
+  IF default_oidc_provider IS NULL THEN
+    IF EXISTS (SELECT 1 FROM users) THEN
+      RAISE EXCEPTION 'actix_server.default_oidc_provider must be set to link existing users to their identities';
+    END IF;
+    RETURN;
+  END IF;
+
+  INSERT INTO identities (user_id, provider, subject, created_at, updated_at)
+  SELECT users.id, default_oidc_provider, users.id::text, NOW(), NOW()
+  FROM users;
+END $$;
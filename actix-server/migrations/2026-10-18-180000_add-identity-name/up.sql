@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE identities ADD COLUMN name TEXT DEFAULT NULL;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS cli_session_refresh_tokens;
+DROP TABLE IF EXISTS cli_sessions;
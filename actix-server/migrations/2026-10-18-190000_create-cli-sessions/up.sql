@@ -0,0 +1,35 @@
+-- Your SQL goes here
+-- Sessions of the CLI. The access token authenticates requests for a short time, the refresh token
+-- is exchanged for a new pair of tokens until the session expires or is revoked.
+CREATE TABLE cli_sessions (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  user_id UUID NOT NULL,
+  hostname TEXT NOT NULL,
+  cli_version TEXT NOT NULL,
+  scopes TEXT[] NOT NULL,
+  access_token_hash TEXT NOT NULL,
+  access_token_expires_at TIMESTAMP NOT NULL,
+  refresh_token_hash TEXT NOT NULL,
+  expires_at TIMESTAMP NOT NULL,
+  revoked_at TIMESTAMP DEFAULT NULL,
+  created_at TIMESTAMP NOT NULL,
+  updated_at TIMESTAMP NOT NULL,
+  FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX cli_sessions_access_token_hash_idx ON cli_sessions(access_token_hash);
+CREATE UNIQUE INDEX cli_sessions_refresh_token_hash_idx ON cli_sessions(refresh_token_hash);
+CREATE INDEX cli_sessions_user_id_idx ON cli_sessions(user_id);
+
+-- Every refresh token a CLI session exchanged. Presenting one of them again after a short grace
+-- period means the refresh tokens of the session leaked, so the whole session is revoked.
+CREATE TABLE cli_session_refresh_tokens (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  cli_session_id UUID NOT NULL,
+  refresh_token_hash TEXT NOT NULL,
+  used_at TIMESTAMP NOT NULL,
+  FOREIGN KEY (cli_session_id) REFERENCES cli_sessions(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX cli_session_refresh_tokens_refresh_token_hash_idx ON cli_session_refresh_tokens(refresh_token_hash);
+CREATE INDEX cli_session_refresh_tokens_cli_session_id_idx ON cli_session_refresh_tokens(cli_session_id);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE identities DROP COLUMN IF EXISTS email_verified;
+DROP TABLE IF EXISTS organization_invitations;
+DROP TABLE IF EXISTS organization_users;
+DROP TABLE IF EXISTS organizations;
@@ -0,0 +1,45 @@
+-- Your SQL goes here
+-- Organizations are the tenants of the product. Users belong to any number of them, every
+-- organization keeps at least one owner.
+CREATE TABLE organizations (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  name TEXT NOT NULL,
+  created_at TIMESTAMP NOT NULL,
+  updated_at TIMESTAMP NOT NULL
+);
+
+CREATE TABLE organization_users (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  organization_id UUID NOT NULL,
+  user_id UUID NOT NULL,
+  role TEXT NOT NULL CHECK (role IN ('owner', 'admin', 'member')),
+  created_at TIMESTAMP NOT NULL,
+  updated_at TIMESTAMP NOT NULL,
+  FOREIGN KEY (organization_id) REFERENCES organizations(id) ON DELETE CASCADE,
+  FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX organization_users_organization_id_user_id_idx ON organization_users(organization_id, user_id);
+CREATE INDEX organization_users_user_id_idx ON organization_users(user_id);
+
+-- Invitations to join an organization. Members are only added once the invitee accepts, the
+-- email is stored lowercased and matched against the email of the user accepting it.
+CREATE TABLE organization_invitations (
+  id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+  organization_id UUID NOT NULL,
+  email TEXT NOT NULL,
+  role TEXT NOT NULL CHECK (role IN ('owner', 'admin', 'member')),
+  invited_by UUID DEFAULT NULL,
+  expires_at TIMESTAMP NOT NULL,
+  created_at TIMESTAMP NOT NULL,
+  updated_at TIMESTAMP NOT NULL,
+  FOREIGN KEY (organization_id) REFERENCES organizations(id) ON DELETE CASCADE,
+  FOREIGN KEY (invited_by) REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE UNIQUE INDEX organization_invitations_organization_id_email_idx ON organization_invitations(organization_id, email);
+CREATE INDEX organization_invitations_email_idx ON organization_invitations(email);
+
+-- Invitations are only accepted for emails the provider of one of the user's identities reported
+-- as verified. Identities are marked when their user logs in again.
+ALTER TABLE identities ADD COLUMN email_verified BOOLEAN NOT NULL DEFAULT FALSE;
@@ -1,6 +1,9 @@
 use super::schema::*;
+use crate::errors::ServiceError;
 use bb8_redis::{bb8, RedisConnectionManager};
+use ipnet::IpNet;
 use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
 use utoipa::ToSchema;
 
 pub type PgPool = diesel_async::pooled_connection::deadpool::Pool<diesel_async::AsyncPgConnection>;
@@ -27,8 +30,8 @@ impl User {
     pub fn from_details(email: String, name: Option<String>) -> Self {
         User {
             id: uuid::Uuid::new_v4(),
-            email: email.into(),
-            name: name.map(|n| n.into()),
+            email,
+            name,
             created_at: chrono::Utc::now().naive_local(),
             updated_at: chrono::Utc::now().naive_local(),
         }
@@ -36,8 +39,8 @@ impl User {
 
     pub fn from_details_with_id(id: uuid::Uuid, email: String, name: Option<String>) -> Self {
         User {
-            id: id.into(),
-            email: email.into(),
+            id,
+            email,
             name,
             created_at: chrono::Utc::now().naive_local(),
             updated_at: chrono::Utc::now().naive_local(),
@@ -45,6 +48,330 @@ impl User {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, Queryable, Insertable, Selectable, Clone, ToSchema)]
+#[schema(example = json!({
+    "id": "d1d1d1d1-d1d1-d1d1-d1d1-d1d1d1d1d1d1",
+    "user_id": "e3e3e3e3-e3e3-e3e3-e3e3-e3e3e3e3e3e3",
+    "provider": "google",
+    "subject": "110169484474386276334",
+    "email": "developers@trieve.ai",
+    "created_at": "2021-01-01T00:00:00",
+    "updated_at": "2021-01-01T00:00:00",
+    "name": "Trieve Team",
+    "email_verified": true,
+}))]
+#[diesel(table_name = identities)]
+pub struct UserIdentity {
+    pub id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    /// Name of the OpenID provider the identity belongs to.
+    pub provider: String,
+    /// The `sub` claim the provider identifies the user with.
+    pub subject: String,
+    /// The email the provider reported for the identity at the last login.
+    pub email: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+    /// The name the provider reported for the identity at the last login.
+    pub name: Option<String>,
+    /// Whether the provider asserted `email` with the `email_verified` claim at the last login.
+    pub email_verified: bool,
+}
+
+impl UserIdentity {
+    pub fn from_details(
+        user_id: uuid::Uuid,
+        provider: String,
+        subject: String,
+        email: Option<String>,
+        name: Option<String>,
+        email_verified: bool,
+    ) -> Self {
+        UserIdentity {
+            id: uuid::Uuid::new_v4(),
+            user_id,
+            provider,
+            subject,
+            email,
+            created_at: chrono::Utc::now().naive_local(),
+            updated_at: chrono::Utc::now().naive_local(),
+            name,
+            email_verified,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum ApiKeyScope {
+    #[serde(rename = "api_key:read")]
+    ApiKeyRead,
+    #[serde(rename = "api_key:write")]
+    ApiKeyWrite,
+    #[serde(rename = "user:read")]
+    UserRead,
+    #[serde(rename = "user:write")]
+    UserWrite,
+}
+
+impl ApiKeyScope {
+    pub const ALL: [ApiKeyScope; 4] = [
+        ApiKeyScope::ApiKeyRead,
+        ApiKeyScope::ApiKeyWrite,
+        ApiKeyScope::UserRead,
+        ApiKeyScope::UserWrite,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiKeyScope::ApiKeyRead => "api_key:read",
+            ApiKeyScope::ApiKeyWrite => "api_key:write",
+            ApiKeyScope::UserRead => "user:read",
+            ApiKeyScope::UserWrite => "user:write",
+        }
+    }
+}
+
+impl std::str::FromStr for ApiKeyScope {
+    type Err = ServiceError;
+
+    fn from_str(scope: &str) -> Result<Self, Self::Err> {
+        ApiKeyScope::ALL
+            .into_iter()
+            .find(|s| s.as_str() == scope)
+            .ok_or_else(|| ServiceError::BadRequest(format!("Unknown api key scope {}", scope)))
+    }
+}
+
+/// The scopes granted to the current request. Requests authenticated with a cookie or an access
+/// token are granted every scope, requests authenticated with an api key or a CLI session only
+/// get the scopes stored on the key or session.
+#[derive(Debug, Clone)]
+pub struct AuthedScopes(pub Vec<ApiKeyScope>);
+
+/// Whether the user approved a device authorization on the verification page.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceAuthorizationStatus {
+    Pending,
+    Approved { user_id: uuid::Uuid },
+    Denied,
+}
+
+/// A login of a device through the OAuth device authorization flow (RFC 8628). Stored in Redis
+/// until it expires or the device exchanged it for a CLI session.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeviceAuthorization {
+    /// Hash of the device code, which only the device knows.
+    pub id: String,
+    /// The code the user enters on the verification page, e.g. `BDFG-HJKL`.
+    pub user_code: String,
+    /// Name of the client which started the login.
+    pub client_id: String,
+    /// Hostname of the device, shown in the list of sessions.
+    #[serde(default)]
+    pub hostname: String,
+    /// Version of the client on the device.
+    #[serde(default)]
+    pub cli_version: String,
+    /// The scopes of the CLI session the device gets.
+    pub scopes: Vec<ApiKeyScope>,
+    pub status: DeviceAuthorizationStatus,
+}
+
+/// A login with a browser. Stored in Redis for as long as the session cookie is valid.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BrowserSession {
+    pub id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    /// Name of the OpenID provider the user logged in with.
+    pub provider: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub last_seen_at: chrono::NaiveDateTime,
+    pub user_agent: Option<String>,
+    /// The client IP of the request which logged in.
+    pub ip: Option<String>,
+}
+
+/// What the identity cookie stores. The user is loaded by its id on every request, so changes to
+/// the user apply to existing logins.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct CookieIdentity {
+    pub user_id: uuid::Uuid,
+    /// The id of the [`BrowserSession`] created by the login.
+    pub session_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionKind {
+    /// A login with a browser, authenticated with the session cookie.
+    Browser,
+    /// A login of the CLI, authenticated with the access token of a CLI session.
+    Cli,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+#[schema(example = json!({
+    "id": "c2c2c2c2-c2c2-c2c2-c2c2-c2c2c2c2c2c2",
+    "kind": "browser",
+    "created_at": "2021-01-01T00:00:00",
+    "last_seen_at": "2021-01-01T02:30:00",
+    "provider": "keycloak",
+    "user_agent": "Mozilla/5.0 (X11; Linux x86_64; rv:125.0) Gecko/20100101 Firefox/125.0",
+    "ip": "203.0.113.7",
+    "hostname": null,
+    "cli_version": null,
+    "current": true,
+}))]
+pub struct SessionRespBody {
+    pub id: uuid::Uuid,
+    pub kind: SessionKind,
+    pub created_at: chrono::NaiveDateTime,
+    /// The last time (UTC) the session authenticated a request. CLI sessions are only seen when they are refreshed.
+    pub last_seen_at: chrono::NaiveDateTime,
+    /// The OpenID provider a browser session logged in with.
+    pub provider: Option<String>,
+    /// The user agent of the browser which logged in.
+    pub user_agent: Option<String>,
+    /// The client IP of the request which logged in the browser.
+    pub ip: Option<String>,
+    /// The hostname of the machine a CLI session was logged in on.
+    pub hostname: Option<String>,
+    /// The version of the CLI which logged in.
+    pub cli_version: Option<String>,
+    /// Whether the session authenticated this request.
+    pub current: bool,
+}
+
+impl From<BrowserSession> for SessionRespBody {
+    fn from(browser_session: BrowserSession) -> Self {
+        SessionRespBody {
+            id: browser_session.id,
+            kind: SessionKind::Browser,
+            created_at: browser_session.created_at,
+            last_seen_at: browser_session.last_seen_at,
+            provider: Some(browser_session.provider),
+            user_agent: browser_session.user_agent,
+            ip: browser_session.ip,
+            hostname: None,
+            cli_version: None,
+            current: false,
+        }
+    }
+}
+
+impl From<CliSession> for SessionRespBody {
+    fn from(cli_session: CliSession) -> Self {
+        SessionRespBody {
+            id: cli_session.id,
+            kind: SessionKind::Cli,
+            created_at: cli_session.created_at,
+            last_seen_at: cli_session.updated_at,
+            provider: None,
+            user_agent: None,
+            ip: None,
+            hostname: Some(cli_session.hostname),
+            cli_version: Some(cli_session.cli_version),
+            current: false,
+        }
+    }
+}
+
+/// How the current request was authenticated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthMethod {
+    /// The identity cookie set by logging in through `/api/auth`.
+    Cookie,
+    /// An api key in the `Authorization` header.
+    ApiKey,
+    /// An access token issued by one of the OpenID providers, sent as
+    /// `Authorization: Bearer <token>`.
+    BearerToken,
+    /// The access token of a CLI session in the `Authorization` header.
+    CliSession,
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Insertable, Selectable, Clone)]
+#[diesel(table_name = cli_sessions)]
+pub struct CliSession {
+    pub id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    /// Hostname of the machine the CLI was logged in on.
+    pub hostname: String,
+    pub cli_version: String,
+    pub scopes: Vec<Option<String>>,
+    pub access_token_hash: String,
+    pub access_token_expires_at: chrono::NaiveDateTime,
+    pub refresh_token_hash: String,
+    /// The time after which the session can no longer be refreshed. Moves forward on every refresh
+    /// until it reaches the maximum lifetime of the session.
+    pub expires_at: chrono::NaiveDateTime,
+    pub revoked_at: Option<chrono::NaiveDateTime>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+impl CliSession {
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_details(
+        user_id: uuid::Uuid,
+        hostname: String,
+        cli_version: String,
+        scopes: Vec<ApiKeyScope>,
+        access_token_hash: String,
+        access_token_expires_at: chrono::NaiveDateTime,
+        refresh_token_hash: String,
+        expires_at: chrono::NaiveDateTime,
+    ) -> Self {
+        CliSession {
+            id: uuid::Uuid::new_v4(),
+            user_id,
+            hostname,
+            cli_version,
+            scopes: scopes
+                .iter()
+                .map(|scope| Some(scope.as_str().to_string()))
+                .collect(),
+            access_token_hash,
+            access_token_expires_at,
+            refresh_token_hash,
+            expires_at,
+            revoked_at: None,
+            created_at: chrono::Utc::now().naive_local(),
+            updated_at: chrono::Utc::now().naive_local(),
+        }
+    }
+
+    pub fn cli_session_scopes(&self) -> Vec<ApiKeyScope> {
+        self.scopes
+            .iter()
+            .flatten()
+            .filter_map(|scope| scope.parse::<ApiKeyScope>().ok())
+            .collect()
+    }
+}
+
+/// A refresh token a CLI session already exchanged for a new pair of tokens.
+#[derive(Debug, Serialize, Deserialize, Queryable, Insertable, Selectable, Clone)]
+#[diesel(table_name = cli_session_refresh_tokens)]
+pub struct CliSessionRefreshToken {
+    pub id: uuid::Uuid,
+    pub cli_session_id: uuid::Uuid,
+    pub refresh_token_hash: String,
+    pub used_at: chrono::NaiveDateTime,
+}
+
+impl CliSessionRefreshToken {
+    pub fn from_details(cli_session_id: uuid::Uuid, refresh_token_hash: String) -> Self {
+        CliSessionRefreshToken {
+            id: uuid::Uuid::new_v4(),
+            cli_session_id,
+            refresh_token_hash,
+            used_at: chrono::Utc::now().naive_utc(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Queryable, Insertable, Selectable, Clone, ToSchema)]
 #[schema(example = json!({
     "id": "afafafaf-afaf-afaf-afaf-afafafafafaf",
@@ -53,6 +380,17 @@ impl User {
     "blake3_hash": "blake3-hash",
     "created_at": "2021-01-01T00:00:00",
     "updated_at": "2021-01-01T00:00:00",
+    "expires_at": "2021-02-01T00:00:00",
+    "scopes": ["api_key:read", "user:read"],
+    "previous_blake3_hash": null,
+    "previous_hash_expires_at": null,
+    "last_used_at": "2021-01-20T00:00:00",
+    "last_used_ip": "127.0.0.1",
+    "usage_count": 42,
+    "key_id": "Ab3dEf6hIj9k",
+    "revoked_at": null,
+    "allowed_cidrs": ["203.0.113.0/24"],
+    "hash_version": 1,
 }))]
 #[diesel(table_name = api_keys)]
 pub struct ApiKey {
@@ -62,10 +400,30 @@ pub struct ApiKey {
     pub blake3_hash: String,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
+    pub expires_at: Option<chrono::NaiveDateTime>,
+    pub scopes: Vec<Option<String>>,
+    pub previous_blake3_hash: Option<String>,
+    pub previous_hash_expires_at: Option<chrono::NaiveDateTime>,
+    pub last_used_at: Option<chrono::NaiveDateTime>,
+    pub last_used_ip: Option<String>,
+    pub usage_count: i64,
+    pub key_id: Option<String>,
+    pub revoked_at: Option<chrono::NaiveDateTime>,
+    pub allowed_cidrs: Vec<Option<String>>,
+    /// Version of the pepper `blake3_hash` was computed with. 0 is the unkeyed hash.
+    pub hash_version: i32,
 }
 
 impl ApiKey {
-    pub fn from_details(user_id: uuid::Uuid, name: String, blake3_hash: String) -> Self {
+    pub fn from_details(
+        user_id: uuid::Uuid,
+        name: String,
+        key_id: String,
+        blake3_hash: String,
+        expires_at: Option<chrono::NaiveDateTime>,
+        scopes: Vec<ApiKeyScope>,
+        allowed_cidrs: Vec<IpNet>,
+    ) -> Self {
         ApiKey {
             id: uuid::Uuid::new_v4(),
             user_id,
@@ -73,6 +431,452 @@ impl ApiKey {
             blake3_hash,
             created_at: chrono::Utc::now().naive_local(),
             updated_at: chrono::Utc::now().naive_local(),
+            expires_at,
+            scopes: scopes
+                .iter()
+                .map(|scope| Some(scope.as_str().to_string()))
+                .collect(),
+            previous_blake3_hash: None,
+            previous_hash_expires_at: None,
+            last_used_at: None,
+            last_used_ip: None,
+            usage_count: 0,
+            key_id: Some(key_id),
+            revoked_at: None,
+            allowed_cidrs: allowed_cidrs
+                .iter()
+                .map(|cidr| Some(cidr.to_string()))
+                .collect(),
+            hash_version: 0,
+        }
+    }
+
+    pub fn api_key_scopes(&self) -> Vec<ApiKeyScope> {
+        self.scopes
+            .iter()
+            .flatten()
+            .filter_map(|scope| scope.parse::<ApiKeyScope>().ok())
+            .collect()
+    }
+
+    pub fn api_key_allowed_cidrs(&self) -> Vec<IpNet> {
+        self.allowed_cidrs
+            .iter()
+            .flatten()
+            .filter_map(|cidr| cidr.parse::<IpNet>().ok())
+            .collect()
+    }
+
+    /// Keys without allowed CIDRs can be used from anywhere.
+    pub fn allows_ip(&self, ip: Option<IpAddr>) -> bool {
+        if self.allowed_cidrs.iter().flatten().next().is_none() {
+            return true;
+        }
+
+        ip.is_some_and(|ip| {
+            self.api_key_allowed_cidrs()
+                .iter()
+                .any(|cidr| cidr.contains(&ip))
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+#[schema(example = json!({
+    "id": "afafafaf-afaf-afaf-afaf-afafafafafaf",
+    "user_id": "e3e3e3e3-e3e3-e3e3-e3e3-e3e3e3e3e3e3",
+    "name": "my-api-key",
+    "created_at": "2021-01-01T00:00:00",
+    "updated_at": "2021-01-01T00:00:00",
+    "expires_at": "2021-02-01T00:00:00",
+    "scopes": ["api_key:read", "user:read"],
+    "previous_key_expires_at": "2021-01-15T01:00:00",
+    "last_used_at": "2021-01-20T00:00:00",
+    "last_used_ip": "127.0.0.1",
+    "usage_count": 42,
+    "key_id": "Ab3dEf6hIj9k",
+    "revoked_at": null,
+    "allowed_cidrs": ["203.0.113.0/24"],
+}))]
+pub struct ApiKeyRespBody {
+    pub id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    pub name: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+    pub expires_at: Option<chrono::NaiveDateTime>,
+    pub scopes: Vec<ApiKeyScope>,
+    /// If the key was rotated, the time (UTC) until which the previous secret keeps authenticating.
+    pub previous_key_expires_at: Option<chrono::NaiveDateTime>,
+    /// The last time (UTC) the key was used to authenticate a request. Usage is recorded in batches, so this can lag behind by up to a minute.
+    pub last_used_at: Option<chrono::NaiveDateTime>,
+    /// The client IP of the last request authenticated with the key.
+    pub last_used_ip: Option<String>,
+    /// The number of requests authenticated with the key.
+    pub usage_count: i64,
+    /// Public identifier embedded in the key. It is not secret and can be used to match a leaked key to its record. Null for legacy `tr-` keys.
+    pub key_id: Option<String>,
+    /// The time (UTC) at which the key was revoked after being reported as leaked. Revoked keys no longer authenticate.
+    pub revoked_at: Option<chrono::NaiveDateTime>,
+    /// The CIDRs the key can be used from. Empty if the key can be used from any IP address.
+    pub allowed_cidrs: Vec<String>,
+}
+
+impl From<ApiKey> for ApiKeyRespBody {
+    fn from(api_key: ApiKey) -> Self {
+        let scopes = api_key.api_key_scopes();
+        let allowed_cidrs = api_key
+            .api_key_allowed_cidrs()
+            .iter()
+            .map(|cidr| cidr.to_string())
+            .collect();
+
+        ApiKeyRespBody {
+            id: api_key.id,
+            user_id: api_key.user_id,
+            name: api_key.name,
+            created_at: api_key.created_at,
+            updated_at: api_key.updated_at,
+            expires_at: api_key.expires_at,
+            scopes,
+            previous_key_expires_at: api_key.previous_hash_expires_at,
+            last_used_at: api_key.last_used_at,
+            last_used_ip: api_key.last_used_ip,
+            usage_count: api_key.usage_count,
+            key_id: api_key.key_id,
+            revoked_at: api_key.revoked_at,
+            allowed_cidrs,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+#[schema(example = json!({
+    "token": "tr_live_Ab3dEf6hIj9k_y8chZiAuBj2IlYsVPZedv7ErB4Pg07Eh3jCprT",
+    "type": "actix_template_api_key",
+    "url": "https://github.com/octocat/hello-world/blob/main/.env",
+    "source": "content",
+}))]
+pub struct LeakedApiKey {
+    /// The raw api key which was found.
+    pub token: String,
+    /// The type of secret the scanner matched.
+    #[serde(rename = "type")]
+    pub token_type: Option<String>,
+    /// Where the api key was found.
+    pub url: Option<String>,
+    /// The kind of location the api key was found in, e.g. `content` or `commit`.
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Insertable, Selectable, Clone)]
+#[diesel(table_name = api_key_revocations)]
+pub struct ApiKeyRevocation {
+    pub id: uuid::Uuid,
+    /// None once the revoked api key was deleted.
+    pub api_key_id: Option<uuid::Uuid>,
+    pub user_id: uuid::Uuid,
+    pub source: Option<String>,
+    pub reported_url: Option<String>,
+    pub reporter_ip: Option<String>,
+    pub reporter_user_agent: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+impl ApiKeyRevocation {
+    pub fn from_details(
+        api_key: &ApiKey,
+        leaked_api_key: &LeakedApiKey,
+        reporter_ip: Option<String>,
+        reporter_user_agent: Option<String>,
+    ) -> Self {
+        ApiKeyRevocation {
+            id: uuid::Uuid::new_v4(),
+            api_key_id: Some(api_key.id),
+            user_id: api_key.user_id,
+            source: leaked_api_key.source.clone(),
+            reported_url: leaked_api_key.url.clone(),
+            reporter_ip,
+            reporter_user_agent,
+            created_at: chrono::Utc::now().naive_local(),
+        }
+    }
+}
+
+/// The role of a user in an organization. Each role can do everything the roles before it can.
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ToSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum OrganizationRole {
+    /// Can see the organization and its members.
+    Member,
+    /// Can also rename the organization and manage members and admins.
+    Admin,
+    /// Can also manage owners and delete the organization.
+    Owner,
+}
+
+impl OrganizationRole {
+    pub const ALL: [OrganizationRole; 3] = [
+        OrganizationRole::Member,
+        OrganizationRole::Admin,
+        OrganizationRole::Owner,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrganizationRole::Member => "member",
+            OrganizationRole::Admin => "admin",
+            OrganizationRole::Owner => "owner",
+        }
+    }
+}
+
+impl std::str::FromStr for OrganizationRole {
+    type Err = ServiceError;
+
+    fn from_str(role: &str) -> Result<Self, Self::Err> {
+        OrganizationRole::ALL
+            .into_iter()
+            .find(|r| r.as_str() == role)
+            .ok_or_else(|| ServiceError::BadRequest(format!("Unknown organization role {}", role)))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Insertable, Selectable, Clone)]
+#[diesel(table_name = organizations)]
+pub struct Organization {
+    pub id: uuid::Uuid,
+    pub name: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+impl Organization {
+    pub fn from_details(name: String) -> Self {
+        Organization {
+            id: uuid::Uuid::new_v4(),
+            name,
+            created_at: chrono::Utc::now().naive_local(),
+            updated_at: chrono::Utc::now().naive_local(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Queryable, Insertable, Selectable, Clone)]
+#[diesel(table_name = organization_users)]
+pub struct OrganizationUser {
+    pub id: uuid::Uuid,
+    pub organization_id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    pub role: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+impl OrganizationUser {
+    pub fn from_details(
+        organization_id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        role: OrganizationRole,
+    ) -> Self {
+        OrganizationUser {
+            id: uuid::Uuid::new_v4(),
+            organization_id,
+            user_id,
+            role: role.as_str().to_string(),
+            created_at: chrono::Utc::now().naive_local(),
+            updated_at: chrono::Utc::now().naive_local(),
+        }
+    }
+
+    pub fn organization_role(&self) -> OrganizationRole {
+        // The column is constrained to the known roles, fall back to the least privileged one
+        self.role.parse().unwrap_or(OrganizationRole::Member)
+    }
+}
+
+/// An invitation to join an organization, which turns into a membership once the user with the
+/// email accepts it.
+#[derive(Debug, Serialize, Deserialize, Queryable, Insertable, Selectable, Clone)]
+#[diesel(table_name = organization_invitations)]
+pub struct OrganizationInvitation {
+    pub id: uuid::Uuid,
+    pub organization_id: uuid::Uuid,
+    /// Lowercased email of the invitee.
+    pub email: String,
+    pub role: String,
+    /// The user who sent the invitation, None if that user was deleted.
+    pub invited_by: Option<uuid::Uuid>,
+    pub expires_at: chrono::NaiveDateTime,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+impl OrganizationInvitation {
+    pub fn from_details(
+        organization_id: uuid::Uuid,
+        email: String,
+        role: OrganizationRole,
+        invited_by: uuid::Uuid,
+        expires_at: chrono::NaiveDateTime,
+    ) -> Self {
+        OrganizationInvitation {
+            id: uuid::Uuid::new_v4(),
+            organization_id,
+            email,
+            role: role.as_str().to_string(),
+            invited_by: Some(invited_by),
+            expires_at,
+            created_at: chrono::Utc::now().naive_local(),
+            updated_at: chrono::Utc::now().naive_local(),
+        }
+    }
+
+    pub fn organization_role(&self) -> OrganizationRole {
+        // The column is constrained to the known roles, fall back to the least privileged one
+        self.role.parse().unwrap_or(OrganizationRole::Member)
+    }
+}
+
+/// An organization together with the role a user has in it. The membership of the auth'ed user in
+/// the organization selected with the `X-Organization` header is inserted into the request
+/// extensions by the auth middleware.
+#[derive(Debug, Clone)]
+pub struct OrganizationMembership {
+    pub organization: Organization,
+    pub role: OrganizationRole,
+}
+
+impl From<(Organization, OrganizationUser)> for OrganizationMembership {
+    fn from((organization, organization_user): (Organization, OrganizationUser)) -> Self {
+        OrganizationMembership {
+            organization,
+            role: organization_user.organization_role(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+#[schema(example = json!({
+    "id": "d4d4d4d4-d4d4-d4d4-d4d4-d4d4d4d4d4d4",
+    "name": "Trieve",
+    "role": "owner",
+    "created_at": "2021-01-01T00:00:00",
+    "updated_at": "2021-01-01T00:00:00",
+}))]
+pub struct OrganizationRespBody {
+    pub id: uuid::Uuid,
+    pub name: String,
+    /// The role of the auth'ed user in the organization.
+    pub role: OrganizationRole,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+impl From<OrganizationMembership> for OrganizationRespBody {
+    fn from(membership: OrganizationMembership) -> Self {
+        OrganizationRespBody {
+            id: membership.organization.id,
+            name: membership.organization.name,
+            role: membership.role,
+            created_at: membership.organization.created_at,
+            updated_at: membership.organization.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+#[schema(example = json!({
+    "user_id": "e3e3e3e3-e3e3-e3e3-e3e3-e3e3e3e3e3e3",
+    "email": "developers@trieve.ai",
+    "name": "Trieve Team",
+    "role": "admin",
+    "created_at": "2021-01-01T00:00:00",
+    "updated_at": "2021-01-01T00:00:00",
+}))]
+pub struct OrganizationUserRespBody {
+    pub user_id: uuid::Uuid,
+    pub email: String,
+    pub name: Option<String>,
+    pub role: OrganizationRole,
+    /// The time (UTC) at which the user was added to the organization.
+    pub created_at: chrono::NaiveDateTime,
+    /// The time (UTC) at which the role of the user was last changed.
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+impl From<(OrganizationUser, User)> for OrganizationUserRespBody {
+    fn from((organization_user, user): (OrganizationUser, User)) -> Self {
+        OrganizationUserRespBody {
+            user_id: user.id,
+            email: user.email,
+            name: user.name,
+            role: organization_user.organization_role(),
+            created_at: organization_user.created_at,
+            updated_at: organization_user.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+#[schema(example = json!({
+    "id": "f5f5f5f5-f5f5-f5f5-f5f5-f5f5f5f5f5f5",
+    "email": "developers@trieve.ai",
+    "role": "member",
+    "expires_at": "2021-01-08T00:00:00",
+    "created_at": "2021-01-01T00:00:00",
+}))]
+pub struct OrganizationInvitationRespBody {
+    pub id: uuid::Uuid,
+    /// The email the invitation was sent to.
+    pub email: String,
+    /// The role the invitee gets when accepting.
+    pub role: OrganizationRole,
+    /// The time (UTC) after which the invitation can no longer be accepted.
+    pub expires_at: chrono::NaiveDateTime,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+impl From<OrganizationInvitation> for OrganizationInvitationRespBody {
+    fn from(invitation: OrganizationInvitation) -> Self {
+        OrganizationInvitationRespBody {
+            id: invitation.id,
+            role: invitation.organization_role(),
+            email: invitation.email,
+            expires_at: invitation.expires_at,
+            created_at: invitation.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+#[schema(example = json!({
+    "id": "f5f5f5f5-f5f5-f5f5-f5f5-f5f5f5f5f5f5",
+    "organization_id": "d4d4d4d4-d4d4-d4d4-d4d4-d4d4d4d4d4d4",
+    "organization_name": "Trieve",
+    "role": "member",
+    "expires_at": "2021-01-08T00:00:00",
+}))]
+pub struct UserInvitationRespBody {
+    pub id: uuid::Uuid,
+    pub organization_id: uuid::Uuid,
+    pub organization_name: String,
+    /// The role the auth'ed user gets when accepting.
+    pub role: OrganizationRole,
+    /// The time (UTC) after which the invitation can no longer be accepted.
+    pub expires_at: chrono::NaiveDateTime,
+}
+
+impl From<(OrganizationInvitation, Organization)> for UserInvitationRespBody {
+    fn from((invitation, organization): (OrganizationInvitation, Organization)) -> Self {
+        UserInvitationRespBody {
+            id: invitation.id,
+            organization_id: organization.id,
+            organization_name: organization.name,
+            role: invitation.organization_role(),
+            expires_at: invitation.expires_at,
         }
     }
 }
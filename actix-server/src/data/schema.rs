@@ -8,6 +8,103 @@ diesel::table! {
         blake3_hash -> Text,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        expires_at -> Nullable<Timestamp>,
+        scopes -> Array<Nullable<Text>>,
+        previous_blake3_hash -> Nullable<Text>,
+        previous_hash_expires_at -> Nullable<Timestamp>,
+        last_used_at -> Nullable<Timestamp>,
+        last_used_ip -> Nullable<Text>,
+        usage_count -> Int8,
+        key_id -> Nullable<Text>,
+        revoked_at -> Nullable<Timestamp>,
+        allowed_cidrs -> Array<Nullable<Text>>,
+        hash_version -> Int4,
+    }
+}
+
+diesel::table! {
+    api_key_revocations (id) {
+        id -> Uuid,
+        api_key_id -> Nullable<Uuid>,
+        user_id -> Uuid,
+        source -> Nullable<Text>,
+        reported_url -> Nullable<Text>,
+        reporter_ip -> Nullable<Text>,
+        reporter_user_agent -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    cli_session_refresh_tokens (id) {
+        id -> Uuid,
+        cli_session_id -> Uuid,
+        refresh_token_hash -> Text,
+        used_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    cli_sessions (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        hostname -> Text,
+        cli_version -> Text,
+        scopes -> Array<Nullable<Text>>,
+        access_token_hash -> Text,
+        access_token_expires_at -> Timestamp,
+        refresh_token_hash -> Text,
+        expires_at -> Timestamp,
+        revoked_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    identities (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        provider -> Text,
+        subject -> Text,
+        email -> Nullable<Text>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        name -> Nullable<Text>,
+        email_verified -> Bool,
+    }
+}
+
+diesel::table! {
+    organization_invitations (id) {
+        id -> Uuid,
+        organization_id -> Uuid,
+        email -> Text,
+        role -> Text,
+        invited_by -> Nullable<Uuid>,
+        expires_at -> Timestamp,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    organization_users (id) {
+        id -> Uuid,
+        organization_id -> Uuid,
+        user_id -> Uuid,
+        role -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    organizations (id) {
+        id -> Uuid,
+        name -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
     }
 }
 
@@ -21,9 +118,25 @@ diesel::table! {
     }
 }
 
+diesel::joinable!(api_key_revocations -> api_keys (api_key_id));
+diesel::joinable!(api_key_revocations -> users (user_id));
 diesel::joinable!(api_keys -> users (user_id));
+diesel::joinable!(cli_session_refresh_tokens -> cli_sessions (cli_session_id));
+diesel::joinable!(cli_sessions -> users (user_id));
+diesel::joinable!(identities -> users (user_id));
+diesel::joinable!(organization_invitations -> organizations (organization_id));
+diesel::joinable!(organization_invitations -> users (invited_by));
+diesel::joinable!(organization_users -> organizations (organization_id));
+diesel::joinable!(organization_users -> users (user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    api_key_revocations,
     api_keys,
+    cli_session_refresh_tokens,
+    cli_sessions,
+    identities,
+    organization_invitations,
+    organization_users,
+    organizations,
     users,
 );
@@ -28,6 +28,12 @@ pub enum ServiceError {
 
     #[display(fmt = "Not Found")]
     NotFound,
+
+    #[display(fmt = "IP Not Allowed")]
+    IpNotAllowed,
+
+    #[display(fmt = "Auth Unavailable")]
+    AuthUnavailable,
 }
 
 // impl ResponseError trait allows to convert our errors into http responses with appropriate data
@@ -53,6 +59,14 @@ impl ResponseError for ServiceError {
             ServiceError::NotFound => HttpResponse::NotFound().json(ErrorRespPayload {
                 message: "Record not found".to_string()
             }),
+            ServiceError::IpNotAllowed => HttpResponse::Forbidden().json(ErrorRespPayload {
+                message: "The api key is not allowed to be used from this IP address".to_string(),
+            }),
+            ServiceError::AuthUnavailable => HttpResponse::ServiceUnavailable()
+                .insert_header(("Retry-After", "5"))
+                .json(ErrorRespPayload {
+                    message: "Authentication is unavailable because the OpenID provider or the session store cannot be reached, try again later".to_string(),
+                }),
         }
     }
 }
@@ -1,24 +1,112 @@
-use super::auth_handler::AuthedUser;
-use crate::{data::models::PgPool, operators::api_key_operator::create_api_key_query};
-use actix_web::{web, HttpResponse};
+use super::auth_handler::{ApiKeyReadScope, ApiKeyWriteScope, ScopedUser};
+use crate::{
+    data::models::{ApiKey, ApiKeyRespBody, ApiKeyScope, LeakedApiKey, PgPool},
+    errors::ServiceError,
+    middleware::auth_middleware::client_ip,
+    operators::api_key_operator::{
+        create_api_key_query, delete_api_key_query, get_api_key_by_id_query,
+        get_api_keys_for_user_query, parse_cidr, revoke_leaked_api_keys_query,
+        rotate_api_key_query, verify_leaked_api_key_report_query,
+    },
+};
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use ipnet::IpNet;
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateApiKeyReqPayload {
     /// The name which will be assigned to the new api key.
     name: String,
+    /// The time (UTC) at which the api key will stop authenticating. If not provided, the key does not expire unless the server enforces a maximum lifetime.
+    expires_at: Option<chrono::NaiveDateTime>,
+    /// The scopes granted to the new api key. Must be non-empty and a subset of the scopes of the credential used to create it.
+    scopes: Vec<ApiKeyScope>,
+    /// The CIDRs (e.g. `203.0.113.0/24`) the api key can be used from. Requests from other addresses are rejected with a 403. If not provided, the key can be used from any address.
+    allowed_cidrs: Option<Vec<String>>,
 }
 
 #[derive(Serialize, Deserialize, ToSchema)]
 pub struct CreateApiKeyRespPayload {
     /// The api key which was created. This is the value which should be used in the Authorization header.
     api_key: String,
+    /// The time (UTC) at which the api key will stop authenticating. Null if the key does not expire.
+    expires_at: Option<chrono::NaiveDateTime>,
+    /// The scopes granted to the api key.
+    scopes: Vec<ApiKeyScope>,
+    /// The CIDRs the api key can be used from. Empty if the key can be used from any address.
+    allowed_cidrs: Vec<String>,
+}
+
+/// Resolve the expiry for a new api key. When `API_KEY_MAX_LIFETIME_DAYS` is set every key must
+/// expire within that many days, and keys created without an expiry get the maximum lifetime.
+pub fn resolve_api_key_expiry(
+    expires_at: Option<chrono::NaiveDateTime>,
+) -> Result<Option<chrono::NaiveDateTime>, ServiceError> {
+    let now = chrono::Utc::now().naive_utc();
+
+    if let Some(expires_at) = expires_at {
+        if expires_at <= now {
+            return Err(ServiceError::BadRequest(
+                "expires_at must be in the future".to_string(),
+            ));
+        }
+    }
+
+    let max_lifetime_days = match std::env::var("API_KEY_MAX_LIFETIME_DAYS") {
+        Ok(days) => days.parse::<i64>().map_err(|_| {
+            ServiceError::InternalServerError("API_KEY_MAX_LIFETIME_DAYS must be an integer".into())
+        })?,
+        Err(_) => return Ok(expires_at),
+    };
+    let max_expires_at = now + chrono::Duration::days(max_lifetime_days);
+
+    match expires_at {
+        Some(expires_at) if expires_at > max_expires_at => Err(ServiceError::BadRequest(format!(
+            "expires_at must be within {} days",
+            max_lifetime_days
+        ))),
+        Some(expires_at) => Ok(Some(expires_at)),
+        None => Ok(Some(max_expires_at)),
+    }
+}
+
+/// Check that an api key creating another key does not escape its own restrictions. Keys
+/// restricted to CIDRs can only create keys restricted to CIDRs within them, and keys with an
+/// expiry can only create keys expiring no later than themselves.
+pub fn check_can_create_api_key(
+    caller_api_key: Option<&ApiKey>,
+    expires_at: Option<chrono::NaiveDateTime>,
+    allowed_cidrs: &[IpNet],
+) -> Result<(), ServiceError> {
+    let Some(caller_api_key) = caller_api_key else {
+        return Ok(());
+    };
+
+    let caller_cidrs = caller_api_key.api_key_allowed_cidrs();
+    if !caller_cidrs.is_empty()
+        && (allowed_cidrs.is_empty()
+            || !allowed_cidrs.iter().all(|cidr| {
+                caller_cidrs
+                    .iter()
+                    .any(|caller_cidr| caller_cidr.contains(cidr))
+            }))
+    {
+        return Err(ServiceError::Forbidden);
+    }
+
+    if let Some(caller_expires_at) = caller_api_key.expires_at {
+        if expires_at.is_none_or(|expires_at| expires_at > caller_expires_at) {
+            return Err(ServiceError::Forbidden);
+        }
+    }
+
+    Ok(())
 }
 
 /// Set User Api Key
 ///
-/// Create a new api key for the auth'ed user. Successful response will contain the newly created api key. The api key will have permission level of the auth'ed user who calls this endpoint.
+/// Create a new api key for the auth'ed user. Successful response will contain the newly created api key. The api key can only act on behalf of the auth'ed user within the scopes it was granted. Expired api keys and keys used from outside their allowed CIDRs are rejected by the server. Api keys restricted to CIDRs or with an expiry can only create keys restricted to CIDRs within theirs and expiring no later than them.
 #[utoipa::path(
   post,
   path = "/api_key",
@@ -27,23 +115,551 @@ pub struct CreateApiKeyRespPayload {
   request_body(content = CreateApiKeyReqPayload, description = "JSON request payload to create a new user api key", content_type = "application/json"),
   responses(
       (status = 201, description = "JSON body representing the api_key for the user", body = CreateApiKeyRespPayload),
+      (status = 400, description = "Service error relating to an invalid expiry, scopes or allowed CIDRs for the api_key", body = ErrorRespPayload),
       (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+      (status = 403, description = "The credential used does not have the api_key:write scope or one of the requested scopes, or the new key would be less restricted than it", body = ErrorRespPayload),
       (status = 500, description = "Service error relating to creating api_key for the user", body = ErrorRespPayload),
   ),
   security(
-      ("ApiKey" = ["readonly"]),
+      ("ApiKey" = ["api_key:write"]),
+      ("Bearer" = []),
   )
 )]
-#[tracing::instrument(skip(pg_pool))]
+#[tracing::instrument(skip(pg_pool, req))]
 pub async fn create_api_key(
-    authed_user: AuthedUser,
+    authed_user: ScopedUser<ApiKeyWriteScope>,
     req_payload: web::Json<CreateApiKeyReqPayload>,
+    req: HttpRequest,
     pg_pool: web::Data<PgPool>,
 ) -> Result<HttpResponse, actix_web::Error> {
     let user_id = authed_user.id;
     let name = req_payload.name.clone();
+    let expires_at = resolve_api_key_expiry(req_payload.expires_at)?;
+
+    let scopes = ApiKeyScope::ALL
+        .into_iter()
+        .filter(|scope| req_payload.scopes.contains(scope))
+        .collect::<Vec<ApiKeyScope>>();
+    if scopes.is_empty() {
+        return Err(ServiceError::BadRequest("scopes must not be empty".to_string()).into());
+    }
+    if scopes
+        .iter()
+        .any(|scope| !authed_user.scopes.contains(scope))
+    {
+        return Err(ServiceError::Forbidden.into());
+    }
+
+    let allowed_cidrs = req_payload
+        .allowed_cidrs
+        .iter()
+        .flatten()
+        .map(|cidr| parse_cidr(cidr))
+        .collect::<Result<Vec<_>, ServiceError>>()?;
+
+    let caller_api_key = req.extensions().get::<ApiKey>().cloned();
+    check_can_create_api_key(caller_api_key.as_ref(), expires_at, &allowed_cidrs)?;
+
+    let api_key = create_api_key_query(
+        user_id,
+        name,
+        expires_at,
+        scopes.clone(),
+        allowed_cidrs.clone(),
+        pg_pool,
+    )
+    .await?;
+
+    Ok(HttpResponse::Created().json(CreateApiKeyRespPayload {
+        api_key,
+        expires_at,
+        scopes,
+        allowed_cidrs: allowed_cidrs.iter().map(|cidr| cidr.to_string()).collect(),
+    }))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetApiKeysQuery {
+    /// Page of api keys to fetch. Pages are 1-indexed and default to 1.
+    pub page: Option<u64>,
+    /// Number of api keys per page. Defaults to 10, max 100.
+    pub page_size: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct GetApiKeysRespPayload {
+    /// The api keys on the requested page. Key hashes are never returned.
+    api_keys: Vec<ApiKeyRespBody>,
+    /// Total number of pages of api keys for the auth'ed user.
+    total_pages: i64,
+}
+
+/// Get User Api Keys
+///
+/// Get a paginated list of the api keys belonging to the auth'ed user. The secret value of the keys is never returned.
+#[utoipa::path(
+  get,
+  path = "/api_key",
+  context_path = "/api",
+  tag = "api_key",
+  params(GetApiKeysQuery),
+  responses(
+      (status = 200, description = "JSON body containing a page of the auth'ed user's api keys", body = GetApiKeysRespPayload),
+      (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+      (status = 403, description = "The credential used does not have the api_key:read scope", body = ErrorRespPayload),
+  ),
+  security(
+      ("ApiKey" = ["api_key:read"]),
+      ("Bearer" = []),
+  )
+)]
+#[tracing::instrument(skip(pg_pool))]
+pub async fn get_api_keys(
+    authed_user: ScopedUser<ApiKeyReadScope>,
+    query: web::Query<GetApiKeysQuery>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    // Capped so that the offset of the page can not overflow with the largest page size
+    let page = query.page.unwrap_or(1).clamp(1, i64::MAX as u64 / 100) as i64;
+    let page_size = query.page_size.unwrap_or(10).clamp(1, 100) as i64;
+
+    let (api_keys, total_count) =
+        get_api_keys_for_user_query(authed_user.id, page, page_size, pg_pool).await?;
+
+    Ok(HttpResponse::Ok().json(GetApiKeysRespPayload {
+        api_keys: api_keys.into_iter().map(ApiKeyRespBody::from).collect(),
+        total_pages: (total_count + page_size - 1) / page_size,
+    }))
+}
+
+/// Get User Api Key
+///
+/// Get a single api key belonging to the auth'ed user by its id. The secret value of the key is never returned.
+#[utoipa::path(
+  get,
+  path = "/api_key/{api_key_id}",
+  context_path = "/api",
+  tag = "api_key",
+  params(
+      ("api_key_id" = uuid::Uuid, Path, description = "The id of the api key to fetch"),
+  ),
+  responses(
+      (status = 200, description = "JSON body representing the api key", body = ApiKeyRespBody),
+      (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+      (status = 403, description = "The credential used does not have the api_key:read scope", body = ErrorRespPayload),
+      (status = 404, description = "The api key does not exist or does not belong to the auth'ed user", body = ErrorRespPayload),
+  ),
+  security(
+      ("ApiKey" = ["api_key:read"]),
+      ("Bearer" = []),
+  )
+)]
+#[tracing::instrument(skip(pg_pool))]
+pub async fn get_api_key(
+    authed_user: ScopedUser<ApiKeyReadScope>,
+    api_key_id: web::Path<uuid::Uuid>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let api_key = get_api_key_by_id_query(api_key_id.into_inner(), authed_user.id, pg_pool).await?;
+
+    Ok(HttpResponse::Ok().json(ApiKeyRespBody::from(api_key)))
+}
+
+/// Delete User Api Key
+///
+/// Revoke an api key belonging to the auth'ed user. The key will stop authenticating immediately.
+#[utoipa::path(
+  delete,
+  path = "/api_key/{api_key_id}",
+  context_path = "/api",
+  tag = "api_key",
+  params(
+      ("api_key_id" = uuid::Uuid, Path, description = "The id of the api key to delete"),
+  ),
+  responses(
+      (status = 204, description = "Confirmation that the api key was deleted"),
+      (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+      (status = 403, description = "The credential used does not have the api_key:write scope", body = ErrorRespPayload),
+      (status = 404, description = "The api key does not exist or does not belong to the auth'ed user", body = ErrorRespPayload),
+  ),
+  security(
+      ("ApiKey" = ["api_key:write"]),
+      ("Bearer" = []),
+  )
+)]
+#[tracing::instrument(skip(pg_pool))]
+pub async fn delete_api_key(
+    authed_user: ScopedUser<ApiKeyWriteScope>,
+    api_key_id: web::Path<uuid::Uuid>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    delete_api_key_query(api_key_id.into_inner(), authed_user.id, pg_pool).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Get Current Api Key
+///
+/// Get the api key used to authenticate this request. Requests authenticated with a cookie instead of an api key will receive a 404.
+#[utoipa::path(
+  get,
+  path = "/api_key/current",
+  context_path = "/api",
+  tag = "api_key",
+  responses(
+      (status = 200, description = "JSON body representing the api key used for this request", body = ApiKeyRespBody),
+      (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+      (status = 403, description = "The credential used does not have the api_key:read scope", body = ErrorRespPayload),
+      (status = 404, description = "The request was not authenticated with an api key", body = ErrorRespPayload),
+  ),
+  security(
+      ("ApiKey" = ["api_key:read"]),
+      ("Bearer" = []),
+  )
+)]
+#[tracing::instrument(skip(req))]
+pub async fn get_current_api_key(
+    _authed_user: ScopedUser<ApiKeyReadScope>,
+    req: HttpRequest,
+) -> Result<HttpResponse, actix_web::Error> {
+    let api_key = req
+        .extensions()
+        .get::<ApiKey>()
+        .cloned()
+        .ok_or(ServiceError::NotFound)?;
+
+    Ok(HttpResponse::Ok().json(ApiKeyRespBody::from(api_key)))
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RotateApiKeyReqPayload {
+    /// Number of seconds the previous secret keeps authenticating after the rotation. Defaults to `API_KEY_ROTATION_GRACE_PERIOD_SECONDS` or one hour, and must not exceed `API_KEY_ROTATION_MAX_GRACE_PERIOD_SECONDS` (one week by default).
+    grace_period_seconds: Option<u64>,
+}
+
+/// Resolve when the previous secret of a rotated api key stops authenticating. Grace periods
+/// longer than `API_KEY_ROTATION_MAX_GRACE_PERIOD_SECONDS` are rejected.
+pub fn resolve_rotation_grace_period(
+    grace_period_seconds: Option<u64>,
+) -> Result<chrono::NaiveDateTime, ServiceError> {
+    let max_grace_period_seconds = std::env::var("API_KEY_ROTATION_MAX_GRACE_PERIOD_SECONDS")
+        .ok()
+        .and_then(|seconds| seconds.parse::<u64>().ok())
+        .unwrap_or(7 * 24 * 3600);
+
+    let grace_period_seconds = match grace_period_seconds {
+        Some(grace_period_seconds) if grace_period_seconds > max_grace_period_seconds => {
+            return Err(ServiceError::BadRequest(format!(
+                "grace_period_seconds must not exceed {}",
+                max_grace_period_seconds
+            )));
+        }
+        Some(grace_period_seconds) => grace_period_seconds,
+        None => std::env::var("API_KEY_ROTATION_GRACE_PERIOD_SECONDS")
+            .ok()
+            .and_then(|seconds| seconds.parse::<u64>().ok())
+            .unwrap_or(3600)
+            .min(max_grace_period_seconds),
+    };
+
+    i64::try_from(grace_period_seconds)
+        .ok()
+        .and_then(chrono::Duration::try_seconds)
+        .and_then(|grace_period| {
+            chrono::Utc::now()
+                .naive_utc()
+                .checked_add_signed(grace_period)
+        })
+        .ok_or(ServiceError::BadRequest(
+            "grace_period_seconds is out of range".to_string(),
+        ))
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct RotateApiKeyRespPayload {
+    /// The new secret for the api key. This is the value which should be used in the Authorization header from now on.
+    api_key: String,
+    /// The time (UTC) at which the new secret will stop authenticating. Null if the key does not expire.
+    expires_at: Option<chrono::NaiveDateTime>,
+    /// The time (UTC) until which the previous secret keeps authenticating.
+    previous_key_expires_at: chrono::NaiveDateTime,
+}
+
+/// Check that the credential rotating `target` is at least as powerful as it, since the rotation
+/// hands out a new secret for `target`. Api keys restricted to CIDRs or with an expiry cannot rotate
+/// keys without those restrictions.
+pub fn check_can_rotate_api_key(
+    caller_scopes: &[ApiKeyScope],
+    caller_api_key: Option<&ApiKey>,
+    target: &ApiKey,
+) -> Result<(), ServiceError> {
+    if target
+        .api_key_scopes()
+        .iter()
+        .any(|scope| !caller_scopes.contains(scope))
+    {
+        return Err(ServiceError::Forbidden);
+    }
+
+    if let Some(caller_api_key) = caller_api_key {
+        if !caller_api_key.api_key_allowed_cidrs().is_empty()
+            && target.api_key_allowed_cidrs().is_empty()
+        {
+            return Err(ServiceError::Forbidden);
+        }
+        if caller_api_key.expires_at.is_some() && target.expires_at.is_none() {
+            return Err(ServiceError::Forbidden);
+        }
+    }
+
+    Ok(())
+}
+
+/// Rotate User Api Key
+///
+/// Replace the secret of an api key belonging to the auth'ed user. The previous secret keeps authenticating until the end of the grace period so that running jobs can be updated without downtime. Rotating a key again ends the grace period of the secret before it. The credential used must have every scope of the rotated key, and api keys restricted to CIDRs or with an expiry can only rotate keys which are restricted as well.
+#[utoipa::path(
+  post,
+  path = "/api_key/{api_key_id}/rotate",
+  context_path = "/api",
+  tag = "api_key",
+  params(
+      ("api_key_id" = uuid::Uuid, Path, description = "The id of the api key to rotate"),
+  ),
+  request_body(content = RotateApiKeyReqPayload, description = "JSON request payload to rotate a user api key", content_type = "application/json"),
+  responses(
+      (status = 200, description = "JSON body containing the new secret and the validity of both secrets", body = RotateApiKeyRespPayload),
+      (status = 400, description = "Service error relating to a grace period longer than the server allows", body = ErrorRespPayload),
+      (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+      (status = 403, description = "The credential used does not have the api_key:write scope or is less powerful than the api key", body = ErrorRespPayload),
+      (status = 404, description = "The api key does not exist or does not belong to the auth'ed user", body = ErrorRespPayload),
+  ),
+  security(
+      ("ApiKey" = ["api_key:write"]),
+      ("Bearer" = []),
+  )
+)]
+#[tracing::instrument(skip(pg_pool, req))]
+pub async fn rotate_api_key(
+    authed_user: ScopedUser<ApiKeyWriteScope>,
+    api_key_id: web::Path<uuid::Uuid>,
+    req_payload: web::Json<RotateApiKeyReqPayload>,
+    req: HttpRequest,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let api_key_id = api_key_id.into_inner();
+    let previous_key_expires_at = resolve_rotation_grace_period(req_payload.grace_period_seconds)?;
+
+    let target = get_api_key_by_id_query(api_key_id, authed_user.id, pg_pool.clone()).await?;
+    let caller_api_key = req.extensions().get::<ApiKey>().cloned();
+    check_can_rotate_api_key(&authed_user.scopes, caller_api_key.as_ref(), &target)?;
+
+    let (api_key, api_key_row) =
+        rotate_api_key_query(api_key_id, authed_user.id, previous_key_expires_at, pg_pool).await?;
+
+    let previous_key_expires_at = match api_key_row.expires_at {
+        Some(expires_at) if expires_at < previous_key_expires_at => expires_at,
+        _ => previous_key_expires_at,
+    };
+
+    Ok(HttpResponse::Ok().json(RotateApiKeyRespPayload {
+        api_key,
+        expires_at: api_key_row.expires_at,
+        previous_key_expires_at,
+    }))
+}
+
+const MAX_LEAKED_API_KEYS_PER_REPORT: usize = 100;
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct RevokeLeakedApiKeysRespPayload {
+    /// The number of reported api keys which were received. This does not reveal whether any of them were valid.
+    received: usize,
+}
+
+/// Revoke Leaked Api Keys
+///
+/// Report api keys which were exposed publicly. Only accepts reports of secret scanning partners, signed like GitHub secret scanning alerts: the Github-Public-Key-Signature header holds an ECDSA signature of the body made with the key named in Github-Public-Key-Identifier, published at `LEAKED_API_KEY_REPORTER_KEYS_URL`. Every reported key that matches an active api key is revoked immediately and the report is recorded. The response is the same whether or not any key matched, so this endpoint cannot be used to check if a key is valid. At most 100 keys can be reported at once.
+#[utoipa::path(
+  post,
+  path = "/api_key/revoke-leaked",
+  context_path = "/api",
+  tag = "api_key",
+  params(
+      ("Github-Public-Key-Identifier" = String, Header, description = "Identifier of the public key the report was signed with"),
+      ("Github-Public-Key-Signature" = String, Header, description = "Base64 encoded ECDSA signature of the SHA-256 of the request body"),
+  ),
+  request_body(content = Vec<LeakedApiKey>, description = "JSON array of the api keys which were found", content_type = "application/json"),
+  responses(
+      (status = 200, description = "The report was received", body = RevokeLeakedApiKeysRespPayload),
+      (status = 400, description = "Service error relating to the report being malformed or containing too many api keys", body = ErrorRespPayload),
+      (status = 401, description = "The report is not signed by a secret scanning partner", body = ErrorRespPayload),
+  ),
+)]
+#[tracing::instrument(skip_all)]
+pub async fn revoke_leaked_api_keys(
+    req: HttpRequest,
+    body: web::Bytes,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let header = |name: &str| {
+        req.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    };
+    let (Some(key_identifier), Some(signature)) = (
+        header("Github-Public-Key-Identifier"),
+        header("Github-Public-Key-Signature"),
+    ) else {
+        return Err(ServiceError::Unauthorized.into());
+    };
+    verify_leaked_api_key_report_query(&body, key_identifier, signature).await?;
+
+    let leaked_api_keys = serde_json::from_slice::<Vec<LeakedApiKey>>(&body).map_err(|_| {
+        ServiceError::BadRequest("The report must be a JSON array of api keys".to_string())
+    })?;
+    if leaked_api_keys.len() > MAX_LEAKED_API_KEYS_PER_REPORT {
+        return Err(ServiceError::BadRequest(format!(
+            "At most {} api keys can be reported at once",
+            MAX_LEAKED_API_KEYS_PER_REPORT
+        ))
+        .into());
+    }
+
+    let received = leaked_api_keys.len();
+    let reporter_user_agent = req
+        .headers()
+        .get("User-Agent")
+        .and_then(|user_agent| user_agent.to_str().ok())
+        .map(|user_agent| user_agent.to_string());
+
+    revoke_leaked_api_keys_query(
+        leaked_api_keys,
+        client_ip(&req).map(|ip| ip.to_string()),
+        reporter_user_agent,
+        pg_pool,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(RevokeLeakedApiKeysRespPayload { received }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_key(
+        scopes: Vec<ApiKeyScope>,
+        allowed_cidrs: Vec<&str>,
+        expires_at: Option<chrono::NaiveDateTime>,
+    ) -> ApiKey {
+        ApiKey::from_details(
+            uuid::Uuid::new_v4(),
+            "key".to_string(),
+            "key_id".to_string(),
+            "hash".to_string(),
+            expires_at,
+            scopes,
+            allowed_cidrs
+                .into_iter()
+                .map(|cidr| parse_cidr(cidr).unwrap())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn keys_cannot_rotate_more_powerful_keys() {
+        let caller_scopes = vec![ApiKeyScope::ApiKeyRead, ApiKeyScope::ApiKeyWrite];
+        let caller = api_key(caller_scopes.clone(), vec![], None);
+
+        let weaker = api_key(vec![ApiKeyScope::ApiKeyRead], vec![], None);
+        assert!(check_can_rotate_api_key(&caller_scopes, Some(&caller), &weaker).is_ok());
+        assert!(check_can_rotate_api_key(&caller_scopes, Some(&caller), &caller).is_ok());
+
+        let stronger = api_key(ApiKeyScope::ALL.to_vec(), vec![], None);
+        assert!(matches!(
+            check_can_rotate_api_key(&caller_scopes, Some(&caller), &stronger),
+            Err(ServiceError::Forbidden)
+        ));
+    }
+
+    #[test]
+    fn restricted_keys_cannot_rotate_unrestricted_keys() {
+        let scopes = ApiKeyScope::ALL.to_vec();
+        let expires_at = Some(chrono::Utc::now().naive_utc() + chrono::Duration::days(1));
+        let unrestricted = api_key(scopes.clone(), vec![], None);
+
+        let cidr_restricted = api_key(scopes.clone(), vec!["10.0.0.0/8"], None);
+        assert!(matches!(
+            check_can_rotate_api_key(&scopes, Some(&cidr_restricted), &unrestricted),
+            Err(ServiceError::Forbidden)
+        ));
+        assert!(
+            check_can_rotate_api_key(&scopes, Some(&cidr_restricted), &cidr_restricted).is_ok()
+        );
+
+        let expiring = api_key(scopes.clone(), vec![], expires_at);
+        assert!(matches!(
+            check_can_rotate_api_key(&scopes, Some(&expiring), &unrestricted),
+            Err(ServiceError::Forbidden)
+        ));
+        assert!(check_can_rotate_api_key(&scopes, Some(&expiring), &expiring).is_ok());
+
+        // Credentials which are not api keys only need the scopes
+        assert!(check_can_rotate_api_key(&scopes, None, &unrestricted).is_ok());
+    }
+
+    #[test]
+    fn cidr_restricted_keys_can_only_create_keys_within_their_cidrs() {
+        let caller = api_key(ApiKeyScope::ALL.to_vec(), vec!["10.0.0.0/8"], None);
+        let cidrs = |cidrs: &[&str]| {
+            cidrs
+                .iter()
+                .map(|cidr| parse_cidr(cidr).unwrap())
+                .collect::<Vec<IpNet>>()
+        };
+
+        assert!(check_can_create_api_key(Some(&caller), None, &cidrs(&["10.1.0.0/16"])).is_ok());
+        assert!(check_can_create_api_key(Some(&caller), None, &cidrs(&["10.0.0.0/8"])).is_ok());
+        for allowed_cidrs in [
+            vec![],
+            cidrs(&["0.0.0.0/0"]),
+            cidrs(&["10.1.0.0/16", "192.168.0.1"]),
+        ] {
+            assert!(
+                matches!(
+                    check_can_create_api_key(Some(&caller), None, &allowed_cidrs),
+                    Err(ServiceError::Forbidden)
+                ),
+                "{:?}",
+                allowed_cidrs
+            );
+        }
+
+        // Credentials which are not api keys can create unrestricted keys
+        assert!(check_can_create_api_key(None, None, &[]).is_ok());
+    }
 
-    let api_key = create_api_key_query(user_id, name, pg_pool).await?;
+    #[test]
+    fn expiring_keys_can_only_create_keys_expiring_no_later() {
+        let now = chrono::Utc::now().naive_utc();
+        let caller_expires_at = now + chrono::Duration::days(1);
+        let caller = api_key(ApiKeyScope::ALL.to_vec(), vec![], Some(caller_expires_at));
 
-    Ok(HttpResponse::Created().json(CreateApiKeyRespPayload { api_key }))
+        assert!(check_can_create_api_key(
+            Some(&caller),
+            Some(now + chrono::Duration::hours(1)),
+            &[]
+        )
+        .is_ok());
+        assert!(check_can_create_api_key(Some(&caller), Some(caller_expires_at), &[]).is_ok());
+        assert!(matches!(
+            check_can_create_api_key(Some(&caller), None, &[]),
+            Err(ServiceError::Forbidden)
+        ));
+        assert!(matches!(
+            check_can_create_api_key(Some(&caller), Some(now + chrono::Duration::days(2)), &[]),
+            Err(ServiceError::Forbidden)
+        ));
+    }
 }
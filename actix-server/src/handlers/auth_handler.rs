@@ -1,23 +1,45 @@
-use crate::data::models::RedisPool;
-use crate::operators::user_operator::create_user_query;
+use crate::data::models::{ApiKeyScope, AuthedScopes, CookieIdentity, RedisPool, UserIdentity};
+use crate::middleware::auth_middleware::client_ip;
+use crate::operators::session_operator::{
+    create_oidc_session_query, end_oidc_sessions_query, end_user_session_query,
+    record_logout_token_query,
+};
+use crate::operators::user_operator::{
+    delete_identity_query, get_identities_for_user_query, invalidate_user_cache_query,
+    link_identity_query, upsert_user_for_identity_query,
+};
 use crate::{
     data::models::{PgPool, User},
     errors::ServiceError,
-    operators::user_operator::get_user_by_id_query,
 };
 use actix_identity::Identity;
+use actix_session::Session;
 use actix_web::{web, Error, FromRequest, HttpMessage as _, HttpRequest, HttpResponse};
-use bb8_redis::redis::AsyncCommands;
+use base64::Engine as _;
+use bb8_redis::redis::{self, AsyncCommands};
 use oauth2::reqwest::async_http_client;
 use oauth2::{
     AuthUrl, AuthorizationCode, ClientSecret, CsrfToken, PkceCodeChallenge, PkceCodeVerifier,
     RedirectUrl, Scope, TokenResponse,
 };
-use openidconnect::core::{CoreAuthenticationFlow, CoreClient, CoreProviderMetadata};
-use openidconnect::{AccessTokenHash, ClientId, IssuerUrl, Nonce};
+use openidconnect::core::{
+    CoreAuthenticationFlow, CoreClient, CoreGenderClaim, CoreIdToken, CoreJsonWebKeySet,
+    CoreJsonWebKeyType, CoreJsonWebKeyUse, CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+};
+use openidconnect::{
+    AccessTokenHash, AdditionalClaims, ClaimsVerificationError, ClientId, EndSessionUrl, IdToken,
+    IdTokenClaims, IssuerUrl, JsonWebKey, JsonWebKeyId, JwsSigningAlgorithm, LanguageTag,
+    LocalizedClaim, Nonce, NonceVerifier, PostLogoutRedirectUrl, ProviderMetadataWithLogout,
+    SignatureVerificationError, SubjectIdentifier,
+};
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use std::collections::HashMap;
 use std::fs::read_to_string;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 use utoipa::{IntoParams, ToSchema};
 
 pub type AuthedUser = User;
@@ -36,72 +58,1060 @@ impl FromRequest for AuthedUser {
         )
     }
 }
+
+/// Marker for a scope that a handler requires through [`ScopedUser`].
+pub trait RequiredScope {
+    const SCOPE: ApiKeyScope;
+}
+
+pub struct ApiKeyReadScope;
+pub struct ApiKeyWriteScope;
+pub struct UserReadScope;
+pub struct UserWriteScope;
+
+impl RequiredScope for ApiKeyReadScope {
+    const SCOPE: ApiKeyScope = ApiKeyScope::ApiKeyRead;
+}
+
+impl RequiredScope for ApiKeyWriteScope {
+    const SCOPE: ApiKeyScope = ApiKeyScope::ApiKeyWrite;
+}
+
+impl RequiredScope for UserReadScope {
+    const SCOPE: ApiKeyScope = ApiKeyScope::UserRead;
+}
+
+impl RequiredScope for UserWriteScope {
+    const SCOPE: ApiKeyScope = ApiKeyScope::UserWrite;
+}
+
+/// An [`AuthedUser`] whose credential was granted the scope `S`. Extraction fails with
+/// `Unauthorized` when the request is not authenticated and `Forbidden` when the scope is missing.
+pub struct ScopedUser<S: RequiredScope> {
+    pub user: AuthedUser,
+    pub scopes: Vec<ApiKeyScope>,
+    _scope: PhantomData<S>,
+}
+
+impl<S: RequiredScope> std::fmt::Debug for ScopedUser<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScopedUser")
+            .field("user", &self.user)
+            .field("scopes", &self.scopes)
+            .finish()
+    }
+}
+
+impl<S: RequiredScope> Deref for ScopedUser<S> {
+    type Target = AuthedUser;
+
+    fn deref(&self) -> &Self::Target {
+        &self.user
+    }
+}
+
+impl<S: RequiredScope> FromRequest for ScopedUser<S> {
+    type Error = actix_web::Error;
+    type Future = std::future::Ready<Result<ScopedUser<S>, actix_web::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let extensions = req.extensions();
+        let result = match (
+            extensions.get::<AuthedUser>(),
+            extensions.get::<AuthedScopes>(),
+        ) {
+            (Some(user), Some(AuthedScopes(scopes))) if scopes.contains(&S::SCOPE) => {
+                Ok(ScopedUser {
+                    user: user.clone(),
+                    scopes: scopes.clone(),
+                    _scope: PhantomData,
+                })
+            }
+            (Some(_), _) => Err(ServiceError::Forbidden.into()),
+            (None, _) => Err(ServiceError::Unauthorized.into()),
+        };
+
+        std::future::ready(result)
+    }
+}
 #[derive(Deserialize, Debug, IntoParams)]
 pub struct OpCallback {
     pub state: String,
-    pub session_state: String,
+    pub session_state: Option<String>,
     pub code: String,
 }
 
-#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
-struct AFClaims {}
+/// Session key under which the ID token of the current login is kept, sent as `id_token_hint`
+/// when logging out at the OpenID provider.
+const ID_TOKEN_SESSION_KEY: &str = "id_token";
 
-#[tracing::instrument]
-pub async fn build_oidc_client() -> CoreClient {
-    let issuer_url =
-        std::env::var("OIDC_ISSUER_URL").expect("Issuer URL for OpenID provider must be set");
-    let client_id =
-        std::env::var("OIDC_CLIENT_ID").expect("Client ID for OpenID provider must be set");
-    let auth_redirect_url = std::env::var("OIDC_AUTH_REDIRECT_URL")
-        .expect("Auth redirect URL for OpenID provider must be set");
-    let client_secret =
-        std::env::var("OIDC_CLIENT_SECRET").expect("Client secret for OpenID provider must be set");
-    let base_server_url =
-        std::env::var("BASE_SERVER_URL").expect("Server hostname for OpenID provider must be set");
-
-    //build OpenId Connect client
-    let meta_data = CoreProviderMetadata::discover_async(
-        IssuerUrl::new(issuer_url.clone()).expect("IssuerUrl for OpenID provider must be set"),
-        async_http_client,
-    )
-    .await
-    .expect("Failed to discover OpenID provider");
-
-    CoreClient::new(
-        ClientId::new(client_id.clone()),
-        Some(ClientSecret::new(client_secret.clone())),
-        IssuerUrl::new(issuer_url.clone()).expect("IssuerUrl for OpenID provider must be set"),
-        AuthUrl::new(auth_redirect_url.clone()).expect("Auth configuration is not a valid URL"),
-        meta_data.token_endpoint().cloned(),
-        meta_data.userinfo_endpoint().cloned(),
-        meta_data.jwks().to_owned(),
-    )
-    .set_redirect_uri(
-        RedirectUrl::new(format!("{}/api/auth/callback", base_server_url))
+/// Session key under which the name of the OpenID provider the user logged in with is kept.
+pub const OIDC_PROVIDER_SESSION_KEY: &str = "oidc_provider";
+
+const BACKCHANNEL_LOGOUT_EVENT: &str = "http://schemas.openid.net/event/backchannel-logout";
+
+type ExtendedIdToken<AC> = IdToken<
+    AC,
+    CoreGenderClaim,
+    CoreJweContentEncryptionAlgorithm,
+    CoreJwsSigningAlgorithm,
+    CoreJsonWebKeyType,
+>;
+
+/// The provider session id, which `CoreIdToken` does not expose.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionIdClaims {
+    pub sid: Option<String>,
+}
+impl AdditionalClaims for SessionIdClaims {}
+
+/// Longest a logout token without `exp` is accepted after it was issued.
+const LOGOUT_TOKEN_MAX_AGE_SECONDS: i64 = 120;
+
+/// How far the clock of an OpenID provider may be ahead when it issues a logout token.
+const LOGOUT_TOKEN_CLOCK_SKEW_SECONDS: i64 = 60;
+
+#[derive(Debug, Deserialize)]
+struct JwtHeader {
+    alg: CoreJwsSigningAlgorithm,
+    kid: Option<JsonWebKeyId>,
+    typ: Option<String>,
+    crit: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum JwtAudiences {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl JwtAudiences {
+    fn as_slice(&self) -> &[String] {
+        match self {
+            JwtAudiences::One(aud) => std::slice::from_ref(aud),
+            JwtAudiences::Many(auds) => auds.as_slice(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AccessTokenClaims {
+    iss: String,
+    sub: String,
+    aud: JwtAudiences,
+    exp: i64,
+    nbf: Option<i64>,
+    /// Marks access tokens of providers which do not set the `at+jwt` type, e.g. Cognito.
+    token_use: Option<String>,
+}
+
+/// The claims of a logout token as defined by OpenID Connect Back-Channel Logout 1.0. Unlike an
+/// ID token it may identify only the provider session (`sid`) without a subject.
+#[derive(Debug, Deserialize)]
+pub struct LogoutTokenClaims {
+    iss: String,
+    pub sub: Option<String>,
+    pub sid: Option<String>,
+    aud: JwtAudiences,
+    iat: i64,
+    exp: Option<i64>,
+    pub jti: String,
+    events: HashMap<String, serde_json::Value>,
+    /// Must be absent, a logout token carrying a nonce could be an ID token.
+    nonce: Option<serde_json::Value>,
+}
+
+impl LogoutTokenClaims {
+    /// When the token stops being accepted, the expiry or the issue time plus the maximum age.
+    pub fn expires_at(&self) -> i64 {
+        self.exp
+            .unwrap_or(self.iat.saturating_add(LOGOUT_TOKEN_MAX_AGE_SECONDS))
+    }
+}
+
+/// A JWT signed by an OpenID provider, decoded but not verified yet.
+///
+/// `CoreIdTokenVerifier` only fits ID tokens: it rejects the `at+jwt` type of access tokens, it
+/// accepts ID tokens, which are handed to the browser in `id_token_hint` and must never be usable
+/// as bearer tokens, and it requires the `sub` claim which logout tokens may omit.
+#[derive(Debug)]
+pub struct Jwt<C> {
+    header: JwtHeader,
+    claims: C,
+    signing_input: String,
+    signature: Vec<u8>,
+}
+
+/// A JWT access token as described in RFC 9068.
+pub type JwtAccessToken = Jwt<AccessTokenClaims>;
+
+/// A logout token sent to the back-channel logout endpoint.
+pub type LogoutToken = Jwt<LogoutTokenClaims>;
+
+impl<C: serde::de::DeserializeOwned> std::str::FromStr for Jwt<C> {
+    type Err = String;
+
+    fn from_str(token: &str) -> Result<Self, Self::Err> {
+        let parts = token.split('.').collect::<Vec<&str>>();
+        let [header, claims, signature] = parts[..] else {
+            return Err("JWT must consist of three parts".to_string());
+        };
+
+        let decode = |part: &str| {
+            base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .decode(part)
+                .map_err(|e| e.to_string())
+        };
+
+        Ok(Jwt {
+            header: serde_json::from_slice(&decode(header)?).map_err(|e| e.to_string())?,
+            claims: serde_json::from_slice(&decode(claims)?).map_err(|e| e.to_string())?,
+            signing_input: format!("{}.{}", header, claims),
+            signature: decode(signature)?,
+        })
+    }
+}
+
+impl<C> Jwt<C> {
+    /// Verifies the signature of the token against the one eligible public key in `jwks`.
+    fn verify_signature(&self, jwks: &CoreJsonWebKeySet) -> Result<(), ClaimsVerificationError> {
+        if self.header.crit.is_some() {
+            return Err(ClaimsVerificationError::Unsupported(
+                "critical JWT header fields are unsupported".to_string(),
+            ));
+        }
+        let alg = &self.header.alg;
+        let Some(key_type) = alg.key_type().filter(|_| !alg.uses_shared_secret()) else {
+            return Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::DisallowedAlg(format!(
+                    "tokens must be signed with a public key (found `{:?}`)",
+                    alg
+                )),
+            ));
+        };
+
+        let keys = jwks
+            .keys()
+            .iter()
+            .filter(|key| self.header.kid.is_none() || key.key_id() == self.header.kid.as_ref())
+            .filter(|key| *key.key_type() == key_type)
+            .filter(|key| {
+                key.key_use()
+                    .is_none_or(|key_use| *key_use == CoreJsonWebKeyUse::Signature)
+            })
+            .collect::<Vec<_>>();
+        let [key] = keys[..] else {
+            return Err(ClaimsVerificationError::SignatureVerification(
+                match keys.is_empty() {
+                    true => SignatureVerificationError::NoMatchingKey,
+                    false => SignatureVerificationError::AmbiguousKeyId(
+                        "JWK set must only contain one eligible public key".to_string(),
+                    ),
+                },
+            ));
+        };
+        key.verify_signature(alg, self.signing_input.as_bytes(), &self.signature)
+            .map_err(ClaimsVerificationError::SignatureVerification)
+    }
+}
+
+fn verify_issuer(iss: &str, issuer_url: &IssuerUrl) -> Result<(), ClaimsVerificationError> {
+    if iss != issuer_url.as_str() {
+        return Err(ClaimsVerificationError::InvalidIssuer(format!(
+            "expected `{}` (found `{}`)",
+            issuer_url.as_str(),
+            iss
+        )));
+    }
+
+    Ok(())
+}
+
+impl JwtAccessToken {
+    /// Whether the token is typed as an access token, either by its `typ` header or by a
+    /// `token_use` claim.
+    fn is_access_token(&self) -> bool {
+        let typed = self.header.typ.as_deref().is_some_and(|typ| {
+            typ.eq_ignore_ascii_case("at+jwt") || typ.eq_ignore_ascii_case("application/at+jwt")
+        });
+
+        typed || self.claims.token_use.as_deref() == Some("access")
+    }
+
+    /// Verifies the issuer, type, audience, signature and expiry of the token and returns its
+    /// subject. Every audience of the token must be `audience`.
+    fn verify(
+        &self,
+        issuer_url: &IssuerUrl,
+        audience: &str,
+        jwks: &CoreJsonWebKeySet,
+    ) -> Result<SubjectIdentifier, ClaimsVerificationError> {
+        verify_issuer(&self.claims.iss, issuer_url)?;
+
+        if !self.is_access_token() {
+            return Err(ClaimsVerificationError::Unsupported(
+                "token is not typed as an access token".to_string(),
+            ));
+        }
+
+        let audiences = self.claims.aud.as_slice();
+        if audiences.is_empty() || audiences.iter().any(|aud| aud != audience) {
+            return Err(ClaimsVerificationError::InvalidAudience(format!(
+                "must only contain `{}` (found audiences: {})",
+                audience,
+                audiences.join(", ")
+            )));
+        }
+
+        self.verify_signature(jwks)?;
+
+        let now = chrono::Utc::now().timestamp();
+        if self.claims.exp <= now {
+            return Err(ClaimsVerificationError::Expired(format!(
+                "expired at {}",
+                self.claims.exp
+            )));
+        }
+        if self.claims.nbf.is_some_and(|nbf| nbf > now) {
+            return Err(ClaimsVerificationError::Other(format!(
+                "not valid before {}",
+                self.claims.nbf.unwrap_or_default()
+            )));
+        }
+
+        Ok(SubjectIdentifier::new(self.claims.sub.clone()))
+    }
+}
+
+impl LogoutToken {
+    /// Verifies the token as described in section 2.6 of OpenID Connect Back-Channel Logout 1.0:
+    /// the issuer, the audience containing `client_id`, the type, the `sub` or `sid` claim, the
+    /// logout event, the absence of a nonce, the signature and the expiry.
+    fn verify(
+        &self,
+        issuer_url: &IssuerUrl,
+        client_id: &ClientId,
+        jwks: &CoreJsonWebKeySet,
+    ) -> Result<&LogoutTokenClaims, ClaimsVerificationError> {
+        verify_issuer(&self.claims.iss, issuer_url)?;
+
+        let audiences = self.claims.aud.as_slice();
+        if !audiences.iter().any(|aud| aud == client_id.as_str()) {
+            return Err(ClaimsVerificationError::InvalidAudience(format!(
+                "must contain `{}` (found audiences: {})",
+                client_id.as_str(),
+                audiences.join(", ")
+            )));
+        }
+
+        if self.header.typ.as_deref().is_some_and(|typ| {
+            !["JWT", "logout+jwt", "application/logout+jwt"]
+                .iter()
+                .any(|logout_typ| typ.eq_ignore_ascii_case(logout_typ))
+        }) {
+            return Err(ClaimsVerificationError::Unsupported(
+                "token is not typed as a logout token".to_string(),
+            ));
+        }
+        if self.claims.sub.is_none() && self.claims.sid.is_none() {
+            return Err(ClaimsVerificationError::Other(
+                "logout token must contain sub or sid".to_string(),
+            ));
+        }
+        if !self.claims.events.contains_key(BACKCHANNEL_LOGOUT_EVENT) {
+            return Err(ClaimsVerificationError::Other(
+                "logout token is missing the back-channel logout event".to_string(),
+            ));
+        }
+        if self.claims.nonce.is_some() {
+            return Err(ClaimsVerificationError::InvalidNonce(
+                "logout tokens must not contain a nonce".to_string(),
+            ));
+        }
+
+        self.verify_signature(jwks)?;
+
+        let now = chrono::Utc::now().timestamp();
+        if self.claims.expires_at() <= now {
+            return Err(ClaimsVerificationError::Expired(format!(
+                "expired at {}",
+                self.claims.expires_at()
+            )));
+        }
+        if self.claims.iat > now.saturating_add(LOGOUT_TOKEN_CLOCK_SKEW_SECONDS) {
+            return Err(ClaimsVerificationError::Other(format!(
+                "issued in the future at {}",
+                self.claims.iat
+            )));
+        }
+
+        Ok(&self.claims)
+    }
+}
+
+/// Name of the provider configured through the unprefixed `OIDC_*` variables when
+/// `OIDC_PROVIDERS` is not set.
+pub const DEFAULT_OIDC_PROVIDER: &str = "default";
+
+/// The OpenID Connect client of one provider built from its discovered metadata, together with
+/// the endpoints discovered for it which `CoreClient` does not keep.
+#[derive(Debug, Clone)]
+pub struct OidcClient {
+    pub client: CoreClient,
+    /// Where to send the user for RP-initiated logout. None if the provider does not support it.
+    pub end_session_endpoint: Option<EndSessionUrl>,
+    /// The keys the provider signs tokens with.
+    pub jwks: CoreJsonWebKeySet,
+}
+
+impl Deref for OidcClient {
+    type Target = CoreClient;
+
+    fn deref(&self) -> &Self::Target {
+        &self.client
+    }
+}
+
+const SUPPORTED_PROFILE_CLAIMS: [&str; 7] = [
+    "email",
+    "preferred_username",
+    "name",
+    "given_name",
+    "family_name",
+    "nickname",
+    "sub",
+];
+
+/// The email and name of a user as reported by an OpenID provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderProfile {
+    pub email: String,
+    pub name: Option<String>,
+    /// Whether the email was read from the `email` claim and the provider asserted it is verified
+    /// with `email_verified`. A missing `email_verified` claim does not count as verified.
+    pub email_verified: bool,
+}
+
+/// Which ID token claims the email and name of a user are read from. Each candidate is a claim
+/// name or several joined with `+`, e.g. `given_name+family_name`, and the first candidate with a
+/// value is used.
+#[derive(Debug, Clone)]
+pub struct ClaimMapping {
+    /// `OIDC_<NAME>_EMAIL_CLAIMS`, defaults to `email`. Values without an `@` are skipped.
+    pub email_claims: Vec<String>,
+    /// `OIDC_<NAME>_NAME_CLAIMS`, defaults to `name,given_name+family_name,preferred_username`.
+    pub name_claims: Vec<String>,
+    /// `OIDC_<NAME>_NAME_LOCALES`, the locales to prefer for localized names after the `locale`
+    /// claim of the user, e.g. `en,de`.
+    pub name_locales: Vec<LanguageTag>,
+    /// `OIDC_<NAME>_REQUIRE_VERIFIED_EMAIL`, defaults to true. Rejects logins whose
+    /// `email_verified` claim is false.
+    pub require_verified_email: bool,
+}
+
+impl ClaimMapping {
+    pub fn from_env(provider: &str) -> Self {
+        let parse_candidates = |var: &str, default: &str| {
+            let candidates = get_oidc_provider_var(provider, var)
+                .unwrap_or(default.to_string())
+                .split(',')
+                .map(|candidate| candidate.trim().to_string())
+                .filter(|candidate| !candidate.is_empty())
+                .collect::<Vec<String>>();
+
+            for claim in candidates.iter().flat_map(|candidate| candidate.split('+')) {
+                if !SUPPORTED_PROFILE_CLAIMS.contains(&claim) {
+                    panic!(
+                        "Unsupported claim {} in the {} of OIDC provider {}, supported claims are {}",
+                        claim,
+                        var,
+                        provider,
+                        SUPPORTED_PROFILE_CLAIMS.join(", ")
+                    );
+                }
+            }
+
+            candidates
+        };
+
+        ClaimMapping {
+            email_claims: parse_candidates("EMAIL_CLAIMS", "email"),
+            name_claims: parse_candidates(
+                "NAME_CLAIMS",
+                "name,given_name+family_name,preferred_username",
+            ),
+            name_locales: get_oidc_provider_var(provider, "NAME_LOCALES")
+                .unwrap_or_default()
+                .split(',')
+                .map(|locale| locale.trim())
+                .filter(|locale| !locale.is_empty())
+                .map(|locale| LanguageTag::new(locale.to_string()))
+                .collect(),
+            require_verified_email: get_oidc_provider_var(provider, "REQUIRE_VERIFIED_EMAIL")
+                .map(|require| require != "false")
+                .unwrap_or(true),
+        }
+    }
+
+    fn localized_value<T: Deref<Target = String>>(
+        &self,
+        claim: Option<&LocalizedClaim<T>>,
+        user_locale: Option<&LanguageTag>,
+    ) -> Option<String> {
+        let claim = claim?;
+
+        user_locale
+            .into_iter()
+            .chain(self.name_locales.iter())
+            .find_map(|locale| claim.get(Some(locale)))
+            .or_else(|| claim.get(None))
+            .or_else(|| claim.iter().next().map(|(_, value)| value))
+            .map(|value| value.to_string())
+    }
+
+    fn claim_value<AC: AdditionalClaims>(
+        &self,
+        claims: &IdTokenClaims<AC, CoreGenderClaim>,
+        claim: &str,
+    ) -> Option<String> {
+        let value = match claim {
+            "email" => claims.email().map(|email| email.to_string()),
+            "preferred_username" => claims
+                .preferred_username()
+                .map(|username| username.to_string()),
+            "name" => self.localized_value(claims.name(), claims.locale()),
+            "given_name" => self.localized_value(claims.given_name(), claims.locale()),
+            "family_name" => self.localized_value(claims.family_name(), claims.locale()),
+            "nickname" => self.localized_value(claims.nickname(), claims.locale()),
+            "sub" => Some(claims.subject().to_string()),
+            _ => None,
+        };
+
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+
+    fn candidate_value<AC: AdditionalClaims>(
+        &self,
+        claims: &IdTokenClaims<AC, CoreGenderClaim>,
+        candidate: &str,
+    ) -> Option<String> {
+        let parts = candidate
+            .split('+')
+            .filter_map(|claim| self.claim_value(claims, claim))
+            .collect::<Vec<String>>();
+
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(" "))
+        }
+    }
+
+    pub fn map_profile<AC: AdditionalClaims>(
+        &self,
+        claims: &IdTokenClaims<AC, CoreGenderClaim>,
+    ) -> Result<ProviderProfile, ServiceError> {
+        if self.require_verified_email && claims.email_verified() == Some(false) {
+            return Err(ServiceError::BadRequest(
+                "Your email address is not verified. Verify it with your identity provider and log in again.".to_string(),
+            ));
+        }
+
+        let (email_candidate, email) = self
+            .email_claims
+            .iter()
+            .filter_map(|candidate| Some((candidate, self.candidate_value(claims, candidate)?)))
+            .find(|(_, email)| email.contains('@'))
+            .ok_or_else(|| {
+                ServiceError::BadRequest(format!(
+                    "The identity provider did not return an email address in any of the claims {}",
+                    self.email_claims.join(", ")
+                ))
+            })?;
+
+        let name = self
+            .name_claims
+            .iter()
+            .find_map(|candidate| self.candidate_value(claims, candidate));
+
+        Ok(ProviderProfile {
+            email,
+            name,
+            email_verified: email_candidate == "email" && claims.email_verified() == Some(true),
+        })
+    }
+}
+
+/// How long requests wait before discovering a provider again which could not be reached.
+const DISCOVERY_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+/// How often a token signed with an unknown key may make the provider keys be fetched again.
+const KEY_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+const DISCOVERY_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const DISCOVERY_MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// One configured OpenID provider. Its metadata and keys are discovered on first use and kept up
+/// to date by `keep_discovered`, so a provider which cannot be reached only makes logins with it
+/// fail until it is back instead of keeping the server from starting.
+#[derive(Debug, Clone)]
+pub struct OidcProvider {
+    /// Name of the provider, used in the `provider` login parameter and its callback route.
+    pub name: String,
+    pub client_id: ClientId,
+    client_secret: ClientSecret,
+    /// `OIDC_<NAME>_ACCESS_TOKEN_AUDIENCE`, the audience access tokens accepted as bearer tokens
+    /// must be issued for. It must differ from the client id, which ID tokens are issued for.
+    /// Bearer tokens of the provider are not accepted when it is not set.
+    access_token_audience: Option<String>,
+    issuer_url: IssuerUrl,
+    /// Overrides the discovered authorization endpoint.
+    auth_url: Option<AuthUrl>,
+    redirect_url: RedirectUrl,
+    /// Scopes requested in addition to `openid`.
+    pub scopes: Vec<Scope>,
+    pub claim_mapping: ClaimMapping,
+    client: Arc<RwLock<Option<Arc<OidcClient>>>>,
+    /// When discovery was last attempted. Held while discovering so that concurrent requests wait
+    /// for one discovery instead of each starting their own.
+    last_discovery: Arc<futures::lock::Mutex<Option<Instant>>>,
+}
+
+impl OidcProvider {
+    pub fn from_env(provider: &str) -> Self {
+        let issuer_url = get_oidc_provider_var(provider, "ISSUER_URL")
+            .expect("Issuer URL for OpenID provider must be set");
+        let client_id = get_oidc_provider_var(provider, "CLIENT_ID")
+            .expect("Client ID for OpenID provider must be set");
+        let client_secret = get_oidc_provider_var(provider, "CLIENT_SECRET")
+            .expect("Client secret for OpenID provider must be set");
+        let scopes = get_oidc_provider_var(provider, "SCOPES")
+            .unwrap_or("email,profile".to_string())
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|scope| !scope.is_empty() && *scope != "openid")
+            .map(|scope| Scope::new(scope.to_string()))
+            .collect();
+        let base_server_url = std::env::var("BASE_SERVER_URL")
+            .expect("Server hostname for OpenID provider must be set");
+        let access_token_audience = get_oidc_provider_var(provider, "ACCESS_TOKEN_AUDIENCE");
+        assert!(
+            access_token_audience.as_ref() != Some(&client_id),
+            "Access token audience for OpenID provider must differ from its client ID"
+        );
+
+        OidcProvider {
+            name: provider.to_string(),
+            access_token_audience,
+            client_id: ClientId::new(client_id),
+            client_secret: ClientSecret::new(client_secret),
+            issuer_url: IssuerUrl::new(issuer_url)
+                .expect("IssuerUrl for OpenID provider must be set"),
+            auth_url: get_oidc_provider_var(provider, "AUTH_REDIRECT_URL").map(
+                |auth_redirect_url| {
+                    AuthUrl::new(auth_redirect_url).expect("Auth configuration is not a valid URL")
+                },
+            ),
+            redirect_url: RedirectUrl::new(format!(
+                "{}/api/auth/callback/{}",
+                base_server_url, provider
+            ))
             .expect("Redirect URL for OpenID provider must be set"),
+            scopes,
+            claim_mapping: ClaimMapping::from_env(provider),
+            client: Arc::new(RwLock::new(None)),
+            last_discovery: Arc::new(futures::lock::Mutex::new(None)),
+        }
+    }
+
+    #[tracing::instrument(skip(self), fields(provider = %self.name))]
+    async fn discover(&self) -> Result<OidcClient, String> {
+        let meta_data =
+            ProviderMetadataWithLogout::discover_async(self.issuer_url.clone(), async_http_client)
+                .await
+                .map_err(|e| e.to_string())?;
+
+        let client = CoreClient::new(
+            self.client_id.clone(),
+            Some(self.client_secret.clone()),
+            self.issuer_url.clone(),
+            self.auth_url
+                .clone()
+                .unwrap_or(meta_data.authorization_endpoint().clone()),
+            meta_data.token_endpoint().cloned(),
+            meta_data.userinfo_endpoint().cloned(),
+            meta_data.jwks().to_owned(),
+        )
+        .set_redirect_uri(self.redirect_url.clone());
+
+        Ok(OidcClient {
+            client,
+            end_session_endpoint: meta_data.additional_metadata().end_session_endpoint.clone(),
+            jwks: meta_data.jwks().to_owned(),
+        })
+    }
+
+    /// Discovers the provider again unless that was attempted less than `min_interval` ago, in
+    /// which case the client discovered last is returned.
+    async fn rediscover(&self, min_interval: Duration) -> Result<Arc<OidcClient>, ServiceError> {
+        let mut last_discovery = self.last_discovery.lock().await;
+        if last_discovery.is_some_and(|last_discovery| last_discovery.elapsed() < min_interval) {
+            return self.current().ok_or(ServiceError::AuthUnavailable);
+        }
+        *last_discovery = Some(Instant::now());
+
+        match self.discover().await {
+            Ok(client) => {
+                let client = Arc::new(client);
+                *self.client.write().unwrap() = Some(client.clone());
+                Ok(client)
+            }
+            Err(e) => {
+                log::error!("Failed to discover OpenID provider {}: {}", self.name, e);
+                Err(ServiceError::AuthUnavailable)
+            }
+        }
+    }
+
+    /// The client discovered last, None if the provider was not discovered yet.
+    pub fn current(&self) -> Option<Arc<OidcClient>> {
+        self.client.read().unwrap().clone()
+    }
+
+    /// The discovered client, discovering the provider if that did not succeed yet. Fails with
+    /// `ServiceError::AuthUnavailable` if the provider cannot be reached.
+    pub async fn client(&self) -> Result<Arc<OidcClient>, ServiceError> {
+        match self.current() {
+            Some(client) => Ok(client),
+            None => self.rediscover(DISCOVERY_RETRY_INTERVAL).await,
+        }
+    }
+
+    /// Runs `verify` with the client, and if the token was signed with a key which is not known,
+    /// the provider may have rotated its keys, so they are fetched again before giving up.
+    async fn verify_with_key_refresh<T>(
+        &self,
+        client: &OidcClient,
+        verify: impl Fn(&OidcClient) -> Result<T, ClaimsVerificationError>,
+    ) -> Result<T, ClaimsVerificationError> {
+        match verify(client) {
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::NoMatchingKey,
+            )) => match self.rediscover(KEY_REFRESH_INTERVAL).await {
+                Ok(client) => verify(&client),
+                Err(_) => Err(ClaimsVerificationError::SignatureVerification(
+                    SignatureVerificationError::NoMatchingKey,
+                )),
+            },
+            result => result,
+        }
+    }
+
+    /// Verifies an ID token issued by the provider for this client.
+    pub async fn verify_claims<'a, AC: AdditionalClaims, N: NonceVerifier + Clone>(
+        &self,
+        client: &OidcClient,
+        token: &'a ExtendedIdToken<AC>,
+        nonce_verifier: N,
+    ) -> Result<&'a IdTokenClaims<AC, CoreGenderClaim>, ClaimsVerificationError> {
+        self.verify_with_key_refresh(client, |client| {
+            token.claims(&client.id_token_verifier(), nonce_verifier.clone())
+        })
+        .await
+    }
+
+    /// Verifies a logout token issued by the provider for this client.
+    pub async fn verify_logout_token<'a>(
+        &self,
+        client: &OidcClient,
+        logout_token: &'a LogoutToken,
+    ) -> Result<&'a LogoutTokenClaims, ClaimsVerificationError> {
+        self.verify_with_key_refresh(client, |client| {
+            logout_token.verify(&self.issuer_url, &self.client_id, &client.jwks)
+        })
+        .await
+    }
+
+    /// Verifies a JWT access token issued by the provider for `access_token_audience` and returns
+    /// its subject. Fails with `InvalidIssuer` if the token was issued by another provider.
+    pub async fn verify_access_token(
+        &self,
+        client: &OidcClient,
+        access_token: &JwtAccessToken,
+        access_token_audience: &str,
+    ) -> Result<SubjectIdentifier, ClaimsVerificationError> {
+        self.verify_with_key_refresh(client, |client| {
+            access_token.verify(&self.issuer_url, access_token_audience, &client.jwks)
+        })
+        .await
+    }
+
+    /// Discovers the provider until it succeeds, backing off exponentially, and then again every
+    /// `refresh_interval` to pick up changed metadata and rotated keys.
+    async fn keep_discovered(self, refresh_interval: Duration) {
+        let mut backoff = DISCOVERY_INITIAL_BACKOFF;
+        loop {
+            match self.rediscover(Duration::ZERO).await {
+                Ok(_) => {
+                    backoff = DISCOVERY_INITIAL_BACKOFF;
+                    actix_web::rt::time::sleep(refresh_interval).await;
+                }
+                Err(_) => {
+                    log::warn!(
+                        "Retrying discovery of OpenID provider {} in {}s",
+                        self.name,
+                        backoff.as_secs()
+                    );
+                    actix_web::rt::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(DISCOVERY_MAX_BACKOFF);
+                }
+            }
+        }
+    }
+}
+
+/// Every configured OpenID provider. The first one in `OIDC_PROVIDERS` is used when a login does
+/// not name a provider.
+#[derive(Debug, Clone)]
+pub struct OidcProviders {
+    providers: Vec<OidcProvider>,
+}
+
+impl OidcProviders {
+    pub fn get(&self, name: &str) -> Result<&OidcProvider, ServiceError> {
+        self.providers
+            .iter()
+            .find(|provider| provider.name == name)
+            .ok_or_else(|| ServiceError::BadRequest(format!("Unknown OIDC provider {}", name)))
+    }
+
+    pub fn default_provider(&self) -> &OidcProvider {
+        &self.providers[0]
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.providers
+            .iter()
+            .map(|provider| provider.name.clone())
+            .collect()
+    }
+
+    /// Finds the provider which issued a JWT access token and verifies it. Providers which were
+    /// not discovered yet are skipped, so that requests do not wait for a provider which is down.
+    pub async fn verify_access_token(
+        &self,
+        access_token: &str,
+    ) -> Result<(&OidcProvider, SubjectIdentifier), ServiceError> {
+        let access_token = access_token
+            .parse::<JwtAccessToken>()
+            .map_err(|_| ServiceError::Unauthorized)?;
+
+        let mut unavailable = false;
+        for provider in self.providers.iter() {
+            let Some(access_token_audience) = provider.access_token_audience.as_deref() else {
+                continue;
+            };
+            let Some(client) = provider.current() else {
+                unavailable = true;
+                continue;
+            };
+
+            // The issuer is checked before the signature, so tokens of other providers are
+            // rejected without fetching keys
+            match provider
+                .verify_access_token(&client, &access_token, access_token_audience)
+                .await
+            {
+                Ok(subject) => return Ok((provider, subject)),
+                Err(ClaimsVerificationError::InvalidIssuer(_)) => continue,
+                Err(e) => {
+                    log::info!("Rejected access token of {}: {}", provider.name, e);
+                    return Err(ServiceError::Unauthorized);
+                }
+            }
+        }
+
+        match unavailable {
+            true => Err(ServiceError::AuthUnavailable),
+            false => Err(ServiceError::Unauthorized),
+        }
+    }
+
+    /// Discovers every provider in the background, see `OidcProvider::keep_discovered`.
+    pub fn keep_discovered(&self, refresh_interval: Duration) {
+        for provider in self.providers.iter() {
+            actix_web::rt::spawn(provider.clone().keep_discovered(refresh_interval));
+        }
+    }
+}
+
+/// The names in `OIDC_PROVIDERS`, e.g. `keycloak,google`. Each provider is configured with
+/// `OIDC_<NAME>_ISSUER_URL`, `OIDC_<NAME>_CLIENT_ID`, `OIDC_<NAME>_CLIENT_SECRET` and optionally
+/// `OIDC_<NAME>_SCOPES`, `OIDC_<NAME>_AUTH_REDIRECT_URL` and `OIDC_<NAME>_ACCESS_TOKEN_AUDIENCE`.
+pub fn get_oidc_provider_names() -> Vec<String> {
+    let names = std::env::var("OIDC_PROVIDERS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|name| name.trim().to_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<String>>();
+
+    if names.is_empty() {
+        return vec![DEFAULT_OIDC_PROVIDER.to_string()];
+    }
+
+    for name in names.iter() {
+        if !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            panic!(
+                "OIDC provider name {} may only contain letters, digits, - and _",
+                name
+            );
+        }
+    }
+
+    names
+}
+
+fn get_oidc_provider_var(provider: &str, var: &str) -> Option<String> {
+    let env_var = if provider == DEFAULT_OIDC_PROVIDER {
+        format!("OIDC_{}", var)
+    } else {
+        format!("OIDC_{}_{}", provider.to_uppercase().replace('-', "_"), var)
+    };
+
+    std::env::var(env_var).ok()
+}
+
+pub fn build_oidc_providers() -> OidcProviders {
+    OidcProviders {
+        providers: get_oidc_provider_names()
+            .iter()
+            .map(|name| OidcProvider::from_env(name))
+            .collect(),
+    }
+}
+
+/// Browsers drop tabs and newlines from URLs and treat backslashes like slashes, so e.g.
+/// `/\evil.com` and `/<TAB>/evil.com` look like paths but lead to another host. Their percent
+/// encoded forms are rejected as well, in case the redirect target is decoded once more.
+fn contains_ambiguous_characters(redirect_uri: &str) -> bool {
+    let redirect_uri = redirect_uri.to_ascii_lowercase();
+
+    redirect_uri.chars().any(|c| c.is_control() || c == '\\')
+        || redirect_uri.contains("%5c")
+        || redirect_uri.contains("%7f")
+        || (0..0x20).any(|byte| redirect_uri.contains(&format!("%{:02x}", byte)))
+}
+
+/// A redirect target is allowed if it is a path on this server or matches one of the comma
+/// separated `ALLOWED_REDIRECT_URIS` patterns. A pattern is an origin such as
+/// `https://app.example.com`, which allows every path, or an origin with a path which must match
+/// exactly unless it ends in `*`, e.g. `https://app.example.com/auth/*`. The origin of
+/// `BASE_SERVER_URL` is always allowed.
+pub fn validate_redirect_uri(redirect_uri: &str) -> Result<(), ServiceError> {
+    validate_redirect_uri_against(
+        redirect_uri,
+        &std::env::var("BASE_SERVER_URL").unwrap_or_default(),
+        &std::env::var("ALLOWED_REDIRECT_URIS").unwrap_or_default(),
     )
 }
 
-#[tracing::instrument(skip(pg_pool))]
-pub async fn create_account(
-    email: String,
-    name: String,
-    user_id: uuid::Uuid,
-    pg_pool: web::Data<PgPool>,
-) -> Result<User, ServiceError> {
-    let user_org = create_user_query(user_id, email, Some(name), pg_pool).await?;
+fn validate_redirect_uri_against(
+    redirect_uri: &str,
+    base_server_url: &str,
+    allowed_redirect_uris: &str,
+) -> Result<(), ServiceError> {
+    let rejected = || {
+        ServiceError::BadRequest(format!(
+            "redirect_uri '{}' is not allowed. Add its origin to ALLOWED_REDIRECT_URIS to redirect there.",
+            redirect_uri
+        ))
+    };
+
+    if contains_ambiguous_characters(redirect_uri) {
+        return Err(rejected());
+    }
 
-    Ok(user_org)
+    // Paths are resolved against this server the way a browser would, so that anything which
+    // leaves it, e.g. `//evil.com`, is rejected
+    if redirect_uri.starts_with('/') {
+        let base_server_url = oauth2::url::Url::parse(base_server_url).map_err(|_| rejected())?;
+        let redirect_url = base_server_url.join(redirect_uri).map_err(|_| rejected())?;
+
+        return match redirect_url.origin() == base_server_url.origin() {
+            true => Ok(()),
+            false => Err(rejected()),
+        };
+    }
+
+    let redirect_url = oauth2::url::Url::parse(redirect_uri).map_err(|_| rejected())?;
+    if !matches!(redirect_url.scheme(), "http" | "https") {
+        return Err(rejected());
+    }
+
+    let allowed = allowed_redirect_uris
+        .split(',')
+        .chain(std::iter::once(base_server_url))
+        .map(|pattern| pattern.trim())
+        .filter(|pattern| !pattern.is_empty())
+        .any(|pattern| {
+            let (pattern, wildcard) = match pattern.strip_suffix('*') {
+                Some(pattern) => (pattern, true),
+                None => (pattern, false),
+            };
+            let Ok(pattern_url) = oauth2::url::Url::parse(pattern) else {
+                log::warn!("Ignoring invalid ALLOWED_REDIRECT_URIS entry '{}'", pattern);
+                return false;
+            };
+
+            if pattern_url.origin() != redirect_url.origin() {
+                return false;
+            }
+
+            match pattern_url.path() {
+                "/" if !wildcard => true,
+                path if wildcard => redirect_url.path().starts_with(path),
+                path => redirect_url.path() == path,
+            }
+        });
+
+    if allowed {
+        Ok(())
+    } else {
+        Err(rejected())
+    }
+}
+
+/// The `Referer` of the request if it is an allowed redirect target, otherwise `/`.
+fn get_referer_redirect_uri(req: &HttpRequest) -> String {
+    req.headers()
+        .get("Referer")
+        .and_then(|referer| referer.to_str().ok())
+        .filter(|referer| validate_redirect_uri(referer).is_ok())
+        .unwrap_or("/")
+        .to_string()
 }
 
 #[derive(Deserialize, Debug, IntoParams)]
 pub struct LogoutRequest {
+    /// URL to redirect to after logout. Must be a path on this server or allowed by `ALLOWED_REDIRECT_URIS`.
     pub redirect_uri: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+#[schema(example = json!({"logout_url": "http://localhost:8080/realms/actix-server/protocol/openid-connect/logout?post_logout_redirect_uri=http%3A%2F%2Flocalhost%3A8090%2F"}))]
+pub struct LogoutRespPayload {
+    /// Where to send the browser next. Ends the session at the OpenID provider if it supports RP-initiated logout, otherwise it is the `redirect_uri`.
+    pub logout_url: String,
+}
+
 /// Logout
 ///
-/// Invalidate your current auth credential stored typically stored in a cookie. This does not invalidate your API key.
+/// Invalidate your current auth credential stored typically stored in a cookie. This does not invalidate your API key. The returned `logout_url` ends the session at the OpenID provider if it supports RP-initiated logout, otherwise it is the `redirect_uri`.
 #[utoipa::path(
     delete,
     path = "/auth",
@@ -111,37 +1121,87 @@ pub struct LogoutRequest {
     ),
     tag = "auth",
     responses(
-        (status = 204, description = "Confirmation that your current auth token has been invalidated. This does not invalidate your API key."),
+        (status = 200, description = "Confirmation that your current auth token has been invalidated, with the URL to end the session at the OpenID provider. This does not invalidate your API key.", body = LogoutRespPayload),
+        (status = 400, description = "The redirect_uri is not in the allowlist", body = ErrorRespPayload),
         (status = 401, description = "The user is already logged out / does not have an account"),
     ),
 )]
-#[tracing::instrument(skip(id))]
+#[tracing::instrument(skip(id, session, oidc_providers, redis_pool))]
 pub async fn logout(
     id: Identity,
+    session: Session,
+    oidc_providers: web::Data<OidcProviders>,
+    redis_pool: web::Data<RedisPool>,
     data: web::Query<LogoutRequest>,
     req: HttpRequest,
-) -> HttpResponse {
+) -> Result<HttpResponse, ServiceError> {
+    let redirect_uri = match data.redirect_uri.clone() {
+        Some(redirect_uri) => {
+            validate_redirect_uri(&redirect_uri)?;
+            redirect_uri
+        }
+        None => get_referer_redirect_uri(&req),
+    };
+
+    // Read before logging out, logging out purges the session
+    let id_token = session
+        .get::<String>(ID_TOKEN_SESSION_KEY)
+        .ok()
+        .flatten()
+        .and_then(|id_token| id_token.parse::<CoreIdToken>().ok());
+    let oidc_provider = session
+        .get::<String>(OIDC_PROVIDER_SESSION_KEY)
+        .ok()
+        .flatten()
+        .and_then(|provider| oidc_providers.get(&provider).ok())
+        .unwrap_or(oidc_providers.default_provider());
+    let cookie_identity = req.extensions().get::<CookieIdentity>().cloned();
+
     id.logout();
-    let issuer_url =
-        std::env::var("OIDC_ISSUER_URL").expect("Issuer URL for OpenID provider must be set");
-    let client_id =
-        std::env::var("OIDC_CLIENT_ID").expect("Client ID for OpenID provider must be set");
-
-    let logout_url = format!(
-        "{}/protocol/openid-connect/logout?post_logout_redirect_uri={}&client_id={}",
-        issuer_url,
-        data.redirect_uri.clone().unwrap_or(
-            req.headers()
-                .get("Referer")
-                .map_or("/", |h| h.to_str().unwrap_or("/"))
-                .to_string()
-        ),
-        client_id
-    );
 
-    HttpResponse::Ok().json(json!({
-        "logout_url": logout_url,
-    }))
+    if let Some(cookie_identity) = cookie_identity {
+        end_user_session_query(
+            cookie_identity.user_id,
+            &cookie_identity.session_id,
+            redis_pool,
+        )
+        .await?;
+    }
+
+    // Logging out does not wait for a provider which was not discovered yet
+    let end_session_endpoint = oidc_provider
+        .current()
+        .and_then(|oidc_client| oidc_client.end_session_endpoint.clone());
+
+    let logout_url = match end_session_endpoint {
+        Some(end_session_endpoint) => {
+            let base_server_url = std::env::var("BASE_SERVER_URL")
+                .expect("Server hostname for OpenID provider must be set");
+            let post_logout_redirect_uri = oauth2::url::Url::parse(&base_server_url)
+                .and_then(|base_server_url| base_server_url.join(&redirect_uri))
+                .map_err(|_| {
+                    ServiceError::InternalServerError(
+                        "Could not build post logout redirect URI".into(),
+                    )
+                })?;
+
+            let mut logout_request = openidconnect::LogoutRequest::from(end_session_endpoint)
+                .set_client_id(oidc_provider.client_id.clone())
+                .set_post_logout_redirect_uri(PostLogoutRedirectUrl::from_url(
+                    post_logout_redirect_uri,
+                ));
+            if let Some(id_token) = &id_token {
+                logout_request = logout_request.set_id_token_hint(id_token);
+            }
+
+            logout_request.http_get_url().to_string()
+        }
+        // The provider does not support RP-initiated logout or cannot be reached, only the local
+        // session is ended
+        None => redirect_uri,
+    };
+
+    Ok(HttpResponse::Ok().json(LogoutRespPayload { logout_url }))
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -151,23 +1211,188 @@ pub struct OpenIdConnectState {
     pub nonce: Nonce,
 }
 
-const OIDC_SESSION_KEY: &str = "oidc_state";
-
 #[derive(Deserialize, Debug, ToSchema, IntoParams)]
 #[schema(
     example = json!({"redirect_uri": "https://api.trieve.ai"}),
 )]
 pub struct AuthQuery {
-    /// URL to redirect to after successful login
+    /// URL to redirect to after successful login. Must be a path on this server or allowed by `ALLOWED_REDIRECT_URIS`.
     pub redirect_uri: Option<String>,
     /// Code sent via email as a result of successful call to send_invitation
     pub inv_code: Option<uuid::Uuid>,
+    /// Name of the OpenID provider to log in with, see `GET /api/auth/providers`. Defaults to the first configured provider.
+    pub provider: Option<String>,
+    /// Link the identity at `provider` to the account which is logged in with this browser instead of logging in with it. The link has to be confirmed at `/auth/link` after logging in at the provider.
+    pub link: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LoginState {
+    /// URL to redirect to after successful login
+    pub redirect_uri: String,
+    /// Name of the OpenID provider the login was started with
+    pub provider: String,
+    /// The user to link the identity to if the login was started to link another provider
+    pub link_user_id: Option<uuid::Uuid>,
+    /// The browser session the link was started in, only that session can confirm the link
+    pub link_session_id: Option<String>,
+}
+
+/// Everything `callback` needs to finish a login, stored under a key derived from the CSRF token
+/// sent to the OpenID provider as the `state` parameter.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PendingLogin {
+    pub oidc_state: OpenIdConnectState,
+    pub login_state: LoginState,
+    /// Hash of the secret kept in the session of the browser which started the login, so that a
+    /// callback can only finish the login in that browser.
+    pub browser_binding: String,
+}
+
+/// An identity waiting for the user to confirm linking it to their account, kept in the session of
+/// the browser which started the link.
+#[derive(Serialize, Deserialize, Debug)]
+struct PendingLink {
+    user_id: uuid::Uuid,
+    session_id: String,
+    provider: String,
+    subject: String,
+    email: String,
+    name: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+    redirect_uri: String,
+    expires_at: chrono::NaiveDateTime,
+}
+
+/// Session key under which the identity waiting to be linked is kept.
+const PENDING_LINK_SESSION_KEY: &str = "pending_link";
+
+/// Session key under which the secret binding logins to the browser which started them is kept.
+const LOGIN_BINDING_SESSION_KEY: &str = "login_binding";
+
+fn browser_binding_hash(browser_binding: &str) -> String {
+    blake3::hash(browser_binding.as_bytes()).to_string()
+}
+
+/// The secret binding logins to this browser, created on its first login. It is shared by every
+/// login started in the browser, so that logins in several tabs do not clobber each other.
+fn get_or_create_browser_binding(session: &Session) -> Result<String, ServiceError> {
+    let browser_binding = session
+        .get::<String>(LOGIN_BINDING_SESSION_KEY)
+        .map_err(|_| ServiceError::InternalServerError("Could not read login binding".into()))?;
+
+    match browser_binding {
+        Some(browser_binding) => Ok(browser_binding),
+        None => {
+            let browser_binding = CsrfToken::new_random().secret().clone();
+            session
+                .insert(LOGIN_BINDING_SESSION_KEY, &browser_binding)
+                .map_err(|_| {
+                    ServiceError::InternalServerError("Could not store login binding".into())
+                })?;
+            Ok(browser_binding)
+        }
+    }
+}
+
+fn pending_login_key(csrf_token: &str) -> String {
+    format!("oidc_state:{}", blake3::hash(csrf_token.as_bytes()))
+}
+
+fn get_pending_login_ttl_seconds() -> u64 {
+    std::env::var("OIDC_STATE_TTL_SECONDS")
+        .unwrap_or("600".to_string())
+        .parse()
+        .unwrap_or(600)
+}
+
+trait PendingLoginStore {
+    async fn put(
+        &mut self,
+        key: String,
+        value: String,
+        ttl_seconds: u64,
+    ) -> Result<(), ServiceError>;
+    /// Get and delete the value in one step so that a state can only be used once.
+    async fn take(&mut self, key: String) -> Result<Option<String>, ServiceError>;
+}
+
+impl PendingLoginStore for redis::aio::MultiplexedConnection {
+    async fn put(
+        &mut self,
+        key: String,
+        value: String,
+        ttl_seconds: u64,
+    ) -> Result<(), ServiceError> {
+        self.set_ex(key, value, ttl_seconds).await.map_err(|_| {
+            ServiceError::InternalServerError("Failed to set OIDC session state".into())
+        })
+    }
+
+    async fn take(&mut self, key: String) -> Result<Option<String>, ServiceError> {
+        let (value,): (Option<String>,) = redis::pipe()
+            .atomic()
+            .get(&key)
+            .del(&key)
+            .ignore()
+            .query_async(self)
+            .await
+            .map_err(|_| {
+                ServiceError::InternalServerError("Could not get OIDC session state".into())
+            })?;
+
+        Ok(value)
+    }
+}
+
+async fn save_pending_login<S: PendingLoginStore>(
+    store: &mut S,
+    pending_login: &PendingLogin,
+) -> Result<(), ServiceError> {
+    let value = serde_json::to_string(pending_login).map_err(|_| {
+        ServiceError::InternalServerError("Could not serialize OIDC session state".into())
+    })?;
+
+    store
+        .put(
+            pending_login_key(pending_login.oidc_state.csrf_token.secret()),
+            value,
+            get_pending_login_ttl_seconds(),
+        )
+        .await
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct LoginState {
-    /// URL to redirect to after successful login
-    pub redirect_uri: String,
+/// Loads and consumes the login started with the CSRF token in `state`. Fails with
+/// `Unauthorized` if the state is unknown, expired, already used or does not match, or if the
+/// login was started in another browser than the one with `browser_binding`.
+async fn take_pending_login<S: PendingLoginStore>(
+    store: &mut S,
+    state: &str,
+    browser_binding: &str,
+) -> Result<PendingLogin, ServiceError> {
+    let pending_login: PendingLogin = match store.take(pending_login_key(state)).await? {
+        Some(pending_login) => serde_json::from_str(&pending_login).map_err(|_| {
+            ServiceError::InternalServerError("Could not deserialize OIDC session state".into())
+        })?,
+        None => return Err(ServiceError::Unauthorized),
+    };
+
+    // blake3::Hash compares in constant time
+    if blake3::hash(pending_login.oidc_state.csrf_token.secret().as_bytes())
+        != blake3::hash(state.as_bytes())
+    {
+        return Err(ServiceError::Unauthorized);
+    }
+
+    if blake3::Hash::from_hex(&pending_login.browser_binding).ok()
+        != Some(blake3::hash(browser_binding.as_bytes()))
+    {
+        log::info!("Rejected OIDC callback from another browser than the one logging in");
+        return Err(ServiceError::Unauthorized);
+    }
+
+    Ok(pending_login)
 }
 
 /// Login
@@ -181,16 +1406,26 @@ pub struct LoginState {
     params(AuthQuery),
     responses(
         (status = 303, description = "Response that redirects to OAuth provider through a Location header to be handled by browser."),
-        (status = 400, description = "OAuth error likely with OIDC provider.", body = ErrorRespPayload),
+        (status = 400, description = "OAuth error likely with OIDC provider or the redirect_uri is not in the allowlist.", body = ErrorRespPayload),
+        (status = 401, description = "link was requested without being logged in with this browser", body = ErrorRespPayload),
+        (status = 403, description = "link was requested with a credential which does not have the user:write scope", body = ErrorRespPayload),
+        (status = 503, description = "The OpenID provider cannot be reached", body = ErrorRespPayload),
     )
 )]
-#[tracing::instrument(skip(oidc_client, redis_pool))]
+#[tracing::instrument(skip(oidc_providers, redis_pool, session))]
 pub async fn login(
     req: HttpRequest,
+    session: Session,
     redis_pool: web::Data<RedisPool>,
     data: web::Query<AuthQuery>,
-    oidc_client: web::Data<CoreClient>,
+    oidc_providers: web::Data<OidcProviders>,
 ) -> Result<HttpResponse, Error> {
+    let oidc_provider = match &data.provider {
+        Some(provider) => oidc_providers.get(provider)?,
+        None => oidc_providers.default_provider(),
+    };
+    let oidc_client = oidc_provider.client().await?;
+
     let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
 
     let (auth_url, csrf_token, nonce) = oidc_client
@@ -200,6 +1435,7 @@ pub async fn login(
             Nonce::new_random,
         )
         .add_scope(Scope::new("openid".to_string()))
+        .add_scopes(oidc_provider.scopes.clone())
         .set_pkce_challenge(pkce_challenge)
         .url();
 
@@ -209,33 +1445,45 @@ pub async fn login(
         nonce,
     };
 
-    let mut redis_conn = redis_pool.get().await.unwrap();
-    let _: () = redis_conn
-        .set(
-            OIDC_SESSION_KEY,
-            serde_json::to_string(&oidc_state).unwrap(),
-        )
-        .await
-        .map_err(|_| {
-            ServiceError::InternalServerError("Failed to set OIDC session state".into())
-        })?;
-
     let redirect_uri = match data.redirect_uri.clone() {
-        Some(redirect_uri) => redirect_uri,
-        None => req
-            .headers()
-            .get("Referer")
-            .map(|h| h.to_str().unwrap_or("/"))
-            .unwrap_or("/")
-            .to_string(),
+        Some(redirect_uri) => {
+            validate_redirect_uri(&redirect_uri)?;
+            redirect_uri
+        }
+        None => get_referer_redirect_uri(&req),
+    };
+
+    // Linking attaches another login to the account, so it is only possible from a browser
+    // session which may change the user
+    let (link_user_id, link_session_id) = match data.link {
+        Some(true) => {
+            let user = ScopedUser::<UserWriteScope>::extract(&req).await?;
+            let cookie_identity = req
+                .extensions()
+                .get::<CookieIdentity>()
+                .cloned()
+                .ok_or(ServiceError::Unauthorized)?;
+            (Some(user.id), Some(cookie_identity.session_id))
+        }
+        _ => (None, None),
     };
 
-    let login_state = LoginState { redirect_uri };
+    let pending_login = PendingLogin {
+        oidc_state,
+        login_state: LoginState {
+            redirect_uri,
+            provider: oidc_provider.name.clone(),
+            link_user_id,
+            link_session_id,
+        },
+        browser_binding: browser_binding_hash(&get_or_create_browser_binding(&session)?),
+    };
 
-    let _: () = redis_conn
-        .set("login_state", serde_json::to_string(&login_state).unwrap())
+    let mut redis_conn = redis_pool
+        .get()
         .await
-        .map_err(|_| ServiceError::InternalServerError("Failed to set login state".into()))?;
+        .map_err(|_| ServiceError::InternalServerError("Could not get redis connection".into()))?;
+    save_pending_login(&mut *redis_conn, &pending_login).await?;
 
     //redirect to OpenIdProvider for authentication
     Ok(HttpResponse::SeeOther()
@@ -248,45 +1496,56 @@ pub async fn login(
 /// This is the callback route for the OAuth provider, it should not be called directly. Redirects to browser with set-cookie header.
 #[utoipa::path(
     get,
-    path = "/auth/callback",
+    path = "/auth/callback/{provider}",
     context_path = "/api",
     tag = "auth",
     params(
+        ("provider" = String, Path, description = "Name of the OpenID provider the login was started with"),
         OpCallback
     ),
     responses(
         (status = 303, description = "Response that returns with set-cookie header"),
         (status = 400, description = "Email or password empty or incorrect", body = ErrorRespPayload),
+        (status = 401, description = "The state is unknown, expired or already used, or the login was started in another browser", body = ErrorRespPayload),
+        (status = 503, description = "The OpenID provider cannot be reached", body = ErrorRespPayload),
     )
 )]
-#[tracing::instrument(skip(redis_pool, oidc_client, pg_pool))]
+#[tracing::instrument(skip(redis_pool, oidc_providers, pg_pool, session))]
 pub async fn callback(
     req: HttpRequest,
+    session: Session,
     redis_pool: web::Data<RedisPool>,
-    oidc_client: web::Data<CoreClient>,
+    oidc_providers: web::Data<OidcProviders>,
     pg_pool: web::Data<PgPool>,
+    provider: web::Path<String>,
     query: web::Query<OpCallback>,
 ) -> Result<HttpResponse, Error> {
-    let mut redis_conn = redis_pool
-        .get()
-        .await
-        .map_err(|_| ServiceError::InternalServerError("Could not get redis connection".into()))?;
+    // Without a binding the browser never started a login
+    let browser_binding = session
+        .get::<String>(LOGIN_BINDING_SESSION_KEY)
+        .ok()
+        .flatten()
+        .ok_or(ServiceError::Unauthorized)?;
 
-    let opt_state: Option<String> =
-        redis_conn
-            .get(OIDC_SESSION_KEY.to_string())
-            .await
-            .map_err(|_| {
-                ServiceError::InternalServerError("Could not get OIDC session state".into())
-            })?;
+    let PendingLogin {
+        oidc_state: state,
+        login_state,
+        ..
+    } = {
+        let mut redis_conn = redis_pool.get().await.map_err(|_| {
+            ServiceError::InternalServerError("Could not get redis connection".into())
+        })?;
 
-    let state: OpenIdConnectState = match opt_state {
-        Some(state) => serde_json::from_str(&state).map_err(|_| {
-            ServiceError::InternalServerError("Could not deserialize OIDC session state".into())
-        })?,
-        None => Err(ServiceError::Unauthorized)?,
+        take_pending_login(&mut *redis_conn, &query.state, &browser_binding).await?
     };
 
+    // The state was issued for a login with another provider
+    if login_state.provider != *provider {
+        return Err(ServiceError::Unauthorized.into());
+    }
+    let oidc_provider = oidc_providers.get(&provider)?;
+    let oidc_client = oidc_provider.client().await?;
+
     let code_verifier = state.pkce_verifier;
     let code = query.code.clone();
     let nonce = state.nonce;
@@ -303,14 +1562,18 @@ pub async fn callback(
             _ => ServiceError::InternalServerError("Unknown error".into()),
         })?;
 
+    // Parsed again to read the `sid` claim, which `CoreIdToken` drops
     let id_token = token_response
         .extra_fields()
         .id_token()
-        .ok_or_else(|| ServiceError::InternalServerError("Empty ID Token".into()))?;
+        .ok_or_else(|| ServiceError::InternalServerError("Empty ID Token".into()))?
+        .to_string()
+        .parse::<ExtendedIdToken<SessionIdClaims>>()
+        .map_err(|_| ServiceError::InternalServerError("Could not parse ID Token".into()))?;
 
-    let id_token_verifier = oidc_client.id_token_verifier();
-    let claims = id_token
-        .claims(&id_token_verifier, &nonce)
+    let claims = oidc_provider
+        .verify_claims(&oidc_client, &id_token, &nonce)
+        .await
         .map_err(|_| ServiceError::InternalServerError("Claims Verification Error".into()))?;
 
     match claims.access_token_hash() {
@@ -336,58 +1599,206 @@ pub async fn callback(
         }
     }?;
 
-    let user_id = claims
-        .subject()
-        .to_string()
-        .parse::<uuid::Uuid>()
-        .map_err(|_| {
-            ServiceError::InternalServerError("Failed to parse user ID from claims".into())
-        })?;
+    let subject = claims.subject().to_string();
 
-    let email = claims.email().ok_or_else(|| {
-        ServiceError::InternalServerError("Failed to parse email from claims".into())
-    })?;
+    let profile = oidc_provider.claim_mapping.map_profile(claims)?;
 
-    let name = claims.name().ok_or_else(|| {
-        ServiceError::InternalServerError("Failed to parse name from claims".into())
-    })?;
+    validate_redirect_uri(&login_state.redirect_uri)?;
 
-    let opt_login_state: Option<String> = redis_conn
-        .get("login_state")
-        .await
-        .map_err(|_| ServiceError::InternalServerError("Failed to get login state".into()))?;
+    if let Some(user_id) = login_state.link_user_id {
+        // The browser must still be logged in with the session which started the link
+        let cookie_identity = req.extensions().get::<CookieIdentity>().cloned();
+        if !cookie_identity.is_some_and(|cookie_identity| {
+            cookie_identity.user_id == user_id
+                && Some(cookie_identity.session_id) == login_state.link_session_id
+        }) {
+            return Err(ServiceError::Unauthorized.into());
+        }
 
-    let login_state: LoginState = match opt_login_state {
-        Some(login_state) => serde_json::from_str(&login_state).map_err(|_| {
-            ServiceError::InternalServerError("Failed to deserialize login state".into())
-        })?,
-        None => Err(ServiceError::Unauthorized)?,
-    };
+        let pending_link = PendingLink {
+            user_id,
+            session_id: login_state.link_session_id.unwrap_or_default(),
+            provider: oidc_provider.name.clone(),
+            subject,
+            email: profile.email,
+            name: profile.name,
+            email_verified: profile.email_verified,
+            redirect_uri: login_state.redirect_uri,
+            expires_at: chrono::Utc::now().naive_utc()
+                + chrono::Duration::seconds(get_pending_login_ttl_seconds() as i64),
+        };
+        session
+            .insert(PENDING_LINK_SESSION_KEY, pending_link)
+            .map_err(|_| {
+                ServiceError::InternalServerError("Could not store pending link".into())
+            })?;
 
-    let user = match get_user_by_id_query(&user_id, pg_pool.clone()).await {
-        Ok(user) => user,
-        Err(_) => {
-            create_account(
-                email.to_string(),
-                name.iter().next().unwrap().1.to_string(),
-                user_id,
-                pg_pool.clone(),
-            )
-            .await?
-        }
-    };
+        return Ok(HttpResponse::SeeOther()
+            .insert_header(("Location", "/auth/link"))
+            .finish());
+    }
+
+    let user = upsert_user_for_identity_query(
+        oidc_provider.name.clone(),
+        subject.clone(),
+        profile.email,
+        profile.name,
+        profile.email_verified,
+        pg_pool.clone(),
+    )
+    .await?;
+
+    // Logging in syncs the email and name reported by the provider, the cached user expires on its
+    // own if it can not be invalidated
+    if let Err(e) = invalidate_user_cache_query(user.id, redis_pool.clone()).await {
+        log::error!("Failed to invalidate cached user {}: {:?}", user.id, e);
+    }
+
+    let user_agent = req
+        .headers()
+        .get("User-Agent")
+        .and_then(|user_agent| user_agent.to_str().ok())
+        .map(|user_agent| user_agent.to_string());
+    let oidc_session_id = create_oidc_session_query(
+        oidc_provider.name.clone(),
+        claims.additional_claims().sid.clone(),
+        subject,
+        user.id,
+        user_agent,
+        client_ip(&req).map(|ip| ip.to_string()),
+        redis_pool.clone(),
+    )
+    .await?;
 
-    let user_string = serde_json::to_string(&user).map_err(|_| {
-        ServiceError::InternalServerError("Failed to serialize user to JSON".into())
+    let identity_string = serde_json::to_string(&CookieIdentity {
+        user_id: user.id,
+        session_id: oidc_session_id,
+    })
+    .map_err(|_| {
+        ServiceError::InternalServerError("Failed to serialize identity to JSON".into())
     })?;
 
-    Identity::login(&req.extensions(), user_string).expect("Failed to set login state for user");
+    Identity::login(&req.extensions(), identity_string)
+        .expect("Failed to set login state for user");
+    session
+        .insert(ID_TOKEN_SESSION_KEY, id_token.to_string())
+        .map_err(|_| ServiceError::InternalServerError("Could not store ID token".into()))?;
+    session
+        .insert(OIDC_PROVIDER_SESSION_KEY, oidc_provider.name.clone())
+        .map_err(|_| ServiceError::InternalServerError("Could not store OIDC provider".into()))?;
 
     Ok(HttpResponse::SeeOther()
         .insert_header(("Location", login_state.redirect_uri))
         .finish())
 }
 
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct BackchannelLogoutReqPayload {
+    /// A JWT signed by the OpenID provider identifying the session (`sid`) or user (`sub`) to log out.
+    pub logout_token: String,
+}
+
+/// OpenID Connect back-channel logout
+///
+/// Called by the OpenID provider when a session ends there, e.g. because an admin terminated it. Every session of this server which was created by logging in with that provider session, or with that user if the logout token has no `sid`, is invalidated. This should not be called directly.
+#[utoipa::path(
+    post,
+    path = "/auth/backchannel-logout/{provider}",
+    context_path = "/api",
+    tag = "auth",
+    params(
+        ("provider" = String, Path, description = "Name of the OpenID provider sending the logout token"),
+    ),
+    request_body(content = BackchannelLogoutReqPayload, description = "Form with the logout token sent by the OpenID provider", content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "The sessions belonging to the logout token were invalidated"),
+        (status = 400, description = "The logout token is invalid, expired or was already used", body = ErrorRespPayload),
+        (status = 503, description = "The OpenID provider cannot be reached", body = ErrorRespPayload),
+    )
+)]
+#[tracing::instrument(skip(data, redis_pool, oidc_providers))]
+pub async fn backchannel_logout(
+    data: web::Form<BackchannelLogoutReqPayload>,
+    redis_pool: web::Data<RedisPool>,
+    oidc_providers: web::Data<OidcProviders>,
+    provider: web::Path<String>,
+) -> Result<HttpResponse, ServiceError> {
+    let oidc_provider = oidc_providers.get(&provider)?;
+    let oidc_client = oidc_provider.client().await?;
+
+    let logout_token = data
+        .logout_token
+        .parse::<LogoutToken>()
+        .map_err(|_| ServiceError::BadRequest("Invalid logout token".to_string()))?;
+
+    // Verifies the signature against the provider JWKS, the issuer, the audience and the expiry
+    let logout_claims = oidc_provider
+        .verify_logout_token(&oidc_client, &logout_token)
+        .await
+        .map_err(|e| ServiceError::BadRequest(format!("Invalid logout token: {}", e)))?;
+
+    let ttl_seconds = (logout_claims.expires_at() - chrono::Utc::now().timestamp()).max(0) as u64;
+    if !record_logout_token_query(
+        &oidc_provider.name,
+        &logout_claims.jti,
+        ttl_seconds,
+        redis_pool.clone(),
+    )
+    .await?
+    {
+        return Err(ServiceError::BadRequest(
+            "Logout token was already used".to_string(),
+        ));
+    }
+
+    let ended_sessions = end_oidc_sessions_query(
+        oidc_provider.name.clone(),
+        logout_claims.sid.clone(),
+        logout_claims.sub.clone(),
+        redis_pool,
+    )
+    .await?;
+    log::info!(
+        "Back-channel logout for subject {} and session {} of {} ended {} sessions",
+        logout_claims.sub.as_deref().unwrap_or("-"),
+        logout_claims.sid.as_deref().unwrap_or("-"),
+        oidc_provider.name,
+        ended_sessions
+    );
+
+    Ok(HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .finish())
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+#[schema(example = json!({"providers": ["keycloak", "google"]}))]
+pub struct GetOidcProvidersRespPayload {
+    /// Names of the configured OpenID providers, the first one is the default.
+    pub providers: Vec<String>,
+}
+
+/// Get OpenID Providers
+///
+/// List the OpenID providers which can be passed as `provider` to log in.
+#[utoipa::path(
+    get,
+    path = "/auth/providers",
+    context_path = "/api",
+    tag = "auth",
+    responses(
+        (status = 200, description = "The names of the configured OpenID providers", body = GetOidcProvidersRespPayload),
+    )
+)]
+#[tracing::instrument(skip(oidc_providers))]
+pub async fn get_oidc_providers(
+    oidc_providers: web::Data<OidcProviders>,
+) -> Result<HttpResponse, ServiceError> {
+    Ok(HttpResponse::Ok().json(GetOidcProvidersRespPayload {
+        providers: oidc_providers.names(),
+    }))
+}
+
 /// Health Check
 ///
 /// Confirmation that the service is healthy
@@ -425,11 +1836,636 @@ pub async fn login_cli() -> Result<HttpResponse, ServiceError> {
     responses(
         (status = 200, description = "JSON body containing the user object", body = User),
         (status = 400, description = "Service error relating to getting the currently auth'ed user", body = ErrorRespPayload),
+        (status = 403, description = "The api key used does not have the user:read scope", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["user:read"]),
+        ("Bearer" = []),
+    )
+)]
+pub async fn whoami(user: ScopedUser<UserReadScope>) -> Result<HttpResponse, actix_web::Error> {
+    Ok(HttpResponse::Ok().json(user.user))
+}
+
+/// Get Identities
+///
+/// List the OpenID provider identities linked to the auth'ed user. Link another one by logging in with `link=true`.
+#[utoipa::path(
+    get,
+    path = "/auth/identities",
+    context_path = "/api",
+    tag = "auth",
+    responses(
+        (status = 200, description = "JSON body containing the identities of the auth'ed user", body = Vec<UserIdentity>),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The api key used does not have the user:read scope", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["user:read"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument(skip(pg_pool))]
+pub async fn get_identities(
+    user: ScopedUser<UserReadScope>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let identities = get_identities_for_user_query(user.id, pg_pool).await?;
+
+    Ok(HttpResponse::Ok().json(identities))
+}
+
+/// Unlink Identity
+///
+/// Unlink an OpenID provider identity from the auth'ed user. The last identity of an account can not be unlinked.
+#[utoipa::path(
+    delete,
+    path = "/auth/identities/{identity_id}",
+    context_path = "/api",
+    tag = "auth",
+    params(
+        ("identity_id" = uuid::Uuid, Path, description = "The id of the identity to unlink"),
+    ),
+    responses(
+        (status = 204, description = "Confirmation that the identity was unlinked"),
+        (status = 400, description = "The identity is the last one of the account", body = ErrorRespPayload),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The api key used does not have the user:write scope", body = ErrorRespPayload),
+        (status = 404, description = "The identity does not exist or does not belong to the auth'ed user", body = ErrorRespPayload),
     ),
     security(
-        ("ApiKey" = ["readonly"]),
+        ("ApiKey" = ["user:write"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument(skip(pg_pool))]
+pub async fn delete_identity(
+    user: ScopedUser<UserWriteScope>,
+    identity_id: web::Path<uuid::Uuid>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    delete_identity_query(identity_id.into_inner(), user.id, pg_pool).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// The identity waiting to be linked to the user in this browser session. Fails with `NotFound`
+/// if there is none, it expired or it was started by another login.
+fn load_pending_link(
+    req: &HttpRequest,
+    session: &Session,
+    user_id: uuid::Uuid,
+) -> Result<PendingLink, ServiceError> {
+    let pending_link = session
+        .get::<PendingLink>(PENDING_LINK_SESSION_KEY)
+        .map_err(|_| ServiceError::InternalServerError("Could not read pending link".into()))?
+        .ok_or(ServiceError::NotFound)?;
+
+    let session_id = req
+        .extensions()
+        .get::<CookieIdentity>()
+        .map(|cookie_identity| cookie_identity.session_id.clone());
+    if pending_link.user_id != user_id
+        || Some(&pending_link.session_id) != session_id.as_ref()
+        || pending_link.expires_at <= chrono::Utc::now().naive_utc()
+    {
+        return Err(ServiceError::NotFound);
+    }
+
+    Ok(pending_link)
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct PendingLinkRespPayload {
+    /// Name of the OpenID provider the identity belongs to.
+    provider: String,
+    /// The email the provider reported for the identity.
+    email: String,
+    /// The name the provider reported for the identity.
+    name: Option<String>,
+}
+
+/// Get Pending Identity Link
+///
+/// Show the identity waiting to be linked to the auth'ed user after logging in with `link=true`, so that the user can check it before confirming. Only available to the browser session which started the link.
+#[utoipa::path(
+    get,
+    path = "/auth/link",
+    context_path = "/api",
+    tag = "auth",
+    responses(
+        (status = 200, description = "JSON body describing the identity waiting to be linked", body = PendingLinkRespPayload),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the user:write scope", body = ErrorRespPayload),
+        (status = 404, description = "No identity is waiting to be linked in this browser session", body = ErrorRespPayload),
+    )
+)]
+#[tracing::instrument(skip(req, session))]
+pub async fn get_pending_link(
+    user: ScopedUser<UserWriteScope>,
+    req: HttpRequest,
+    session: Session,
+) -> Result<HttpResponse, actix_web::Error> {
+    let pending_link = load_pending_link(&req, &session, user.id)?;
+
+    Ok(HttpResponse::Ok().json(PendingLinkRespPayload {
+        provider: pending_link.provider,
+        email: pending_link.email,
+        name: pending_link.name,
+    }))
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct ConfirmLinkReqPayload {
+    /// Whether to link the identity. The pending link is discarded either way.
+    approve: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct ConfirmLinkRespPayload {
+    /// Where to send the user next, the `redirect_uri` the link was started with.
+    redirect_uri: String,
+    /// The identity which was linked, null if the link was denied.
+    identity: Option<UserIdentity>,
+}
+
+/// Confirm Identity Link
+///
+/// Link the identity waiting in this browser session to the auth'ed user, or discard it. Only available to the browser session which started the link.
+#[utoipa::path(
+    post,
+    path = "/auth/link",
+    context_path = "/api",
+    tag = "auth",
+    request_body(content = ConfirmLinkReqPayload, description = "JSON request payload to confirm or deny the identity link", content_type = "application/json"),
+    responses(
+        (status = 200, description = "JSON body with the linked identity and where to continue", body = ConfirmLinkRespPayload),
+        (status = 400, description = "The identity is already linked to another account", body = ErrorRespPayload),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the user:write scope", body = ErrorRespPayload),
+        (status = 404, description = "No identity is waiting to be linked in this browser session", body = ErrorRespPayload),
     )
 )]
-pub async fn whoami(user: AuthedUser) -> Result<HttpResponse, actix_web::Error> {
-    Ok(HttpResponse::Ok().json(user))
+#[tracing::instrument(skip(req, session, pg_pool))]
+pub async fn confirm_link(
+    user: ScopedUser<UserWriteScope>,
+    req: HttpRequest,
+    session: Session,
+    req_payload: web::Json<ConfirmLinkReqPayload>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let pending_link = load_pending_link(&req, &session, user.id)?;
+    session.remove(PENDING_LINK_SESSION_KEY);
+
+    let identity = match req_payload.approve {
+        true => Some(
+            link_identity_query(
+                user.id,
+                pending_link.provider,
+                pending_link.subject,
+                pending_link.email,
+                pending_link.name,
+                pending_link.email_verified,
+                pg_pool,
+            )
+            .await?,
+        ),
+        false => None,
+    };
+
+    Ok(HttpResponse::Ok().json(ConfirmLinkRespPayload {
+        redirect_uri: pending_link.redirect_uri,
+        identity,
+    }))
+}
+
+/// The page where users confirm linking another identity to their account.
+pub async fn link_page() -> Result<HttpResponse, ServiceError> {
+    let html_page = read_to_string("src/public/link.html").map_err(|e| {
+        ServiceError::InternalServerError(format!("Could not read identity link page {}", e))
+    })?;
+    Ok(HttpResponse::Ok().content_type("text/html").body(html_page))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    struct InMemoryPendingLoginStore(HashMap<String, String>);
+
+    impl PendingLoginStore for InMemoryPendingLoginStore {
+        async fn put(
+            &mut self,
+            key: String,
+            value: String,
+            _ttl_seconds: u64,
+        ) -> Result<(), ServiceError> {
+            self.0.insert(key, value);
+            Ok(())
+        }
+
+        async fn take(&mut self, key: String) -> Result<Option<String>, ServiceError> {
+            Ok(self.0.remove(&key))
+        }
+    }
+
+    const BROWSER_BINDING: &str = "browser-binding";
+
+    fn start_login(redirect_uri: &str) -> PendingLogin {
+        let (_, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+
+        PendingLogin {
+            oidc_state: OpenIdConnectState {
+                pkce_verifier,
+                csrf_token: CsrfToken::new_random(),
+                nonce: Nonce::new_random(),
+            },
+            login_state: LoginState {
+                redirect_uri: redirect_uri.to_string(),
+                provider: DEFAULT_OIDC_PROVIDER.to_string(),
+                link_user_id: None,
+                link_session_id: None,
+            },
+            browser_binding: browser_binding_hash(BROWSER_BINDING),
+        }
+    }
+
+    #[actix_web::test]
+    async fn concurrent_logins_do_not_clobber_each_other() {
+        let mut store = InMemoryPendingLoginStore::default();
+
+        let alice = start_login("https://alice.example.com");
+        let bob = start_login("https://bob.example.com");
+        save_pending_login(&mut store, &alice).await.unwrap();
+        save_pending_login(&mut store, &bob).await.unwrap();
+
+        // Callbacks arrive in the opposite order of the logins
+        let bob_callback = take_pending_login(
+            &mut store,
+            bob.oidc_state.csrf_token.secret(),
+            BROWSER_BINDING,
+        )
+        .await
+        .unwrap();
+        let alice_callback = take_pending_login(
+            &mut store,
+            alice.oidc_state.csrf_token.secret(),
+            BROWSER_BINDING,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            alice_callback.oidc_state.pkce_verifier.secret(),
+            alice.oidc_state.pkce_verifier.secret()
+        );
+        assert_eq!(
+            alice_callback.oidc_state.nonce.secret(),
+            alice.oidc_state.nonce.secret()
+        );
+        assert_eq!(
+            alice_callback.login_state.redirect_uri,
+            "https://alice.example.com"
+        );
+        assert_eq!(
+            bob_callback.oidc_state.pkce_verifier.secret(),
+            bob.oidc_state.pkce_verifier.secret()
+        );
+        assert_eq!(
+            bob_callback.oidc_state.nonce.secret(),
+            bob.oidc_state.nonce.secret()
+        );
+        assert_eq!(
+            bob_callback.login_state.redirect_uri,
+            "https://bob.example.com"
+        );
+    }
+
+    #[actix_web::test]
+    async fn pending_login_can_only_be_used_once() {
+        let mut store = InMemoryPendingLoginStore::default();
+
+        let login = start_login("/");
+        save_pending_login(&mut store, &login).await.unwrap();
+
+        assert!(take_pending_login(
+            &mut store,
+            login.oidc_state.csrf_token.secret(),
+            BROWSER_BINDING
+        )
+        .await
+        .is_ok());
+        assert!(matches!(
+            take_pending_login(
+                &mut store,
+                login.oidc_state.csrf_token.secret(),
+                BROWSER_BINDING
+            )
+            .await,
+            Err(ServiceError::Unauthorized)
+        ));
+    }
+
+    #[actix_web::test]
+    async fn unknown_state_is_rejected() {
+        let mut store = InMemoryPendingLoginStore::default();
+
+        let login = start_login("/");
+        save_pending_login(&mut store, &login).await.unwrap();
+
+        assert!(matches!(
+            take_pending_login(
+                &mut store,
+                CsrfToken::new_random().secret(),
+                BROWSER_BINDING
+            )
+            .await,
+            Err(ServiceError::Unauthorized)
+        ));
+        // A forged state must not consume someone else's login
+        assert!(take_pending_login(
+            &mut store,
+            login.oidc_state.csrf_token.secret(),
+            BROWSER_BINDING
+        )
+        .await
+        .is_ok());
+    }
+
+    #[actix_web::test]
+    async fn state_must_match_stored_csrf_token() {
+        let mut store = InMemoryPendingLoginStore::default();
+
+        let login = start_login("/");
+        let other_login = start_login("/");
+        // Store another login's state under this login's key
+        store.0.insert(
+            pending_login_key(login.oidc_state.csrf_token.secret()),
+            serde_json::to_string(&other_login).unwrap(),
+        );
+
+        assert!(matches!(
+            take_pending_login(
+                &mut store,
+                login.oidc_state.csrf_token.secret(),
+                BROWSER_BINDING
+            )
+            .await,
+            Err(ServiceError::Unauthorized)
+        ));
+    }
+
+    #[actix_web::test]
+    async fn callback_from_another_browser_is_rejected() {
+        let mut store = InMemoryPendingLoginStore::default();
+
+        let login = start_login("/");
+        save_pending_login(&mut store, &login).await.unwrap();
+
+        assert!(matches!(
+            take_pending_login(
+                &mut store,
+                login.oidc_state.csrf_token.secret(),
+                "other-browser-binding"
+            )
+            .await,
+            Err(ServiceError::Unauthorized)
+        ));
+    }
+
+    #[test]
+    fn redirect_uris_which_leave_the_server_are_rejected() {
+        let validate = |redirect_uri: &str| {
+            validate_redirect_uri_against(
+                redirect_uri,
+                "http://localhost:8090",
+                "https://app.example.com, https://docs.example.com/auth/*",
+            )
+        };
+
+        for redirect_uri in [
+            "/",
+            "/auth/device?user_code=ABCD-EFGH",
+            "http://localhost:8090/",
+            "https://app.example.com/anywhere",
+            "https://docs.example.com/auth/callback",
+        ] {
+            assert!(validate(redirect_uri).is_ok(), "{}", redirect_uri);
+        }
+        for redirect_uri in [
+            "//evil.com",
+            "/\\evil.com",
+            "/\t/evil.com",
+            "/%09/evil.com",
+            "/%5Cevil.com",
+            "/\n/evil.com",
+            "https://evil.com",
+            "https://docs.example.com/other",
+            "javascript:alert(1)",
+        ] {
+            assert!(validate(redirect_uri).is_err(), "{}", redirect_uri);
+        }
+    }
+
+    fn encode_jwt<C: serde::de::DeserializeOwned>(
+        header: serde_json::Value,
+        claims: serde_json::Value,
+    ) -> Jwt<C> {
+        let encode = |part: serde_json::Value| {
+            base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(part.to_string())
+        };
+
+        format!("{}.{}.c2lnbmF0dXJl", encode(header), encode(claims))
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn only_access_tokens_for_the_audience_are_accepted() {
+        let issuer_url = IssuerUrl::new("https://idp.example.com".to_string()).unwrap();
+        let jwks = CoreJsonWebKeySet::default();
+        let claims = |aud: serde_json::Value| {
+            json!({
+                "iss": "https://idp.example.com",
+                "sub": "subject",
+                "aud": aud,
+                "exp": chrono::Utc::now().timestamp() + 60,
+            })
+        };
+
+        let id_token: JwtAccessToken =
+            encode_jwt(json!({"alg": "RS256", "typ": "JWT"}), claims(json!("api")));
+        assert!(matches!(
+            id_token.verify(&issuer_url, "api", &jwks),
+            Err(ClaimsVerificationError::Unsupported(_))
+        ));
+
+        let other_issuer: JwtAccessToken = encode_jwt(
+            json!({"alg": "RS256", "typ": "at+jwt"}),
+            json!({"iss": "https://other.example.com", "sub": "subject", "aud": "api", "exp": 0}),
+        );
+        assert!(matches!(
+            other_issuer.verify(&issuer_url, "api", &jwks),
+            Err(ClaimsVerificationError::InvalidIssuer(_))
+        ));
+
+        let extra_audience: JwtAccessToken = encode_jwt(
+            json!({"alg": "RS256", "typ": "at+jwt"}),
+            claims(json!(["api", "client"])),
+        );
+        assert!(matches!(
+            extra_audience.verify(&issuer_url, "api", &jwks),
+            Err(ClaimsVerificationError::InvalidAudience(_))
+        ));
+
+        let symmetric: JwtAccessToken = encode_jwt(
+            json!({"alg": "HS256", "typ": "at+jwt"}),
+            claims(json!("api")),
+        );
+        assert!(matches!(
+            symmetric.verify(&issuer_url, "api", &jwks),
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::DisallowedAlg(_)
+            ))
+        ));
+
+        let access_token: JwtAccessToken = encode_jwt(
+            json!({"alg": "RS256"}),
+            json!({
+                "iss": "https://idp.example.com",
+                "sub": "subject",
+                "aud": "api",
+                "exp": chrono::Utc::now().timestamp() + 60,
+                "token_use": "access",
+            }),
+        );
+        assert!(matches!(
+            access_token.verify(&issuer_url, "api", &jwks),
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::NoMatchingKey
+            ))
+        ));
+    }
+
+    #[test]
+    fn logout_tokens_need_a_subject_or_session_and_no_nonce() {
+        let issuer_url = IssuerUrl::new("https://idp.example.com".to_string()).unwrap();
+        let client_id = ClientId::new("client".to_string());
+        let jwks = CoreJsonWebKeySet::default();
+        let claims = |extra_claims: serde_json::Value| {
+            let mut claims = json!({
+                "iss": "https://idp.example.com",
+                "aud": ["client", "other"],
+                "iat": chrono::Utc::now().timestamp(),
+                "jti": "jti",
+                "events": {BACKCHANNEL_LOGOUT_EVENT: {}},
+            });
+            claims
+                .as_object_mut()
+                .unwrap()
+                .extend(extra_claims.as_object().unwrap().clone());
+            claims
+        };
+        let header = json!({"alg": "RS256", "typ": "logout+jwt"});
+
+        let anonymous: LogoutToken = encode_jwt(header.clone(), claims(json!({})));
+        assert!(matches!(
+            anonymous.verify(&issuer_url, &client_id, &jwks),
+            Err(ClaimsVerificationError::Other(_))
+        ));
+
+        let with_nonce: LogoutToken = encode_jwt(
+            header.clone(),
+            claims(json!({"sub": "subject", "nonce": "n"})),
+        );
+        assert!(matches!(
+            with_nonce.verify(&issuer_url, &client_id, &jwks),
+            Err(ClaimsVerificationError::InvalidNonce(_))
+        ));
+
+        let access_token: LogoutToken = encode_jwt(
+            json!({"alg": "RS256", "typ": "at+jwt"}),
+            claims(json!({"sub": "subject"})),
+        );
+        assert!(matches!(
+            access_token.verify(&issuer_url, &client_id, &jwks),
+            Err(ClaimsVerificationError::Unsupported(_))
+        ));
+
+        let other_client: LogoutToken = encode_jwt(
+            header.clone(),
+            claims(json!({"sub": "subject", "aud": "other"})),
+        );
+        assert!(matches!(
+            other_client.verify(&issuer_url, &client_id, &jwks),
+            Err(ClaimsVerificationError::InvalidAudience(_))
+        ));
+
+        // Only the signature is missing from a token which identifies just the provider session
+        let session_only: LogoutToken = encode_jwt(header, claims(json!({"sid": "session"})));
+        assert!(matches!(
+            session_only.verify(&issuer_url, &client_id, &jwks),
+            Err(ClaimsVerificationError::SignatureVerification(
+                SignatureVerificationError::NoMatchingKey
+            ))
+        ));
+    }
+
+    #[test]
+    fn only_asserted_emails_are_marked_verified() {
+        let mapping = |email_claims: &[&str]| ClaimMapping {
+            email_claims: email_claims.iter().map(|claim| claim.to_string()).collect(),
+            name_claims: vec![],
+            name_locales: vec![],
+            require_verified_email: true,
+        };
+        let claims = |extra_claims: serde_json::Value| {
+            let mut claims = json!({
+                "iss": "https://idp.example.com",
+                "aud": ["client"],
+                "exp": chrono::Utc::now().timestamp() + 60,
+                "iat": chrono::Utc::now().timestamp(),
+                "sub": "subject",
+            });
+            claims
+                .as_object_mut()
+                .unwrap()
+                .extend(extra_claims.as_object().unwrap().clone());
+            serde_json::from_value::<openidconnect::core::CoreIdTokenClaims>(claims).unwrap()
+        };
+
+        let verified = claims(json!({"email": "user@example.com", "email_verified": true}));
+        assert!(
+            mapping(&["email"])
+                .map_profile(&verified)
+                .unwrap()
+                .email_verified
+        );
+
+        // Providers which do not send `email_verified` are not trusted for the email
+        let unasserted = claims(json!({"email": "user@example.com"}));
+        assert!(
+            !mapping(&["email"])
+                .map_profile(&unasserted)
+                .unwrap()
+                .email_verified
+        );
+
+        let unverified = claims(json!({"email": "user@example.com", "email_verified": false}));
+        assert!(mapping(&["email"]).map_profile(&unverified).is_err());
+
+        // `email_verified` says nothing about emails read from other claims
+        let username = claims(json!({
+            "preferred_username": "user@example.com",
+            "email_verified": true,
+        }));
+        let profile = mapping(&["email", "preferred_username"])
+            .map_profile(&username)
+            .unwrap();
+        assert_eq!(profile.email, "user@example.com");
+        assert!(!profile.email_verified);
+    }
 }
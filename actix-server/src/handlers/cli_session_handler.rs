@@ -0,0 +1,186 @@
+use super::auth_handler::{ApiKeyWriteScope, ScopedUser};
+use crate::{
+    data::models::{ApiKeyScope, CliSession, PgPool},
+    errors::ServiceError,
+    operators::cli_session_operator::{
+        create_cli_session_query, refresh_cli_session_query, revoke_cli_session_query,
+        CliSessionTokens,
+    },
+};
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+const MAX_CLI_SESSION_DETAIL_LENGTH: usize = 255;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "hostname": "devbox",
+    "cli_version": "0.4.1",
+    "scopes": ["api_key:read", "api_key:write", "user:read"]
+}))]
+pub struct CreateCliSessionReqPayload {
+    /// Hostname of the machine the CLI runs on, shown to tell sessions apart.
+    pub hostname: String,
+    /// Version of the CLI which is logging in.
+    pub cli_version: String,
+    /// The scopes granted to the session. Must be non-empty and a subset of the scopes of the credential used to create it.
+    pub scopes: Vec<ApiKeyScope>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "session_id": "c2c2c2c2-c2c2-c2c2-c2c2-c2c2c2c2c2c2",
+    "access_token": "tr_cliat_Ab3dEf6hIj9kAb3dEf6hIj9kAb3dEf6hIj9kAb3dEf6hIj9k",
+    "access_token_expires_at": "2021-01-01T01:00:00",
+    "refresh_token": "tr_clirt_Ab3dEf6hIj9kAb3dEf6hIj9kAb3dEf6hIj9kAb3dEf6hIj9k",
+    "expires_at": "2021-01-31T00:00:00",
+    "scopes": ["api_key:read", "api_key:write", "user:read"]
+}))]
+pub struct CliSessionTokensRespPayload {
+    pub session_id: uuid::Uuid,
+    /// Send it in the Authorization header without a prefix until it expires.
+    pub access_token: String,
+    /// The time (UTC) at which the access token stops authenticating.
+    pub access_token_expires_at: chrono::NaiveDateTime,
+    /// Exchanged at `/api/auth/cli/session/refresh` for a new pair of tokens. Every refresh token can only be used once.
+    pub refresh_token: String,
+    /// The time (UTC) at which the session expires unless it is refreshed before.
+    pub expires_at: chrono::NaiveDateTime,
+    pub scopes: Vec<ApiKeyScope>,
+}
+
+impl CliSessionTokensRespPayload {
+    fn new(tokens: CliSessionTokens, cli_session: CliSession) -> Self {
+        CliSessionTokensRespPayload {
+            session_id: cli_session.id,
+            access_token: tokens.access_token,
+            access_token_expires_at: cli_session.access_token_expires_at,
+            refresh_token: tokens.refresh_token,
+            expires_at: cli_session.expires_at,
+            scopes: cli_session.cli_session_scopes(),
+        }
+    }
+}
+
+/// Trims a detail describing a CLI, which has to be between 1 and 255 characters long.
+pub fn validate_cli_session_detail(name: &str, value: &str) -> Result<String, ServiceError> {
+    let value = value.trim();
+    if value.is_empty() || value.len() > MAX_CLI_SESSION_DETAIL_LENGTH {
+        return Err(ServiceError::BadRequest(format!(
+            "{} must be between 1 and {} characters",
+            name, MAX_CLI_SESSION_DETAIL_LENGTH
+        )));
+    }
+
+    Ok(value.to_string())
+}
+
+/// Create CLI Session
+///
+/// Log in the CLI with the auth'ed user. Instead of a long-lived api key the CLI gets a short-lived access token and a refresh token to renew it, which stop working when the CLI logs out. Called by the page at `/auth/cli`.
+#[utoipa::path(
+    post,
+    path = "/auth/cli/session",
+    context_path = "/api",
+    tag = "auth",
+    request_body(content = CreateCliSessionReqPayload, description = "JSON request payload describing the CLI which logs in", content_type = "application/json"),
+    responses(
+        (status = 201, description = "The tokens of the new session", body = CliSessionTokensRespPayload),
+        (status = 400, description = "The hostname, CLI version or scopes are invalid", body = ErrorRespPayload),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the api_key:write scope or one of the requested scopes", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["api_key:write"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument(skip(pg_pool))]
+pub async fn create_cli_session(
+    authed_user: ScopedUser<ApiKeyWriteScope>,
+    data: web::Json<CreateCliSessionReqPayload>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let hostname = validate_cli_session_detail("hostname", &data.hostname)?;
+    let cli_version = validate_cli_session_detail("cli_version", &data.cli_version)?;
+
+    let scopes = ApiKeyScope::ALL
+        .into_iter()
+        .filter(|scope| data.scopes.contains(scope))
+        .collect::<Vec<ApiKeyScope>>();
+    if scopes.is_empty() {
+        return Err(ServiceError::BadRequest(
+            "scopes must not be empty".to_string(),
+        ));
+    }
+    if scopes
+        .iter()
+        .any(|scope| !authed_user.scopes.contains(scope))
+    {
+        return Err(ServiceError::Forbidden);
+    }
+
+    let (tokens, cli_session) =
+        create_cli_session_query(authed_user.id, hostname, cli_version, scopes, pg_pool).await?;
+
+    Ok(HttpResponse::Created()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(CliSessionTokensRespPayload::new(tokens, cli_session)))
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({"refresh_token": "tr_clirt_Ab3dEf6hIj9kAb3dEf6hIj9kAb3dEf6hIj9kAb3dEf6hIj9k"}))]
+pub struct CliSessionRefreshTokenReqPayload {
+    /// The refresh token of the session.
+    pub refresh_token: String,
+}
+
+/// Refresh CLI Session
+///
+/// Exchange the refresh token of a CLI session for a new access token and refresh token. The session is extended and the previous refresh token stops working. Presenting a refresh token which was already exchanged revokes the session.
+#[utoipa::path(
+    post,
+    path = "/auth/cli/session/refresh",
+    context_path = "/api",
+    tag = "auth",
+    request_body(content = CliSessionRefreshTokenReqPayload, description = "JSON request payload with the refresh token", content_type = "application/json"),
+    responses(
+        (status = 200, description = "The new tokens of the session", body = CliSessionTokensRespPayload),
+        (status = 401, description = "The refresh token is unknown, was already used, or the session expired or was revoked", body = ErrorRespPayload),
+    )
+)]
+#[tracing::instrument(skip_all)]
+pub async fn refresh_cli_session(
+    data: web::Json<CliSessionRefreshTokenReqPayload>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let (tokens, cli_session) = refresh_cli_session_query(&data.refresh_token, pg_pool).await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(CliSessionTokensRespPayload::new(tokens, cli_session)))
+}
+
+/// Revoke CLI Session
+///
+/// Log out the CLI session of the refresh token. Its access token and refresh token stop working immediately. Unknown refresh tokens are ignored, so the response does not reveal whether the token was valid.
+#[utoipa::path(
+    post,
+    path = "/auth/cli/session/revoke",
+    context_path = "/api",
+    tag = "auth",
+    request_body(content = CliSessionRefreshTokenReqPayload, description = "JSON request payload with the refresh token", content_type = "application/json"),
+    responses(
+        (status = 204, description = "The session was revoked if the refresh token belonged to one"),
+    )
+)]
+#[tracing::instrument(skip_all)]
+pub async fn revoke_cli_session(
+    data: web::Json<CliSessionRefreshTokenReqPayload>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ServiceError> {
+    revoke_cli_session_query(&data.refresh_token, pg_pool).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
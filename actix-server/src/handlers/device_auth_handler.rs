@@ -0,0 +1,437 @@
+use super::{
+    auth_handler::{ApiKeyWriteScope, ScopedUser},
+    cli_session_handler::validate_cli_session_detail,
+};
+use crate::{
+    data::models::{ApiKeyScope, DeviceAuthorizationStatus, PgPool, RedisPool},
+    errors::ServiceError,
+    operators::{
+        cli_session_operator::create_cli_session_query,
+        device_auth_operator::{
+            create_device_authorization_query, decide_device_authorization_query,
+            get_device_authorization_by_user_code_query, poll_device_authorization_query,
+            DeviceAuthorizationPoll,
+        },
+    },
+};
+use actix_web::{web, HttpResponse};
+use serde::{Deserialize, Serialize};
+use std::fs::read_to_string;
+use utoipa::{IntoParams, ToSchema};
+
+const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+fn get_device_code_ttl_seconds() -> u64 {
+    std::env::var("DEVICE_CODE_TTL_SECONDS")
+        .ok()
+        .and_then(|ttl| ttl.parse().ok())
+        .unwrap_or(600)
+}
+
+fn get_device_code_interval_seconds() -> u64 {
+    std::env::var("DEVICE_CODE_INTERVAL_SECONDS")
+        .ok()
+        .and_then(|interval| interval.parse().ok())
+        .unwrap_or(5)
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "client_id": "actix-template-cli",
+    "scope": "api_key:read api_key:write user:read",
+    "hostname": "devbox",
+    "cli_version": "0.4.1"
+}))]
+pub struct DeviceCodeReqPayload {
+    /// Name of the client logging in.
+    pub client_id: String,
+    /// Space separated scopes of the CLI session the device gets. Defaults to every scope.
+    pub scope: Option<String>,
+    /// Hostname of the device, shown to tell sessions apart. Defaults to `unknown`.
+    pub hostname: Option<String>,
+    /// Version of the client on the device. Defaults to `unknown`.
+    pub cli_version: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "device_code": "GmRhmhcxhwAzkoEqiMEg_DnyEysNkuNhszIySk9eS",
+    "user_code": "BDFG-HJKL",
+    "verification_uri": "http://localhost:8090/auth/device",
+    "verification_uri_complete": "http://localhost:8090/auth/device?user_code=BDFG-HJKL",
+    "expires_in": 600,
+    "interval": 5
+}))]
+pub struct DeviceCodeRespPayload {
+    /// Secret the device polls `/api/auth/device/token` with. Do not show it to the user.
+    pub device_code: String,
+    /// The code the user enters at the verification URI.
+    pub user_code: String,
+    /// The page where the user logs in and enters the user code.
+    pub verification_uri: String,
+    /// The verification URI with the user code filled in.
+    pub verification_uri_complete: String,
+    /// Seconds until the device code expires.
+    pub expires_in: u64,
+    /// Seconds the device must wait between polls.
+    pub interval: u64,
+}
+
+/// Start Device Login
+///
+/// Starts the OAuth device authorization flow (RFC 8628) for clients which can not receive a browser redirect, e.g. a CLI over SSH. Show the user_code and verification_uri to the user and poll `/api/auth/device/token` with the device_code until the user approved the login.
+#[utoipa::path(
+    post,
+    path = "/auth/device/code",
+    context_path = "/api",
+    tag = "auth",
+    request_body(content = DeviceCodeReqPayload, description = "Form with the client and the requested scopes", content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "The device and user codes of the login", body = DeviceCodeRespPayload),
+        (status = 400, description = "A requested scope is unknown or the hostname or client version is invalid", body = ErrorRespPayload),
+    )
+)]
+#[tracing::instrument(skip(redis_pool))]
+pub async fn create_device_code(
+    data: web::Form<DeviceCodeReqPayload>,
+    redis_pool: web::Data<RedisPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let scopes = match &data.scope {
+        Some(scope) => scope
+            .split_whitespace()
+            .map(|scope| scope.parse::<ApiKeyScope>())
+            .collect::<Result<Vec<ApiKeyScope>, ServiceError>>()?,
+        None => ApiKeyScope::ALL.to_vec(),
+    };
+    if scopes.is_empty() {
+        return Err(ServiceError::BadRequest(
+            "scope must not be empty".to_string(),
+        ));
+    }
+
+    let hostname =
+        validate_cli_session_detail("hostname", data.hostname.as_deref().unwrap_or("unknown"))?;
+    let cli_version = validate_cli_session_detail(
+        "cli_version",
+        data.cli_version.as_deref().unwrap_or("unknown"),
+    )?;
+
+    let expires_in = get_device_code_ttl_seconds();
+    let (device_code, authorization) = create_device_authorization_query(
+        data.client_id.clone(),
+        hostname,
+        cli_version,
+        scopes,
+        expires_in,
+        redis_pool,
+    )
+    .await?;
+
+    let verification_uri = format!(
+        "{}/auth/device",
+        std::env::var("BASE_SERVER_URL").expect("BASE_SERVER_URL must be set")
+    );
+
+    Ok(HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(DeviceCodeRespPayload {
+            device_code,
+            verification_uri_complete: format!(
+                "{}?user_code={}",
+                verification_uri, authorization.user_code
+            ),
+            user_code: authorization.user_code,
+            verification_uri,
+            expires_in,
+            interval: get_device_code_interval_seconds(),
+        }))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GetDeviceAuthorizationQuery {
+    /// The code shown by the device. Case, spaces and dashes are ignored.
+    pub user_code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "client_id": "actix-template-cli",
+    "hostname": "devbox",
+    "cli_version": "0.4.1",
+    "scopes": ["api_key:read", "api_key:write", "user:read"]
+}))]
+pub struct DeviceAuthorizationRespPayload {
+    /// Name of the client which started the login.
+    pub client_id: String,
+    /// Hostname the device reported for itself.
+    pub hostname: String,
+    /// Version the client on the device reported.
+    pub cli_version: String,
+    /// The scopes the device gets when the login is approved.
+    pub scopes: Vec<ApiKeyScope>,
+}
+
+/// Get Device Login
+///
+/// Look up the device login waiting for the user code, so that the user can check which client asks for which scopes before approving it. Called by the page at `/auth/device`.
+#[utoipa::path(
+    get,
+    path = "/auth/device/verify",
+    context_path = "/api",
+    tag = "auth",
+    params(GetDeviceAuthorizationQuery),
+    responses(
+        (status = 200, description = "The client and scopes of the device login", body = DeviceAuthorizationRespPayload),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the api_key:write scope", body = ErrorRespPayload),
+        (status = 404, description = "The user code is unknown, expired or was already used", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["api_key:write"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument(skip(redis_pool))]
+pub async fn get_device_authorization(
+    _authed_user: ScopedUser<ApiKeyWriteScope>,
+    query: web::Query<GetDeviceAuthorizationQuery>,
+    redis_pool: web::Data<RedisPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let authorization =
+        get_device_authorization_by_user_code_query(&query.user_code, redis_pool).await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(DeviceAuthorizationRespPayload {
+            client_id: authorization.client_id,
+            hostname: authorization.hostname,
+            cli_version: authorization.cli_version,
+            scopes: authorization.scopes,
+        }))
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({"user_code": "BDFG-HJKL", "approve": true}))]
+pub struct VerifyDeviceCodeReqPayload {
+    /// The code shown by the device. Case, spaces and dashes are ignored.
+    pub user_code: String,
+    /// Whether to log the device in with the auth'ed user or to deny the login.
+    pub approve: bool,
+}
+
+/// Approve Device Login
+///
+/// Approve or deny the device login with the user code shown by the device. Approving lets the device exchange its device code for a CLI session of the auth'ed user. Called by the page at `/auth/device`.
+#[utoipa::path(
+    post,
+    path = "/auth/device/verify",
+    context_path = "/api",
+    tag = "auth",
+    request_body(content = VerifyDeviceCodeReqPayload, description = "JSON request payload with the user code and the decision", content_type = "application/json"),
+    responses(
+        (status = 204, description = "The decision was recorded, the device receives it on its next poll"),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the api_key:write scope or one of the scopes requested by the device", body = ErrorRespPayload),
+        (status = 404, description = "The user code is unknown, expired or was already used", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["api_key:write"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument(skip(redis_pool))]
+pub async fn verify_device_code(
+    authed_user: ScopedUser<ApiKeyWriteScope>,
+    data: web::Json<VerifyDeviceCodeReqPayload>,
+    redis_pool: web::Data<RedisPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let authorization =
+        get_device_authorization_by_user_code_query(&data.user_code, redis_pool.clone()).await?;
+
+    let status = if data.approve {
+        if authorization
+            .scopes
+            .iter()
+            .any(|scope| !authed_user.scopes.contains(scope))
+        {
+            return Err(ServiceError::Forbidden);
+        }
+        DeviceAuthorizationStatus::Approved {
+            user_id: authed_user.id,
+        }
+    } else {
+        DeviceAuthorizationStatus::Denied
+    };
+
+    decide_device_authorization_query(authorization, status, redis_pool).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "grant_type": "urn:ietf:params:oauth:grant-type:device_code",
+    "device_code": "GmRhmhcxhwAzkoEqiMEg_DnyEysNkuNhszIySk9eS",
+    "client_id": "actix-template-cli"
+}))]
+pub struct DeviceTokenReqPayload {
+    /// Must be `urn:ietf:params:oauth:grant-type:device_code`.
+    pub grant_type: String,
+    /// The device code returned by `/api/auth/device/code`.
+    pub device_code: String,
+    /// The client_id the device code was requested with.
+    pub client_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({
+    "access_token": "tr_cliat_Ab3dEf6hIj9kAb3dEf6hIj9kAb3dEf6hIj9kAb3dEf6hIj9k",
+    "token_type": "Bearer",
+    "expires_in": 3600,
+    "refresh_token": "tr_clirt_Ab3dEf6hIj9kAb3dEf6hIj9kAb3dEf6hIj9kAb3dEf6hIj9k",
+    "scope": "api_key:read api_key:write user:read",
+    "session_id": "c2c2c2c2-c2c2-c2c2-c2c2-c2c2c2c2c2c2"
+}))]
+pub struct DeviceTokenRespPayload {
+    /// The access token of a CLI session of the user who approved the login. Send it in the Authorization header.
+    pub access_token: String,
+    /// Always `Bearer`.
+    pub token_type: String,
+    /// Seconds until the access token expires.
+    pub expires_in: i64,
+    /// Exchanged at `/api/auth/cli/session/refresh` for a new pair of tokens. Every refresh token can only be used once.
+    pub refresh_token: String,
+    /// Space separated scopes of the session.
+    pub scope: String,
+    /// The id of the CLI session, listed at `/api/auth/sessions`.
+    pub session_id: uuid::Uuid,
+}
+
+/// Error response of the device token endpoint as defined by RFC 8628 section 3.5.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({"error": "authorization_pending", "error_description": "The user has not approved the login yet"}))]
+pub struct DeviceTokenErrorRespPayload {
+    /// One of `authorization_pending`, `slow_down`, `access_denied`, `expired_token`, `invalid_grant` or `unsupported_grant_type`.
+    pub error: String,
+    pub error_description: String,
+}
+
+fn device_token_error(error: &str, error_description: &str) -> HttpResponse {
+    HttpResponse::BadRequest()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(DeviceTokenErrorRespPayload {
+            error: error.to_string(),
+            error_description: error_description.to_string(),
+        })
+}
+
+/// Poll Device Login
+///
+/// Exchange the device code for the tokens of a CLI session once the user approved the login. Until then this fails with `authorization_pending`. Poll at most once per interval returned by `/api/auth/device/code`, polling faster fails with `slow_down`.
+#[utoipa::path(
+    post,
+    path = "/auth/device/token",
+    context_path = "/api",
+    tag = "auth",
+    request_body(content = DeviceTokenReqPayload, description = "Form with the device code", content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "The user approved the login", body = DeviceTokenRespPayload),
+        (status = 400, description = "The login was not approved yet, was denied or expired", body = DeviceTokenErrorRespPayload),
+    )
+)]
+#[tracing::instrument(skip(data, redis_pool, pg_pool))]
+pub async fn device_token(
+    data: web::Form<DeviceTokenReqPayload>,
+    redis_pool: web::Data<RedisPool>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ServiceError> {
+    if data.grant_type != DEVICE_CODE_GRANT_TYPE {
+        return Ok(device_token_error(
+            "unsupported_grant_type",
+            "grant_type must be urn:ietf:params:oauth:grant-type:device_code",
+        ));
+    }
+
+    let poll = poll_device_authorization_query(
+        &data.device_code,
+        get_device_code_interval_seconds(),
+        redis_pool,
+    )
+    .await?;
+
+    let authorization = match poll {
+        DeviceAuthorizationPoll::Pending => {
+            return Ok(device_token_error(
+                "authorization_pending",
+                "The user has not approved the login yet",
+            ))
+        }
+        DeviceAuthorizationPoll::SlowDown => {
+            return Ok(device_token_error(
+                "slow_down",
+                "Polled before the interval passed, wait 5 more seconds between polls",
+            ))
+        }
+        DeviceAuthorizationPoll::Denied => {
+            return Ok(device_token_error(
+                "access_denied",
+                "The user denied the login",
+            ))
+        }
+        DeviceAuthorizationPoll::Expired => {
+            return Ok(device_token_error(
+                "expired_token",
+                "The device code expired, start a new login",
+            ))
+        }
+        DeviceAuthorizationPoll::Approved(authorization) => authorization,
+    };
+
+    let DeviceAuthorizationStatus::Approved { user_id } = authorization.status else {
+        return Ok(device_token_error(
+            "access_denied",
+            "The user denied the login",
+        ));
+    };
+    if authorization.client_id != data.client_id {
+        return Ok(device_token_error(
+            "invalid_grant",
+            "The device code was issued to another client",
+        ));
+    }
+
+    let (tokens, cli_session) = create_cli_session_query(
+        user_id,
+        authorization.hostname,
+        authorization.cli_version,
+        authorization.scopes,
+        pg_pool,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(("Cache-Control", "no-store"))
+        .json(DeviceTokenRespPayload {
+            access_token: tokens.access_token,
+            token_type: "Bearer".to_string(),
+            expires_in: (cli_session.access_token_expires_at - chrono::Utc::now().naive_utc())
+                .num_seconds(),
+            refresh_token: tokens.refresh_token,
+            scope: cli_session
+                .cli_session_scopes()
+                .iter()
+                .map(|scope| scope.as_str())
+                .collect::<Vec<&str>>()
+                .join(" "),
+            session_id: cli_session.id,
+        }))
+}
+
+/// The page where users enter the user code of a device login.
+pub async fn device_page() -> Result<HttpResponse, ServiceError> {
+    let html_page = read_to_string("src/public/device.html").map_err(|e| {
+        ServiceError::InternalServerError(format!("Could not read device login page {}", e))
+    })?;
+    Ok(HttpResponse::Ok().content_type("text/html").body(html_page))
+}
@@ -1,2 +1,6 @@
 pub mod auth_handler;
 pub mod api_key_handler;
+pub mod device_auth_handler;
+pub mod cli_session_handler;
+pub mod session_handler;
+pub mod organization_handler;
@@ -0,0 +1,704 @@
+use super::auth_handler::{AuthedUser, ScopedUser, UserReadScope, UserWriteScope};
+use crate::{
+    data::models::{
+        Organization, OrganizationInvitationRespBody, OrganizationMembership, OrganizationRespBody,
+        OrganizationRole, OrganizationUserRespBody, PgPool, UserInvitationRespBody,
+    },
+    errors::ServiceError,
+    middleware::auth_middleware::ORGANIZATION_HEADER,
+    operators::organization_operator::{
+        accept_organization_invitation_query, create_organization_invitation_query,
+        create_organization_query, decline_organization_invitation_query,
+        delete_organization_invitation_query, delete_organization_query,
+        get_invitations_for_user_query, get_organization_invitations_query,
+        get_organization_users_query, get_organizations_for_user_query,
+        remove_organization_user_query, update_organization_query,
+        update_organization_user_role_query,
+    },
+};
+use actix_web::{web, FromRequest, HttpMessage, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+use utoipa::ToSchema;
+
+const MAX_ORGANIZATION_NAME_LENGTH: usize = 255;
+
+/// Marker for the least role in the active organization that a handler requires through
+/// [`OrgUser`].
+pub trait RequiredRole {
+    const ROLE: OrganizationRole;
+}
+
+pub struct OrgMemberRole;
+pub struct OrgAdminRole;
+pub struct OrgOwnerRole;
+
+impl RequiredRole for OrgMemberRole {
+    const ROLE: OrganizationRole = OrganizationRole::Member;
+}
+
+impl RequiredRole for OrgAdminRole {
+    const ROLE: OrganizationRole = OrganizationRole::Admin;
+}
+
+impl RequiredRole for OrgOwnerRole {
+    const ROLE: OrganizationRole = OrganizationRole::Owner;
+}
+
+/// An [`AuthedUser`] acting on the organization selected with the `X-Organization` header, in
+/// which it has at least the role `R`. Extraction fails with `Unauthorized` when the request is
+/// not authenticated, `BadRequest` when the header is missing or not an organization id, and
+/// `Forbidden` when the user does not belong to the organization or its role is too low.
+pub struct OrgUser<R: RequiredRole> {
+    pub user: AuthedUser,
+    pub organization: Organization,
+    pub role: OrganizationRole,
+    _role: PhantomData<R>,
+}
+
+impl<R: RequiredRole> std::fmt::Debug for OrgUser<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OrgUser")
+            .field("user", &self.user)
+            .field("organization", &self.organization)
+            .field("role", &self.role)
+            .finish()
+    }
+}
+
+impl<R: RequiredRole> FromRequest for OrgUser<R> {
+    type Error = actix_web::Error;
+    type Future = std::future::Ready<Result<OrgUser<R>, actix_web::Error>>;
+
+    #[inline]
+    fn from_request(req: &HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let organization_header = req
+            .headers()
+            .get(ORGANIZATION_HEADER)
+            .and_then(|organization_id| organization_id.to_str().ok())
+            .map(|organization_id| organization_id.trim().parse::<uuid::Uuid>());
+        let extensions = req.extensions();
+        let result = match (
+            extensions.get::<AuthedUser>(),
+            extensions.get::<OrganizationMembership>(),
+        ) {
+            (None, _) => Err(ServiceError::Unauthorized),
+            (Some(user), Some(membership)) if membership.role >= R::ROLE => Ok(OrgUser {
+                user: user.clone(),
+                organization: membership.organization.clone(),
+                role: membership.role,
+                _role: PhantomData,
+            }),
+            (Some(_), Some(_)) => Err(ServiceError::Forbidden),
+            (Some(_), None) => match organization_header {
+                None => Err(ServiceError::BadRequest(format!(
+                    "The {} header is required",
+                    ORGANIZATION_HEADER
+                ))),
+                Some(Err(_)) => Err(ServiceError::BadRequest(format!(
+                    "The {} header must be the id of an organization",
+                    ORGANIZATION_HEADER
+                ))),
+                Some(Ok(_)) => Err(ServiceError::Forbidden),
+            },
+        };
+
+        std::future::ready(result.map_err(actix_web::Error::from))
+    }
+}
+
+fn validate_organization_name(name: &str) -> Result<String, ServiceError> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > MAX_ORGANIZATION_NAME_LENGTH {
+        return Err(ServiceError::BadRequest(format!(
+            "name must be between 1 and {} characters",
+            MAX_ORGANIZATION_NAME_LENGTH
+        )));
+    }
+
+    Ok(name.to_string())
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({"name": "Trieve"}))]
+pub struct OrganizationReqPayload {
+    /// The name of the organization.
+    pub name: String,
+}
+
+/// Create Organization
+///
+/// Create an organization with the auth'ed user as its owner. Select it with the `X-Organization` header to act on it.
+#[utoipa::path(
+    post,
+    path = "/organization",
+    context_path = "/api",
+    tag = "organization",
+    request_body(content = OrganizationReqPayload, description = "JSON request payload to create an organization", content_type = "application/json"),
+    responses(
+        (status = 201, description = "The organization which was created", body = OrganizationRespBody),
+        (status = 400, description = "The name is invalid", body = ErrorRespPayload),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the user:write scope", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["user:write"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument(skip(pg_pool))]
+pub async fn create_organization(
+    authed_user: ScopedUser<UserWriteScope>,
+    data: web::Json<OrganizationReqPayload>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let name = validate_organization_name(&data.name)?;
+
+    let membership = create_organization_query(name, authed_user.id, pg_pool).await?;
+
+    Ok(HttpResponse::Created().json(OrganizationRespBody::from(membership)))
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct GetOrganizationsRespPayload {
+    /// The organizations the auth'ed user belongs to, oldest first.
+    organizations: Vec<OrganizationRespBody>,
+}
+
+/// Get Organizations
+///
+/// Get the organizations the auth'ed user belongs to along with its role in each of them.
+#[utoipa::path(
+    get,
+    path = "/organization",
+    context_path = "/api",
+    tag = "organization",
+    responses(
+        (status = 200, description = "JSON body containing the organizations of the auth'ed user", body = GetOrganizationsRespPayload),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the user:read scope", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["user:read"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument(skip(pg_pool))]
+pub async fn get_organizations(
+    authed_user: ScopedUser<UserReadScope>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let organizations = get_organizations_for_user_query(authed_user.id, pg_pool).await?;
+
+    Ok(HttpResponse::Ok().json(GetOrganizationsRespPayload {
+        organizations: organizations
+            .into_iter()
+            .map(OrganizationRespBody::from)
+            .collect(),
+    }))
+}
+
+/// Get Current Organization
+///
+/// Get the organization selected with the `X-Organization` header along with the role of the auth'ed user in it.
+#[utoipa::path(
+    get,
+    path = "/organization/current",
+    context_path = "/api",
+    tag = "organization",
+    params(
+        ("X-Organization" = uuid::Uuid, Header, description = "The id of the organization to act on"),
+    ),
+    responses(
+        (status = 200, description = "JSON body representing the organization", body = OrganizationRespBody),
+        (status = 400, description = "The X-Organization header is missing or invalid", body = ErrorRespPayload),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the user:read scope or the auth'ed user does not belong to the organization", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["user:read"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument]
+pub async fn get_organization(
+    _authed_user: ScopedUser<UserReadScope>,
+    org_user: OrgUser<OrgMemberRole>,
+) -> Result<HttpResponse, ServiceError> {
+    Ok(
+        HttpResponse::Ok().json(OrganizationRespBody::from(OrganizationMembership {
+            organization: org_user.organization,
+            role: org_user.role,
+        })),
+    )
+}
+
+/// Update Current Organization
+///
+/// Rename the organization selected with the `X-Organization` header. Requires the admin role.
+#[utoipa::path(
+    put,
+    path = "/organization/current",
+    context_path = "/api",
+    tag = "organization",
+    params(
+        ("X-Organization" = uuid::Uuid, Header, description = "The id of the organization to act on"),
+    ),
+    request_body(content = OrganizationReqPayload, description = "JSON request payload with the new name of the organization", content_type = "application/json"),
+    responses(
+        (status = 200, description = "JSON body representing the updated organization", body = OrganizationRespBody),
+        (status = 400, description = "The name or the X-Organization header is invalid", body = ErrorRespPayload),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the user:write scope or the auth'ed user is not an admin of the organization", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["user:write"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument(skip(pg_pool))]
+pub async fn update_organization(
+    _authed_user: ScopedUser<UserWriteScope>,
+    org_user: OrgUser<OrgAdminRole>,
+    data: web::Json<OrganizationReqPayload>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let name = validate_organization_name(&data.name)?;
+
+    let organization = update_organization_query(org_user.organization.id, name, pg_pool).await?;
+
+    Ok(
+        HttpResponse::Ok().json(OrganizationRespBody::from(OrganizationMembership {
+            organization,
+            role: org_user.role,
+        })),
+    )
+}
+
+/// Delete Current Organization
+///
+/// Delete the organization selected with the `X-Organization` header and all of its memberships. Requires the owner role.
+#[utoipa::path(
+    delete,
+    path = "/organization/current",
+    context_path = "/api",
+    tag = "organization",
+    params(
+        ("X-Organization" = uuid::Uuid, Header, description = "The id of the organization to act on"),
+    ),
+    responses(
+        (status = 204, description = "Confirmation that the organization was deleted"),
+        (status = 400, description = "The X-Organization header is missing or invalid", body = ErrorRespPayload),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the user:write scope or the auth'ed user is not an owner of the organization", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["user:write"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument(skip(pg_pool))]
+pub async fn delete_organization(
+    _authed_user: ScopedUser<UserWriteScope>,
+    org_user: OrgUser<OrgOwnerRole>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ServiceError> {
+    delete_organization_query(org_user.organization.id, pg_pool).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct GetOrganizationUsersRespPayload {
+    /// The members of the organization, in the order they were added.
+    users: Vec<OrganizationUserRespBody>,
+}
+
+/// Get Organization Users
+///
+/// Get the members of the organization selected with the `X-Organization` header and their roles.
+#[utoipa::path(
+    get,
+    path = "/organization/current/users",
+    context_path = "/api",
+    tag = "organization",
+    params(
+        ("X-Organization" = uuid::Uuid, Header, description = "The id of the organization to act on"),
+    ),
+    responses(
+        (status = 200, description = "JSON body containing the members of the organization", body = GetOrganizationUsersRespPayload),
+        (status = 400, description = "The X-Organization header is missing or invalid", body = ErrorRespPayload),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the user:read scope or the auth'ed user does not belong to the organization", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["user:read"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument(skip(pg_pool))]
+pub async fn get_members(
+    _authed_user: ScopedUser<UserReadScope>,
+    org_user: OrgUser<OrgMemberRole>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let users = get_organization_users_query(org_user.organization.id, pg_pool).await?;
+
+    Ok(HttpResponse::Ok().json(GetOrganizationUsersRespPayload {
+        users: users
+            .into_iter()
+            .map(OrganizationUserRespBody::from)
+            .collect(),
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({"email": "developers@trieve.ai", "role": "member"}))]
+pub struct InviteOrganizationUserReqPayload {
+    /// The email to invite. The user with this email joins the organization by accepting the invitation.
+    pub email: String,
+    /// The role the invitee gets when accepting. Can not be higher than the role of the auth'ed user.
+    pub role: OrganizationRole,
+}
+
+/// Invite Organization User
+///
+/// Invite an email to the organization selected with the `X-Organization` header. The user with the email joins by accepting the invitation, inviting the same email again replaces its pending invitation. The response is the same whether or not a user with the email exists. Requires the admin role, and the owner role to invite owners.
+#[utoipa::path(
+    post,
+    path = "/organization/current/invitations",
+    context_path = "/api",
+    tag = "organization",
+    params(
+        ("X-Organization" = uuid::Uuid, Header, description = "The id of the organization to act on"),
+    ),
+    request_body(content = InviteOrganizationUserReqPayload, description = "JSON request payload with the email to invite", content_type = "application/json"),
+    responses(
+        (status = 202, description = "Confirmation that the email was invited"),
+        (status = 400, description = "The email or the X-Organization header is invalid", body = ErrorRespPayload),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the user:write scope or the auth'ed user can not grant the role", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["user:write"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument(skip(pg_pool))]
+pub async fn invite_member(
+    _authed_user: ScopedUser<UserWriteScope>,
+    org_user: OrgUser<OrgAdminRole>,
+    data: web::Json<InviteOrganizationUserReqPayload>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ServiceError> {
+    if data.role > org_user.role {
+        return Err(ServiceError::Forbidden);
+    }
+
+    let email = data.email.trim();
+    if !email.contains('@') {
+        return Err(ServiceError::BadRequest("email is invalid".to_string()));
+    }
+
+    create_organization_invitation_query(
+        org_user.organization.id,
+        email.to_string(),
+        data.role,
+        org_user.user.id,
+        pg_pool,
+    )
+    .await?;
+
+    Ok(HttpResponse::Accepted().finish())
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct GetOrganizationInvitationsRespPayload {
+    /// The pending invitations of the organization, newest first.
+    invitations: Vec<OrganizationInvitationRespBody>,
+}
+
+/// Get Organization Invitations
+///
+/// Get the pending invitations of the organization selected with the `X-Organization` header. Requires the admin role.
+#[utoipa::path(
+    get,
+    path = "/organization/current/invitations",
+    context_path = "/api",
+    tag = "organization",
+    params(
+        ("X-Organization" = uuid::Uuid, Header, description = "The id of the organization to act on"),
+    ),
+    responses(
+        (status = 200, description = "JSON body containing the pending invitations of the organization", body = GetOrganizationInvitationsRespPayload),
+        (status = 400, description = "The X-Organization header is missing or invalid", body = ErrorRespPayload),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the user:read scope or the auth'ed user is not an admin of the organization", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["user:read"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument(skip(pg_pool))]
+pub async fn get_invitations(
+    _authed_user: ScopedUser<UserReadScope>,
+    org_user: OrgUser<OrgAdminRole>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let invitations = get_organization_invitations_query(org_user.organization.id, pg_pool).await?;
+
+    Ok(
+        HttpResponse::Ok().json(GetOrganizationInvitationsRespPayload {
+            invitations: invitations
+                .into_iter()
+                .map(OrganizationInvitationRespBody::from)
+                .collect(),
+        }),
+    )
+}
+
+/// Delete Organization Invitation
+///
+/// Withdraw a pending invitation of the organization selected with the `X-Organization` header. Requires the admin role.
+#[utoipa::path(
+    delete,
+    path = "/organization/current/invitations/{invitation_id}",
+    context_path = "/api",
+    tag = "organization",
+    params(
+        ("X-Organization" = uuid::Uuid, Header, description = "The id of the organization to act on"),
+        ("invitation_id" = uuid::Uuid, Path, description = "The id of the invitation to withdraw"),
+    ),
+    responses(
+        (status = 204, description = "Confirmation that the invitation was withdrawn"),
+        (status = 400, description = "The X-Organization header is missing or invalid", body = ErrorRespPayload),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the user:write scope or the auth'ed user is not an admin of the organization", body = ErrorRespPayload),
+        (status = 404, description = "The organization has no such invitation", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["user:write"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument(skip(pg_pool))]
+pub async fn delete_invitation(
+    _authed_user: ScopedUser<UserWriteScope>,
+    org_user: OrgUser<OrgAdminRole>,
+    invitation_id: web::Path<uuid::Uuid>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ServiceError> {
+    delete_organization_invitation_query(
+        org_user.organization.id,
+        invitation_id.into_inner(),
+        pg_pool,
+    )
+    .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct GetUserInvitationsRespPayload {
+    /// The pending invitations sent to the verified emails of the auth'ed user, newest first.
+    invitations: Vec<UserInvitationRespBody>,
+}
+
+/// Get User Invitations
+///
+/// Get the pending invitations sent to the verified emails of the auth'ed user. An email is verified when the provider of one of the user's logins asserted it with the `email_verified` claim.
+#[utoipa::path(
+    get,
+    path = "/organization/invitations",
+    context_path = "/api",
+    tag = "organization",
+    responses(
+        (status = 200, description = "JSON body containing the pending invitations of the auth'ed user", body = GetUserInvitationsRespPayload),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the user:read scope", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["user:read"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument(skip(pg_pool))]
+pub async fn get_user_invitations(
+    authed_user: ScopedUser<UserReadScope>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let invitations = get_invitations_for_user_query(authed_user.id, pg_pool).await?;
+
+    Ok(HttpResponse::Ok().json(GetUserInvitationsRespPayload {
+        invitations: invitations
+            .into_iter()
+            .map(UserInvitationRespBody::from)
+            .collect(),
+    }))
+}
+
+/// Accept Invitation
+///
+/// Accept an invitation sent to a verified email of the auth'ed user, joining the organization with the invited role.
+#[utoipa::path(
+    post,
+    path = "/organization/invitations/{invitation_id}/accept",
+    context_path = "/api",
+    tag = "organization",
+    params(
+        ("invitation_id" = uuid::Uuid, Path, description = "The id of the invitation to accept"),
+    ),
+    responses(
+        (status = 201, description = "The organization which was joined", body = OrganizationRespBody),
+        (status = 400, description = "The auth'ed user is already a member of the organization", body = ErrorRespPayload),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the user:write scope", body = ErrorRespPayload),
+        (status = 404, description = "There is no pending invitation with the id for the auth'ed user", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["user:write"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument(skip(pg_pool))]
+pub async fn accept_invitation(
+    authed_user: ScopedUser<UserWriteScope>,
+    invitation_id: web::Path<uuid::Uuid>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let membership =
+        accept_organization_invitation_query(invitation_id.into_inner(), authed_user.id, pg_pool)
+            .await?;
+
+    Ok(HttpResponse::Created().json(OrganizationRespBody::from(membership)))
+}
+
+/// Decline Invitation
+///
+/// Decline an invitation sent to a verified email of the auth'ed user.
+#[utoipa::path(
+    delete,
+    path = "/organization/invitations/{invitation_id}",
+    context_path = "/api",
+    tag = "organization",
+    params(
+        ("invitation_id" = uuid::Uuid, Path, description = "The id of the invitation to decline"),
+    ),
+    responses(
+        (status = 204, description = "Confirmation that the invitation was declined"),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the user:write scope", body = ErrorRespPayload),
+        (status = 404, description = "There is no invitation with the id for the auth'ed user", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["user:write"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument(skip(pg_pool))]
+pub async fn decline_invitation(
+    authed_user: ScopedUser<UserWriteScope>,
+    invitation_id: web::Path<uuid::Uuid>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ServiceError> {
+    decline_organization_invitation_query(invitation_id.into_inner(), authed_user.id, pg_pool)
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[schema(example = json!({"role": "admin"}))]
+pub struct UpdateOrganizationUserReqPayload {
+    /// The new role of the user. Can not be higher than the role of the auth'ed user.
+    pub role: OrganizationRole,
+}
+
+/// Update Organization User
+///
+/// Change the role of a member of the organization selected with the `X-Organization` header. Requires the admin role, and the owner role to change the role of owners or make someone an owner. The last owner can not be demoted.
+#[utoipa::path(
+    put,
+    path = "/organization/current/users/{user_id}",
+    context_path = "/api",
+    tag = "organization",
+    params(
+        ("X-Organization" = uuid::Uuid, Header, description = "The id of the organization to act on"),
+        ("user_id" = uuid::Uuid, Path, description = "The id of the member to update"),
+    ),
+    request_body(content = UpdateOrganizationUserReqPayload, description = "JSON request payload with the new role", content_type = "application/json"),
+    responses(
+        (status = 200, description = "The updated membership", body = OrganizationUserRespBody),
+        (status = 400, description = "The member is the last owner or the X-Organization header is invalid", body = ErrorRespPayload),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the user:write scope or the auth'ed user can not change the role", body = ErrorRespPayload),
+        (status = 404, description = "The user is not a member of the organization", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["user:write"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument(skip(pg_pool))]
+pub async fn update_member(
+    _authed_user: ScopedUser<UserWriteScope>,
+    org_user: OrgUser<OrgAdminRole>,
+    user_id: web::Path<uuid::Uuid>,
+    data: web::Json<UpdateOrganizationUserReqPayload>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let organization_user = update_organization_user_role_query(
+        org_user.organization.id,
+        user_id.into_inner(),
+        data.role,
+        org_user.role,
+        pg_pool,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(OrganizationUserRespBody::from(organization_user)))
+}
+
+/// Remove Organization User
+///
+/// Remove a member from the organization selected with the `X-Organization` header. Every member can remove itself to leave the organization, removing others requires the admin role and the owner role to remove owners. The last owner can not be removed.
+#[utoipa::path(
+    delete,
+    path = "/organization/current/users/{user_id}",
+    context_path = "/api",
+    tag = "organization",
+    params(
+        ("X-Organization" = uuid::Uuid, Header, description = "The id of the organization to act on"),
+        ("user_id" = uuid::Uuid, Path, description = "The id of the member to remove"),
+    ),
+    responses(
+        (status = 204, description = "Confirmation that the user was removed from the organization"),
+        (status = 400, description = "The member is the last owner or the X-Organization header is invalid", body = ErrorRespPayload),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the user:write scope or the auth'ed user can not remove the member", body = ErrorRespPayload),
+        (status = 404, description = "The user is not a member of the organization", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["user:write"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument(skip(pg_pool))]
+pub async fn remove_member(
+    _authed_user: ScopedUser<UserWriteScope>,
+    org_user: OrgUser<OrgMemberRole>,
+    user_id: web::Path<uuid::Uuid>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ServiceError> {
+    remove_organization_user_query(
+        org_user.organization.id,
+        user_id.into_inner(),
+        org_user.user.id,
+        org_user.role,
+        pg_pool,
+    )
+    .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
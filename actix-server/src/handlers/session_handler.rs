@@ -0,0 +1,162 @@
+use super::auth_handler::{ScopedUser, UserReadScope, UserWriteScope};
+use crate::{
+    data::models::{CliSession, CookieIdentity, PgPool, RedisPool, SessionRespBody},
+    errors::ServiceError,
+    operators::{
+        cli_session_operator::{
+            get_cli_sessions_for_user_query, revoke_cli_sessions_for_user_query,
+        },
+        session_operator::{
+            end_user_session_query, end_user_sessions_query, get_user_sessions_query,
+        },
+    },
+};
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct GetSessionsRespPayload {
+    /// The active sessions of the auth'ed user, browser sessions first and newest first.
+    sessions: Vec<SessionRespBody>,
+}
+
+/// Get Sessions
+///
+/// List the active sessions of the auth'ed user: every browser logged in with the session cookie and every logged in CLI. Api keys are listed at `/api/api_key`.
+#[utoipa::path(
+    get,
+    path = "/auth/sessions",
+    context_path = "/api",
+    tag = "auth",
+    responses(
+        (status = 200, description = "JSON body containing the sessions of the auth'ed user", body = GetSessionsRespPayload),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the user:read scope", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["user:read"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument(skip(req, redis_pool, pg_pool))]
+pub async fn get_sessions(
+    user: ScopedUser<UserReadScope>,
+    req: HttpRequest,
+    redis_pool: web::Data<RedisPool>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let current_browser_session_id = req
+        .extensions()
+        .get::<CookieIdentity>()
+        .and_then(|cookie_identity| cookie_identity.session_id.parse::<uuid::Uuid>().ok());
+    let current_cli_session_id = req
+        .extensions()
+        .get::<CliSession>()
+        .map(|cli_session| cli_session.id);
+
+    let browser_sessions = get_user_sessions_query(user.id, redis_pool).await?;
+    let cli_sessions = get_cli_sessions_for_user_query(user.id, pg_pool).await?;
+
+    let sessions = browser_sessions
+        .into_iter()
+        .map(SessionRespBody::from)
+        .chain(cli_sessions.into_iter().map(SessionRespBody::from))
+        .map(|session| SessionRespBody {
+            current: Some(session.id) == current_browser_session_id
+                || Some(session.id) == current_cli_session_id,
+            ..session
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(GetSessionsRespPayload { sessions }))
+}
+
+/// Revoke Session
+///
+/// Log out one session of the auth'ed user. A browser session stops authenticating with its next request, a CLI session can no longer use or refresh its tokens.
+#[utoipa::path(
+    delete,
+    path = "/auth/sessions/{session_id}",
+    context_path = "/api",
+    tag = "auth",
+    params(
+        ("session_id" = uuid::Uuid, Path, description = "The id of the session to revoke"),
+    ),
+    responses(
+        (status = 204, description = "Confirmation that the session was revoked"),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the user:write scope", body = ErrorRespPayload),
+        (status = 404, description = "The session does not exist, already ended or does not belong to the auth'ed user", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["user:write"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument(skip(redis_pool, pg_pool))]
+pub async fn delete_session(
+    user: ScopedUser<UserWriteScope>,
+    session_id: web::Path<uuid::Uuid>,
+    redis_pool: web::Data<RedisPool>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let session_id = session_id.into_inner();
+
+    if end_user_session_query(user.id, &session_id.to_string(), redis_pool).await? {
+        return Ok(HttpResponse::NoContent().finish());
+    }
+    if revoke_cli_sessions_for_user_query(user.id, Some(session_id), pg_pool).await? > 0 {
+        return Ok(HttpResponse::NoContent().finish());
+    }
+
+    Err(ServiceError::NotFound)
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct RevokeSessionsRespPayload {
+    /// The number of browser sessions which were logged out.
+    browser_sessions: usize,
+    /// The number of CLI sessions which were logged out.
+    cli_sessions: usize,
+}
+
+/// Revoke All Sessions
+///
+/// Sign out everywhere: log out every browser and CLI session of the auth'ed user, including the one used for this request. Api keys keep working and have to be deleted separately.
+#[utoipa::path(
+    delete,
+    path = "/auth/sessions",
+    context_path = "/api",
+    tag = "auth",
+    responses(
+        (status = 200, description = "The number of sessions which were revoked", body = RevokeSessionsRespPayload),
+        (status = 401, description = "Service error relating to authentication status of the user", body = ErrorRespPayload),
+        (status = 403, description = "The credential used does not have the user:write scope", body = ErrorRespPayload),
+    ),
+    security(
+        ("ApiKey" = ["user:write"]),
+        ("Bearer" = []),
+    )
+)]
+#[tracing::instrument(skip(redis_pool, pg_pool))]
+pub async fn delete_sessions(
+    user: ScopedUser<UserWriteScope>,
+    redis_pool: web::Data<RedisPool>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ServiceError> {
+    let browser_sessions = end_user_sessions_query(user.id, redis_pool).await?;
+    let cli_sessions = revoke_cli_sessions_for_user_query(user.id, None, pg_pool).await?;
+
+    log::info!(
+        "User {} signed out of {} browser and {} CLI sessions",
+        user.id,
+        browser_sessions,
+        cli_sessions
+    );
+
+    Ok(HttpResponse::Ok().json(RevokeSessionsRespPayload {
+        browser_sessions,
+        cli_sessions,
+    }))
+}
@@ -3,7 +3,12 @@
 #[macro_use]
 extern crate diesel;
 use crate::{
-    errors::ServiceError, handlers::auth_handler::build_oidc_client, middleware::auth_middleware,
+    errors::ServiceError,
+    handlers::auth_handler::build_oidc_providers,
+    middleware::{
+        auth_middleware,
+        rate_limit_middleware::{RateLimitMiddlewareFactory, RateLimitPolicy},
+    },
 };
 use actix_cors::Cors;
 use actix_identity::IdentityMiddleware;
@@ -41,13 +46,18 @@ pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations");
 fn run_migrations(url: &str) {
     use diesel::prelude::*;
     let mut conn = diesel::pg::PgConnection::establish(url).expect("Failed to connect to database");
+    // Read by the migration which links users created before identities existed
+    diesel::sql_query("SELECT set_config('actix_server.default_oidc_provider', $1, false)")
+        .bind::<diesel::sql_types::Text, _>(&handlers::auth_handler::get_oidc_provider_names()[0])
+        .execute(&mut conn)
+        .expect("Failed to set default OIDC provider for migrations");
     conn.run_pending_migrations(MIGRATIONS)
         .expect("Failed to run migrations");
 }
 
 pub fn establish_connection(
     config: &str,
-) -> BoxFuture<diesel::ConnectionResult<diesel_async::AsyncPgConnection>> {
+) -> BoxFuture<'_, diesel::ConnectionResult<diesel_async::AsyncPgConnection>> {
     let fut = async {
         let mut tls = SslConnector::builder(SslMethod::tls()).unwrap();
 
@@ -69,7 +79,7 @@ pub fn establish_connection(
 }
 
 use utoipa::{
-    openapi::security::{ApiKey, ApiKeyValue, SecurityScheme},
+    openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
     Modify, OpenApi,
 };
 struct SecurityAddon;
@@ -84,6 +94,18 @@ impl Modify for SecurityAddon {
             "ApiKey",
             SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("Authorization"))),
         );
+        components.add_security_scheme(
+            "Bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .description(Some(
+                        "An access token issued by one of the configured OpenID providers",
+                    ))
+                    .build(),
+            ),
+        );
     }
 }
 
@@ -113,20 +135,97 @@ impl Modify for SecurityAddon {
         handlers::auth_handler::logout,
         handlers::auth_handler::whoami,
         handlers::auth_handler::callback,
+        handlers::auth_handler::backchannel_logout,
+        handlers::auth_handler::get_oidc_providers,
+        handlers::auth_handler::get_identities,
+        handlers::auth_handler::delete_identity,
+        handlers::auth_handler::get_pending_link,
+        handlers::auth_handler::confirm_link,
+        handlers::device_auth_handler::create_device_code,
+        handlers::device_auth_handler::get_device_authorization,
+        handlers::device_auth_handler::verify_device_code,
+        handlers::device_auth_handler::device_token,
+        handlers::cli_session_handler::create_cli_session,
+        handlers::cli_session_handler::refresh_cli_session,
+        handlers::cli_session_handler::revoke_cli_session,
+        handlers::session_handler::get_sessions,
+        handlers::session_handler::delete_session,
+        handlers::session_handler::delete_sessions,
         handlers::api_key_handler::create_api_key,
+        handlers::api_key_handler::get_api_keys,
+        handlers::api_key_handler::get_api_key,
+        handlers::api_key_handler::delete_api_key,
+        handlers::api_key_handler::get_current_api_key,
+        handlers::api_key_handler::rotate_api_key,
+        handlers::api_key_handler::revoke_leaked_api_keys,
+        handlers::organization_handler::create_organization,
+        handlers::organization_handler::get_organizations,
+        handlers::organization_handler::get_organization,
+        handlers::organization_handler::update_organization,
+        handlers::organization_handler::delete_organization,
+        handlers::organization_handler::get_members,
+        handlers::organization_handler::update_member,
+        handlers::organization_handler::remove_member,
+        handlers::organization_handler::invite_member,
+        handlers::organization_handler::get_invitations,
+        handlers::organization_handler::delete_invitation,
+        handlers::organization_handler::get_user_invitations,
+        handlers::organization_handler::accept_invitation,
+        handlers::organization_handler::decline_invitation,
         handlers::auth_handler::health_check,
     ),
     components(
         schemas(
             handlers::api_key_handler::CreateApiKeyRespPayload,
             handlers::api_key_handler::CreateApiKeyReqPayload,
+            handlers::api_key_handler::GetApiKeysRespPayload,
+            handlers::api_key_handler::RotateApiKeyReqPayload,
+            handlers::api_key_handler::RotateApiKeyRespPayload,
+            handlers::api_key_handler::RevokeLeakedApiKeysRespPayload,
+            handlers::auth_handler::LogoutRespPayload,
+            handlers::auth_handler::BackchannelLogoutReqPayload,
+            handlers::auth_handler::GetOidcProvidersRespPayload,
+            handlers::auth_handler::PendingLinkRespPayload,
+            handlers::auth_handler::ConfirmLinkReqPayload,
+            handlers::auth_handler::ConfirmLinkRespPayload,
+            handlers::device_auth_handler::DeviceCodeReqPayload,
+            handlers::device_auth_handler::DeviceCodeRespPayload,
+            handlers::device_auth_handler::DeviceAuthorizationRespPayload,
+            handlers::device_auth_handler::VerifyDeviceCodeReqPayload,
+            handlers::device_auth_handler::DeviceTokenReqPayload,
+            handlers::device_auth_handler::DeviceTokenRespPayload,
+            handlers::device_auth_handler::DeviceTokenErrorRespPayload,
+            handlers::cli_session_handler::CreateCliSessionReqPayload,
+            handlers::cli_session_handler::CliSessionTokensRespPayload,
+            handlers::cli_session_handler::CliSessionRefreshTokenReqPayload,
+            handlers::session_handler::GetSessionsRespPayload,
+            handlers::session_handler::RevokeSessionsRespPayload,
+            handlers::organization_handler::OrganizationReqPayload,
+            handlers::organization_handler::GetOrganizationsRespPayload,
+            handlers::organization_handler::GetOrganizationUsersRespPayload,
+            handlers::organization_handler::UpdateOrganizationUserReqPayload,
+            handlers::organization_handler::InviteOrganizationUserReqPayload,
+            handlers::organization_handler::GetOrganizationInvitationsRespPayload,
+            handlers::organization_handler::GetUserInvitationsRespPayload,
+            models::LeakedApiKey,
+            models::ApiKeyRespBody,
+            models::ApiKeyScope,
             models::User,
+            models::UserIdentity,
+            models::SessionRespBody,
+            models::SessionKind,
+            models::OrganizationRole,
+            models::OrganizationRespBody,
+            models::OrganizationUserRespBody,
+            models::OrganizationInvitationRespBody,
+            models::UserInvitationRespBody,
             errors::ErrorRespPayload,
         )
     ),
     tags(
         (name = "auth", description = "Authentication endpoints. Used to authenticate users."),
         (name = "api_key", description = "API Key endpoints. Used to manage user API keys."),
+        (name = "organization", description = "Organization endpoints. Used to manage organizations and their members."),
         (name = "health", description = "Health check endpoint. Used to check if the server is up and running."),
     ),
 )]
@@ -174,6 +273,10 @@ pub fn main() -> std::io::Result<()> {
         None
     };
 
+    // Fail on startup rather than on the first request if the peppers or proxies are misconfigured
+    operators::api_key_operator::get_api_key_peppers();
+    middleware::auth_middleware::get_trusted_proxies();
+
     let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL should be set");
     let redis_url = std::env::var("REDIS_URL").expect("REDIS_URL should be set");
 
@@ -212,10 +315,44 @@ pub fn main() -> std::io::Result<()> {
             .await
             .expect("Failed to create redis pool");
 
-        let oidc_client = build_oidc_client().await;
+        let oidc_providers = build_oidc_providers();
+        let oidc_refresh_interval: u64 = std::env::var("OIDC_REFRESH_INTERVAL_SECONDS")
+            .unwrap_or("3600".to_string())
+            .parse()
+            .unwrap_or(3600);
+        oidc_providers.keep_discovered(std::time::Duration::from_secs(oidc_refresh_interval));
+
+        let api_key_usage_flush_interval: u64 =
+            std::env::var("API_KEY_USAGE_FLUSH_INTERVAL_SECONDS")
+                .unwrap_or("60".to_string())
+                .parse()
+                .unwrap_or(60);
+        let flush_redis_pool = web::Data::new(redis_pool.clone());
+        let flush_pg_pool = web::Data::new(pg_pool.clone());
+        actix_web::rt::spawn(async move {
+            let mut interval = actix_web::rt::time::interval(std::time::Duration::from_secs(
+                api_key_usage_flush_interval,
+            ));
+            loop {
+                interval.tick().await;
+                if let Err(e) = operators::api_key_operator::flush_api_key_usage_query(
+                    flush_redis_pool.clone(),
+                    flush_pg_pool.clone(),
+                )
+                .await
+                {
+                    log::error!("Failed to flush api key usage: {:?}", e);
+                }
+            }
+        });
 
         println!("{:?}", std::env::var("SECRET_KEY"));
 
+        let auth_rate_limit_policy = RateLimitPolicy::from_env("auth", 30, 60);
+        let auth_session_rate_limit_policy = RateLimitPolicy::from_env("auth_session", 300, 60);
+        let api_key_rate_limit_policy = RateLimitPolicy::from_env("api_key", 300, 60);
+        let organization_rate_limit_policy = RateLimitPolicy::from_env("organization", 300, 60);
+
         HttpServer::new(move || {
             App::new()
                 .app_data(PayloadConfig::new(134200000))
@@ -232,7 +369,7 @@ pub fn main() -> std::io::Result<()> {
                     }),
                 )
                 .app_data(web::Data::new(pg_pool.clone()))
-                .app_data(web::Data::new(oidc_client.clone()))
+                .app_data(web::Data::new(oidc_providers.clone()))
                 .app_data(web::Data::new(redis_pool.clone()))
                 .wrap(sentry_actix::Sentry::new())
                 .wrap(auth_middleware::AuthMiddlewareFactory)
@@ -272,30 +409,249 @@ pub fn main() -> std::io::Result<()> {
                     web::resource("/auth/cli")
                         .route(web::get().to(handlers::auth_handler::login_cli)),
                 )
+                .service(
+                    web::resource("/auth/device")
+                        .route(web::get().to(handlers::device_auth_handler::device_page)),
+                )
+                .service(
+                    web::resource("/auth/link")
+                        .route(web::get().to(handlers::auth_handler::link_page)),
+                )
                 .service(
                     web::scope("/api")
+                        // Registered before the rate limited /auth scope, which would otherwise
+                        // match it first. The provider may send many logout tokens at once.
+                        .service(
+                            web::resource("/auth/backchannel-logout/{provider}")
+                                .route(web::post().to(handlers::auth_handler::backchannel_logout)),
+                        )
+                        // Called repeatedly by running clients, e.g. CLIs polling for a device
+                        // login or refreshing their session, so they get a looser limit than
+                        // logging in. Registered before the /auth scope for the same reason.
+                        .service(
+                            web::resource("/auth/whoami")
+                                .wrap(RateLimitMiddlewareFactory::new(
+                                    auth_session_rate_limit_policy.clone(),
+                                ))
+                                .route(web::get().to(handlers::auth_handler::whoami)),
+                        )
+                        .service(
+                            web::resource("/auth/device/token")
+                                .wrap(RateLimitMiddlewareFactory::new(
+                                    auth_session_rate_limit_policy.clone(),
+                                ))
+                                .route(web::post().to(handlers::device_auth_handler::device_token)),
+                        )
+                        .service(
+                            web::resource("/auth/cli/session/refresh")
+                                .wrap(RateLimitMiddlewareFactory::new(
+                                    auth_session_rate_limit_policy.clone(),
+                                ))
+                                .route(
+                                    web::post()
+                                        .to(handlers::cli_session_handler::refresh_cli_session),
+                                ),
+                        )
+                        .service(
+                            web::resource("/auth/sessions")
+                                .wrap(RateLimitMiddlewareFactory::new(
+                                    auth_session_rate_limit_policy.clone(),
+                                ))
+                                .route(web::get().to(handlers::session_handler::get_sessions))
+                                .route(
+                                    web::delete().to(handlers::session_handler::delete_sessions),
+                                ),
+                        )
+                        .service(
+                            web::resource("/auth/sessions/{session_id}")
+                                .wrap(RateLimitMiddlewareFactory::new(
+                                    auth_session_rate_limit_policy.clone(),
+                                ))
+                                .route(web::delete().to(handlers::session_handler::delete_session)),
+                        )
                         .service(
                             web::scope("/auth")
+                                .wrap(RateLimitMiddlewareFactory::new(
+                                    auth_rate_limit_policy.clone(),
+                                ))
                                 .service(
                                     web::resource("")
                                         .route(web::get().to(handlers::auth_handler::login))
                                         .route(web::delete().to(handlers::auth_handler::logout)),
                                 )
                                 .service(
-                                    web::resource("/whoami")
-                                        .route(web::get().to(handlers::auth_handler::whoami)),
+                                    web::resource("/identities").route(
+                                        web::get().to(handlers::auth_handler::get_identities),
+                                    ),
                                 )
+                                .service(web::resource("/identities/{identity_id}").route(
+                                    web::delete().to(handlers::auth_handler::delete_identity),
+                                ))
                                 .service(
-                                    web::resource("/callback")
+                                    web::resource("/link")
+                                        .route(
+                                            web::get().to(handlers::auth_handler::get_pending_link),
+                                        )
+                                        .route(
+                                            web::post().to(handlers::auth_handler::confirm_link),
+                                        ),
+                                )
+                                .service(web::resource("/providers").route(
+                                    web::get().to(handlers::auth_handler::get_oidc_providers),
+                                ))
+                                .service(
+                                    web::resource("/callback/{provider}")
                                         .route(web::get().to(handlers::auth_handler::callback)),
+                                )
+                                .service(
+                                    web::resource("/device/code").route(
+                                        web::post()
+                                            .to(handlers::device_auth_handler::create_device_code),
+                                    ),
+                                )
+                                .service(
+                                    web::resource("/device/verify")
+                                        .route(web::get().to(
+                                            handlers::device_auth_handler::get_device_authorization,
+                                        ))
+                                        .route(
+                                            web::post().to(
+                                                handlers::device_auth_handler::verify_device_code,
+                                            ),
+                                        ),
+                                )
+                                .service(
+                                    web::resource("/cli/session").route(
+                                        web::post()
+                                            .to(handlers::cli_session_handler::create_cli_session),
+                                    ),
+                                )
+                                .service(
+                                    web::resource("/cli/session/revoke").route(
+                                        web::post()
+                                            .to(handlers::cli_session_handler::revoke_cli_session),
+                                    ),
                                 ),
                         )
                         .service(
-                            web::scope("/api_key").service(
-                                web::resource("").route(
-                                    web::post().to(handlers::api_key_handler::create_api_key),
+                            web::scope("/api_key")
+                                .wrap(RateLimitMiddlewareFactory::new(
+                                    api_key_rate_limit_policy.clone(),
+                                ))
+                                .service(
+                                    web::resource("")
+                                        .route(
+                                            web::post()
+                                                .to(handlers::api_key_handler::create_api_key),
+                                        )
+                                        .route(
+                                            web::get().to(handlers::api_key_handler::get_api_keys),
+                                        ),
+                                )
+                                .service(
+                                    web::resource("/revoke-leaked").route(
+                                        web::post()
+                                            .to(handlers::api_key_handler::revoke_leaked_api_keys),
+                                    ),
+                                )
+                                .service(web::resource("/current").route(
+                                    web::get().to(handlers::api_key_handler::get_current_api_key),
+                                ))
+                                .service(
+                                    web::resource("/{api_key_id}")
+                                        .route(
+                                            web::get().to(handlers::api_key_handler::get_api_key),
+                                        )
+                                        .route(
+                                            web::delete()
+                                                .to(handlers::api_key_handler::delete_api_key),
+                                        ),
+                                )
+                                .service(web::resource("/{api_key_id}/rotate").route(
+                                    web::post().to(handlers::api_key_handler::rotate_api_key),
+                                )),
+                        )
+                        .service(
+                            web::scope("/organization")
+                                .wrap(RateLimitMiddlewareFactory::new(
+                                    organization_rate_limit_policy.clone(),
+                                ))
+                                .service(
+                                    web::resource("")
+                                        .route(web::post().to(
+                                            handlers::organization_handler::create_organization,
+                                        ))
+                                        .route(
+                                            web::get().to(
+                                                handlers::organization_handler::get_organizations,
+                                            ),
+                                        ),
+                                )
+                                .service(
+                                    web::resource("/current")
+                                        .route(
+                                            web::get().to(
+                                                handlers::organization_handler::get_organization,
+                                            ),
+                                        )
+                                        .route(web::put().to(
+                                            handlers::organization_handler::update_organization,
+                                        ))
+                                        .route(web::delete().to(
+                                            handlers::organization_handler::delete_organization,
+                                        )),
+                                )
+                                .service(web::resource("/current/users").route(
+                                    web::get().to(handlers::organization_handler::get_members),
+                                ))
+                                .service(
+                                    web::resource("/current/users/{user_id}")
+                                        .route(
+                                            web::put()
+                                                .to(handlers::organization_handler::update_member),
+                                        )
+                                        .route(
+                                            web::delete()
+                                                .to(handlers::organization_handler::remove_member),
+                                        ),
+                                )
+                                .service(
+                                    web::resource("/current/invitations")
+                                        .route(
+                                            web::get().to(
+                                                handlers::organization_handler::get_invitations,
+                                            ),
+                                        )
+                                        .route(
+                                            web::post()
+                                                .to(handlers::organization_handler::invite_member),
+                                        ),
+                                )
+                                .service(
+                                    web::resource("/current/invitations/{invitation_id}").route(
+                                        web::delete()
+                                            .to(handlers::organization_handler::delete_invitation),
+                                    ),
+                                )
+                                .service(
+                                    web::resource("/invitations").route(
+                                        web::get().to(
+                                            handlers::organization_handler::get_user_invitations,
+                                        ),
+                                    ),
+                                )
+                                .service(
+                                    web::resource("/invitations/{invitation_id}")
+                                        .route(web::delete().to(
+                                            handlers::organization_handler::decline_invitation,
+                                        )),
+                                )
+                                .service(
+                                    web::resource("/invitations/{invitation_id}/accept").route(
+                                        web::post()
+                                            .to(handlers::organization_handler::accept_invitation),
+                                    ),
                                 ),
-                            ),
                         )
                         .service(
                             web::resource("/health")
@@ -1,6 +1,17 @@
 use crate::{
-    data::models::{PgPool, User},
-    operators::user_operator::get_user_from_api_key,
+    data::models::{
+        ApiKey, ApiKeyScope, AuthMethod, AuthedScopes, CliSession, CookieIdentity,
+        OrganizationMembership, PgPool, RedisPool, User,
+    },
+    errors::ServiceError,
+    handlers::auth_handler::OidcProviders,
+    operators::{
+        api_key_operator::{parse_cidr, record_api_key_usage_query},
+        cli_session_operator::{get_user_from_cli_access_token_query, is_cli_access_token},
+        organization_operator::get_organization_membership_query,
+        session_operator::touch_oidc_session_query,
+        user_operator::{get_cached_user_query, get_user_by_identity_query, get_user_from_api_key},
+    },
 };
 use actix_identity::Identity;
 use actix_web::{
@@ -8,12 +19,18 @@ use actix_web::{
     web, Error, FromRequest, HttpMessage, HttpRequest,
 };
 use futures_util::future::LocalBoxFuture;
+use ipnet::IpNet;
 use sentry::Transaction;
 use std::{
     future::{ready, Ready},
+    net::IpAddr,
     rc::Rc,
 };
 
+/// Header selecting the organization a request acts on. The membership of the auth'ed user in it
+/// is inserted into the request extensions as an [`OrganizationMembership`].
+pub const ORGANIZATION_HEADER: &str = "X-Organization";
+
 pub struct AuthenticationMiddleware<S> {
     service: Rc<S>,
 }
@@ -41,9 +58,25 @@ where
             let get_user_span = transaction.start_child("get_user", "Getting user");
 
             let (http_req, pl) = req.parts_mut();
-            let user = get_user(http_req, pl, transaction.clone()).await;
-            if let Some(ref user) = user {
-                req.extensions_mut().insert(user.clone());
+            let user = get_user(http_req, pl, transaction.clone()).await?;
+            if let Some(authed) = user {
+                let organization_membership =
+                    get_organization_membership(http_req, authed.user.id).await?;
+                if let Some(organization_membership) = organization_membership {
+                    req.extensions_mut().insert(organization_membership);
+                }
+                req.extensions_mut().insert(authed.user);
+                req.extensions_mut().insert(authed.scopes);
+                req.extensions_mut().insert(authed.method);
+                if let Some(api_key) = authed.api_key {
+                    req.extensions_mut().insert(api_key);
+                }
+                if let Some(cli_session) = authed.cli_session {
+                    req.extensions_mut().insert(cli_session);
+                }
+                if let Some(cookie_identity) = authed.cookie_identity {
+                    req.extensions_mut().insert(cookie_identity);
+                }
             };
 
             get_user_span.finish();
@@ -57,20 +90,89 @@ where
     }
 }
 
-async fn get_user(req: &HttpRequest, pl: &mut Payload, tx: Transaction) -> Option<User> {
+struct Authed {
+    user: User,
+    scopes: AuthedScopes,
+    method: AuthMethod,
+    api_key: Option<ApiKey>,
+    cli_session: Option<CliSession>,
+    cookie_identity: Option<CookieIdentity>,
+}
+
+async fn get_user(
+    req: &HttpRequest,
+    pl: &mut Payload,
+    tx: Transaction,
+) -> Result<Option<Authed>, ServiceError> {
     let get_user_from_identity_span =
         tx.start_child("get_user_from_identity", "Getting user from identity");
 
     if let Ok(identity) = Identity::from_request(req, pl).into_inner() {
-        if let Ok(user_json) = identity.id() {
-            if let Ok(user) = serde_json::from_str::<User>(&user_json) {
-                return Some(user);
+        if let Ok(identity_json) = identity.id() {
+            // Identities which are not a `CookieIdentity` were created by older versions which
+            // stored the whole user, they have to log in again
+            if let Ok(cookie_identity) = serde_json::from_str::<CookieIdentity>(&identity_json) {
+                if browser_session_is_active(req, &cookie_identity.session_id).await? {
+                    let user = get_cached_user_query(
+                        cookie_identity.user_id,
+                        req.app_data::<web::Data<RedisPool>>()
+                            .expect("RedisPool will always be in server state")
+                            .to_owned(),
+                        req.app_data::<web::Data<PgPool>>()
+                            .expect("PgPool will always be in server state")
+                            .to_owned(),
+                    )
+                    .await?;
+                    if let Some(user) = user {
+                        get_user_from_identity_span.finish();
+                        return Ok(Some(Authed {
+                            user,
+                            scopes: AuthedScopes(ApiKeyScope::ALL.to_vec()),
+                            method: AuthMethod::Cookie,
+                            api_key: None,
+                            cli_session: None,
+                            cookie_identity: Some(cookie_identity),
+                        }));
+                    }
+                }
             }
+            identity.logout();
         }
     }
 
     if let Some(auth_header) = req.headers().get("Authorization") {
         if let Ok(auth_header) = auth_header.to_str() {
+            let token = auth_header
+                .strip_prefix("Bearer ")
+                .unwrap_or(auth_header)
+                .trim();
+            if is_cli_access_token(token) {
+                let user = get_user_from_cli_access_token_query(
+                    token,
+                    req.app_data::<web::Data<PgPool>>()
+                        .expect("PgPool will always be in server state")
+                        .to_owned(),
+                )
+                .await
+                .ok()
+                .map(|(user, cli_session)| Authed {
+                    user,
+                    scopes: AuthedScopes(cli_session.cli_session_scopes()),
+                    method: AuthMethod::CliSession,
+                    api_key: None,
+                    cli_session: Some(cli_session),
+                    cookie_identity: None,
+                });
+                get_user_from_identity_span.finish();
+                return Ok(user);
+            }
+
+            if let Some(access_token) = auth_header.strip_prefix("Bearer ") {
+                let user = get_user_from_access_token(req, access_token.trim()).await;
+                get_user_from_identity_span.finish();
+                return user;
+            }
+
             let user = get_user_from_api_key(
                 auth_header,
                 req.app_data::<web::Data<PgPool>>()
@@ -78,15 +180,182 @@ async fn get_user(req: &HttpRequest, pl: &mut Payload, tx: Transaction) -> Optio
                     .to_owned(),
             )
             .await
-            .ok();
+            .ok()
+            .map(|(user, api_key)| Authed {
+                user,
+                scopes: AuthedScopes(api_key.api_key_scopes()),
+                method: AuthMethod::ApiKey,
+                api_key: Some(api_key),
+                cli_session: None,
+                cookie_identity: None,
+            });
+            if let Some(Authed {
+                api_key: Some(api_key),
+                ..
+            }) = &user
+            {
+                let ip = client_ip(req);
+                if !api_key.allows_ip(ip) {
+                    get_user_from_identity_span.finish();
+                    return Err(ServiceError::IpNotAllowed);
+                }
+
+                let api_key_id = api_key.id;
+                let ip = ip.map(|ip| ip.to_string());
+                let redis_pool = req
+                    .app_data::<web::Data<RedisPool>>()
+                    .expect("RedisPool will always be in server state")
+                    .to_owned();
+                actix_web::rt::spawn(async move {
+                    if let Err(e) = record_api_key_usage_query(api_key_id, ip, redis_pool).await {
+                        log::error!("Failed to record api key usage: {:?}", e);
+                    }
+                });
+            }
             get_user_from_identity_span.finish();
-            return user;
+            return Ok(user);
         }
     }
 
     get_user_from_identity_span.finish();
 
-    None
+    Ok(None)
+}
+
+/// Resolves the user of an access token issued by one of the OpenID providers. The user must have
+/// logged in with the provider before. Access tokens act on behalf of the user like the cookie,
+/// so they are granted every scope.
+async fn get_user_from_access_token(
+    req: &HttpRequest,
+    access_token: &str,
+) -> Result<Option<Authed>, ServiceError> {
+    let oidc_providers = req
+        .app_data::<web::Data<OidcProviders>>()
+        .expect("OidcProviders will always be in server state");
+
+    let (provider, subject) = match oidc_providers.verify_access_token(access_token).await {
+        Ok(verified) => verified,
+        Err(ServiceError::AuthUnavailable) => return Err(ServiceError::AuthUnavailable),
+        Err(_) => return Ok(None),
+    };
+
+    let user = get_user_by_identity_query(
+        &provider.name,
+        subject.as_str(),
+        req.app_data::<web::Data<PgPool>>()
+            .expect("PgPool will always be in server state")
+            .to_owned(),
+    )
+    .await?;
+
+    Ok(user.map(|user| Authed {
+        user,
+        scopes: AuthedScopes(ApiKeyScope::ALL.to_vec()),
+        method: AuthMethod::BearerToken,
+        api_key: None,
+        cli_session: None,
+        cookie_identity: None,
+    }))
+}
+
+/// The membership of the user in the organization selected with the `X-Organization` header.
+/// `None` if the header is missing or invalid, or the user does not belong to the organization.
+async fn get_organization_membership(
+    req: &HttpRequest,
+    user_id: uuid::Uuid,
+) -> Result<Option<OrganizationMembership>, ServiceError> {
+    let Some(organization_id) = req
+        .headers()
+        .get(ORGANIZATION_HEADER)
+        .and_then(|organization_id| organization_id.to_str().ok())
+        .and_then(|organization_id| organization_id.trim().parse::<uuid::Uuid>().ok())
+    else {
+        return Ok(None);
+    };
+
+    get_organization_membership_query(
+        organization_id,
+        user_id,
+        req.app_data::<web::Data<PgPool>>()
+            .expect("PgPool will always be in server state")
+            .to_owned(),
+    )
+    .await
+}
+
+/// Whether the cookie session is still active, i.e. neither revoked by the user nor ended by the
+/// OpenID provider. Cookie sessions depend on Redis, which holds the session registry: while it
+/// can not be reached this fails with `AuthUnavailable` instead of logging the session out.
+async fn browser_session_is_active(
+    req: &HttpRequest,
+    session_id: &str,
+) -> Result<bool, ServiceError> {
+    let redis_pool = req
+        .app_data::<web::Data<RedisPool>>()
+        .expect("RedisPool will always be in server state")
+        .to_owned();
+    touch_oidc_session_query(session_id, redis_pool)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to check OIDC session: {:?}", e);
+            ServiceError::AuthUnavailable
+        })
+}
+
+/// The comma separated CIDRs in `TRUSTED_PROXIES`, parsed once. Panics if one of them is invalid.
+pub fn get_trusted_proxies() -> &'static [IpNet] {
+    static TRUSTED_PROXIES: std::sync::OnceLock<Vec<IpNet>> = std::sync::OnceLock::new();
+
+    TRUSTED_PROXIES.get_or_init(|| {
+        std::env::var("TRUSTED_PROXIES")
+            .unwrap_or_default()
+            .split(',')
+            .filter(|cidr| !cidr.trim().is_empty())
+            .map(|cidr| {
+                parse_cidr(cidr).unwrap_or_else(|_| {
+                    panic!(
+                        "TRUSTED_PROXIES contains the invalid CIDR '{}'",
+                        cidr.trim()
+                    )
+                })
+            })
+            .collect()
+    })
+}
+
+/// The address of the client which sent the request. `X-Forwarded-For` is only honored when the
+/// peer is one of the `TRUSTED_PROXIES`, in which case the right-most address which is not a
+/// trusted proxy is used. `None` if the peer is unknown or a trusted proxy forwarded a malformed
+/// `X-Forwarded-For`.
+pub fn client_ip(req: &HttpRequest) -> Option<IpAddr> {
+    client_ip_behind(req, get_trusted_proxies())
+}
+
+fn client_ip_behind(req: &HttpRequest, trusted_proxies: &[IpNet]) -> Option<IpAddr> {
+    let peer_ip = req.peer_addr()?.ip();
+    let is_trusted = |ip: &IpAddr| trusted_proxies.iter().any(|proxy| proxy.contains(ip));
+
+    if !is_trusted(&peer_ip) {
+        return Some(peer_ip);
+    }
+
+    let mut client_ip = peer_ip;
+    let forwarded_for = req
+        .headers()
+        .get_all("X-Forwarded-For")
+        .filter_map(|header| header.to_str().ok())
+        .flat_map(|header| header.split(','))
+        .map(|hop| hop.trim())
+        .collect::<Vec<&str>>();
+    for hop in forwarded_for.into_iter().rev() {
+        // A trusted proxy forwarding garbage can not be relied on for the client address
+        client_ip = hop.parse::<IpAddr>().ok()?;
+        if !is_trusted(&client_ip) {
+            break;
+        }
+    }
+
+    Some(client_ip)
 }
 
 pub struct AuthMiddlewareFactory;
@@ -109,3 +378,59 @@ where
         }))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    fn request(peer: &str, forwarded_for: Option<&str>) -> HttpRequest {
+        let req = TestRequest::default().peer_addr(format!("{}:443", peer).parse().unwrap());
+        match forwarded_for {
+            Some(forwarded_for) => req.insert_header(("X-Forwarded-For", forwarded_for)),
+            None => req,
+        }
+        .to_http_request()
+    }
+
+    fn ip(ip: &str) -> Option<IpAddr> {
+        Some(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn forwarded_for_is_only_honored_from_trusted_proxies() {
+        let proxies = [parse_cidr("10.0.0.0/8").unwrap()];
+
+        let untrusted = request("203.0.113.1", Some("198.51.100.1"));
+        assert_eq!(client_ip_behind(&untrusted, &proxies), ip("203.0.113.1"));
+
+        let direct = request("10.0.0.1", None);
+        assert_eq!(client_ip_behind(&direct, &proxies), ip("10.0.0.1"));
+
+        // Addresses left of the client were sent by the client itself
+        let proxied = request("10.0.0.1", Some("192.0.2.1, 198.51.100.1, 10.0.0.2"));
+        assert_eq!(client_ip_behind(&proxied, &proxies), ip("198.51.100.1"));
+    }
+
+    #[test]
+    fn chains_of_only_trusted_proxies_resolve_to_the_left_most_hop() {
+        let proxies = [parse_cidr("10.0.0.0/8").unwrap()];
+
+        let req = request("10.0.0.1", Some("10.0.0.3, 10.0.0.2"));
+        assert_eq!(client_ip_behind(&req, &proxies), ip("10.0.0.3"));
+    }
+
+    #[test]
+    fn malformed_forwarded_for_from_trusted_proxies_has_no_client_ip() {
+        let proxies = [parse_cidr("10.0.0.0/8").unwrap()];
+
+        for forwarded_for in ["not-an-ip", "198.51.100.1, garbage", "198.51.100.1,"] {
+            let req = request("10.0.0.1", Some(forwarded_for));
+            assert_eq!(client_ip_behind(&req, &proxies), None, "{}", forwarded_for);
+        }
+
+        // A malformed hop left of the client is not looked at
+        let req = request("10.0.0.1", Some("garbage, 198.51.100.1"));
+        assert_eq!(client_ip_behind(&req, &proxies), ip("198.51.100.1"));
+    }
+}
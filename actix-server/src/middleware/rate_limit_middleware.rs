@@ -0,0 +1,446 @@
+use crate::{
+    data::models::{ApiKey, RedisPool, User},
+    errors::ErrorRespPayload,
+    middleware::auth_middleware::client_ip,
+};
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{HeaderName, HeaderValue},
+    web, Error, HttpMessage, HttpResponse,
+};
+use bb8_redis::redis;
+use futures_util::future::LocalBoxFuture;
+use std::{
+    collections::HashMap,
+    future::{ready, Ready},
+    rc::Rc,
+    sync::{Mutex, OnceLock},
+    time::Duration,
+};
+
+/// GCRA: the key stores the theoretical arrival time (TAT) of the next request in milliseconds.
+/// Returns `{allowed, remaining, retry_after_ms, reset_ms}`.
+const GCRA_SCRIPT: &str = r#"
+local emission_interval = tonumber(ARGV[1])
+local limit = tonumber(ARGV[2])
+local now = tonumber(ARGV[3])
+local delay_tolerance = emission_interval * limit
+
+local tat = tonumber(redis.call('GET', KEYS[1]) or now)
+if tat < now then
+    tat = now
+end
+
+local new_tat = tat + emission_interval
+local allow_at = new_tat - delay_tolerance
+if now < allow_at then
+    return {0, 0, math.ceil(allow_at - now), math.ceil(tat - now)}
+end
+
+redis.call('SET', KEYS[1], new_tat, 'PX', math.ceil(new_tat - now))
+local remaining = math.floor((delay_tolerance - (new_tat - now)) / emission_interval)
+return {1, remaining, 0, math.ceil(new_tat - now)}
+"#;
+
+fn get_gcra_script() -> &'static redis::Script {
+    static GCRA: OnceLock<redis::Script> = OnceLock::new();
+    GCRA.get_or_init(|| redis::Script::new(GCRA_SCRIPT))
+}
+
+/// How long a request waits for redis before it is limited in memory instead, read once from
+/// `RATE_LIMIT_REDIS_TIMEOUT_MS`. Defaults to 100ms.
+fn get_redis_timeout() -> Duration {
+    static REDIS_TIMEOUT: OnceLock<Duration> = OnceLock::new();
+    *REDIS_TIMEOUT.get_or_init(|| match std::env::var("RATE_LIMIT_REDIS_TIMEOUT_MS") {
+        Ok(timeout) => Duration::from_millis(
+            timeout
+                .trim()
+                .parse()
+                .expect("RATE_LIMIT_REDIS_TIMEOUT_MS must be a number of milliseconds"),
+        ),
+        Err(_) => Duration::from_millis(100),
+    })
+}
+
+/// The most keys the in-memory fallback tracks at once, so that requests from many clients can
+/// not grow it without bound while redis is unavailable.
+const MAX_FALLBACK_ENTRIES: usize = 10_000;
+
+/// A limit of `limit` requests per `period` with bursts of up to `limit` requests, applied to a
+/// route scope or resource. Requests are counted per policy name, so resources wrapped with the
+/// same policy share a limit, and per api key, per user for cookie sessions and per client IP for
+/// anonymous requests.
+#[derive(Debug, Clone)]
+pub struct RateLimitPolicy {
+    pub name: String,
+    pub limit: u64,
+    pub period: Duration,
+}
+
+impl RateLimitPolicy {
+    /// Reads the policy from `RATE_LIMIT_<NAME>` formatted as `<limit>/<period in seconds>`,
+    /// e.g. `RATE_LIMIT_AUTH=30/60`.
+    pub fn from_env(name: &str, default_limit: u64, default_period_seconds: u64) -> Self {
+        let env_var = format!("RATE_LIMIT_{}", name.to_uppercase());
+        let (limit, period_seconds) = match std::env::var(&env_var) {
+            Ok(policy) => policy
+                .split_once('/')
+                .and_then(|(limit, period)| {
+                    Some((limit.trim().parse().ok()?, period.trim().parse().ok()?))
+                })
+                .filter(|(limit, period)| *limit > 0 && *period > 0)
+                .unwrap_or_else(|| panic!("{} must be formatted as <limit>/<seconds>", env_var)),
+            Err(_) => (default_limit, default_period_seconds),
+        };
+
+        RateLimitPolicy {
+            name: name.to_string(),
+            limit,
+            period: Duration::from_secs(period_seconds),
+        }
+    }
+
+    fn emission_interval_ms(&self) -> f64 {
+        self.period.as_millis() as f64 / self.limit as f64
+    }
+}
+
+struct RateLimitDecision {
+    allowed: bool,
+    remaining: u64,
+    retry_after_ms: u64,
+    reset_ms: u64,
+}
+
+fn get_fallback_store() -> &'static Mutex<HashMap<String, f64>> {
+    static FALLBACK_STORE: OnceLock<Mutex<HashMap<String, f64>>> = OnceLock::new();
+    FALLBACK_STORE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Same algorithm as `GCRA_SCRIPT`, but limited to this process. Used while redis is unavailable.
+fn check_rate_limit_in_memory(key: &str, policy: &RateLimitPolicy, now: f64) -> RateLimitDecision {
+    let mut store = get_fallback_store()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    check_rate_limit_in_store(&mut store, MAX_FALLBACK_ENTRIES, key, policy, now)
+}
+
+/// Keeps at most `max_entries` keys in the store. Keys whose limit fully recovered are dropped
+/// first, then the key which recovers soonest, as forgetting it lets the fewest requests through.
+fn check_rate_limit_in_store(
+    store: &mut HashMap<String, f64>,
+    max_entries: usize,
+    key: &str,
+    policy: &RateLimitPolicy,
+    now: f64,
+) -> RateLimitDecision {
+    let emission_interval = policy.emission_interval_ms();
+    let delay_tolerance = emission_interval * policy.limit as f64;
+
+    if store.len() >= max_entries && !store.contains_key(key) {
+        store.retain(|_, tat| *tat > now);
+        if store.len() >= max_entries {
+            let soonest = store
+                .iter()
+                .min_by(|(_, tat), (_, other_tat)| tat.total_cmp(other_tat))
+                .map(|(key, _)| key.clone());
+            if let Some(soonest) = soonest {
+                store.remove(&soonest);
+            }
+        }
+    }
+
+    let tat = store.get(key).copied().unwrap_or(now).max(now);
+    let new_tat = tat + emission_interval;
+    let allow_at = new_tat - delay_tolerance;
+    if now < allow_at {
+        return RateLimitDecision {
+            allowed: false,
+            remaining: 0,
+            retry_after_ms: (allow_at - now).ceil() as u64,
+            reset_ms: (tat - now).ceil() as u64,
+        };
+    }
+
+    store.insert(key.to_string(), new_tat);
+
+    RateLimitDecision {
+        allowed: true,
+        remaining: ((delay_tolerance - (new_tat - now)) / emission_interval).floor() as u64,
+        retry_after_ms: 0,
+        reset_ms: (new_tat - now).ceil() as u64,
+    }
+}
+
+async fn check_rate_limit_in_redis(
+    key: &str,
+    policy: &RateLimitPolicy,
+    now: f64,
+    redis_pool: &RedisPool,
+) -> Result<RateLimitDecision, String> {
+    let timeout = get_redis_timeout();
+
+    let (allowed, remaining, retry_after_ms, reset_ms): (u64, u64, u64, u64) =
+        actix_web::rt::time::timeout(timeout, async {
+            let mut redis_conn = redis_pool.get().await.map_err(|e| e.to_string())?;
+
+            // Sent with EVALSHA, the script is only loaded again when redis does not know it
+            get_gcra_script()
+                .key(key)
+                .arg(policy.emission_interval_ms())
+                .arg(policy.limit)
+                .arg(now)
+                .invoke_async(&mut *redis_conn)
+                .await
+                .map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|_| "Timed out".to_string())??;
+
+    Ok(RateLimitDecision {
+        allowed: allowed == 1,
+        remaining,
+        retry_after_ms,
+        reset_ms,
+    })
+}
+
+fn rate_limit_identity(req: &ServiceRequest) -> String {
+    if let Some(api_key) = req.extensions().get::<ApiKey>() {
+        return format!("api_key:{}", api_key.id);
+    }
+    if let Some(user) = req.extensions().get::<User>() {
+        return format!("user:{}", user.id);
+    }
+
+    match client_ip(req.request()) {
+        Some(ip) => format!("ip:{}", ip),
+        None => "ip:unknown".to_string(),
+    }
+}
+
+fn insert_rate_limit_headers<B>(
+    res: &mut ServiceResponse<B>,
+    policy: &RateLimitPolicy,
+    decision: &RateLimitDecision,
+) {
+    let headers = [
+        ("ratelimit-limit", policy.limit.to_string()),
+        ("ratelimit-remaining", decision.remaining.to_string()),
+        (
+            "ratelimit-reset",
+            decision.reset_ms.div_ceil(1000).to_string(),
+        ),
+        (
+            "ratelimit-policy",
+            format!("{};w={}", policy.limit, policy.period.as_secs()),
+        ),
+    ];
+    for (name, value) in headers {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            res.headers_mut()
+                .insert(HeaderName::from_static(name), value);
+        }
+    }
+}
+
+pub struct RateLimitMiddleware<S> {
+    service: Rc<S>,
+    policy: Rc<RateLimitPolicy>,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let srv = self.service.clone();
+        let policy = self.policy.clone();
+        Box::pin(async move {
+            let key = format!("rate_limit:{}:{}", policy.name, rate_limit_identity(&req));
+            let now = chrono::Utc::now().timestamp_millis() as f64;
+
+            let redis_pool = req
+                .app_data::<web::Data<RedisPool>>()
+                .expect("RedisPool will always be in server state")
+                .to_owned();
+            let decision = match check_rate_limit_in_redis(&key, &policy, now, &redis_pool).await {
+                Ok(decision) => decision,
+                Err(e) => {
+                    log::warn!("Rate limiting in memory, redis is unavailable: {}", e);
+                    check_rate_limit_in_memory(&key, &policy, now)
+                }
+            };
+
+            if !decision.allowed {
+                let response = HttpResponse::TooManyRequests()
+                    .insert_header((
+                        "Retry-After",
+                        decision.retry_after_ms.div_ceil(1000).to_string(),
+                    ))
+                    .json(ErrorRespPayload {
+                        message: "Too many requests".to_string(),
+                    });
+                let mut res = req.into_response(response).map_into_right_body();
+                insert_rate_limit_headers(&mut res, &policy, &decision);
+                return Ok(res);
+            }
+
+            let mut res = srv.call(req).await?.map_into_left_body();
+            insert_rate_limit_headers(&mut res, &policy, &decision);
+
+            Ok(res)
+        })
+    }
+}
+
+pub struct RateLimitMiddlewareFactory {
+    policy: Rc<RateLimitPolicy>,
+}
+
+impl RateLimitMiddlewareFactory {
+    pub fn new(policy: RateLimitPolicy) -> Self {
+        // Fail on startup rather than on the first request if the timeout is misconfigured
+        get_redis_timeout();
+
+        RateLimitMiddlewareFactory {
+            policy: Rc::new(policy),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimitMiddlewareFactory
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RateLimitMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitMiddleware {
+            service: Rc::new(service),
+            policy: self.policy.clone(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(limit: u64, period_seconds: u64) -> RateLimitPolicy {
+        RateLimitPolicy {
+            name: "test".to_string(),
+            limit,
+            period: Duration::from_secs(period_seconds),
+        }
+    }
+
+    #[test]
+    fn fallback_allows_bursts_up_to_the_limit() {
+        let policy = policy(3, 60);
+        let key = format!("rate_limit:test:{}", uuid::Uuid::new_v4());
+        let now = 1_000_000.0;
+
+        let remaining = (0..3)
+            .map(|_| {
+                let decision = check_rate_limit_in_memory(&key, &policy, now);
+                assert!(decision.allowed);
+                decision.remaining
+            })
+            .collect::<Vec<u64>>();
+        assert_eq!(remaining, vec![2, 1, 0]);
+
+        let decision = check_rate_limit_in_memory(&key, &policy, now);
+        assert!(!decision.allowed);
+        assert_eq!(decision.remaining, 0);
+        // One request is allowed again after the emission interval of 20 seconds
+        assert_eq!(decision.retry_after_ms, 20_000);
+        assert_eq!(decision.reset_ms, 60_000);
+    }
+
+    #[test]
+    fn fallback_allows_requests_again_after_the_emission_interval() {
+        let policy = policy(2, 60);
+        let key = format!("rate_limit:test:{}", uuid::Uuid::new_v4());
+        let now = 1_000_000.0;
+
+        assert!(check_rate_limit_in_memory(&key, &policy, now).allowed);
+        assert!(check_rate_limit_in_memory(&key, &policy, now).allowed);
+        assert!(!check_rate_limit_in_memory(&key, &policy, now + 29_999.0).allowed);
+
+        let decision = check_rate_limit_in_memory(&key, &policy, now + 30_000.0);
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 0);
+        assert!(!check_rate_limit_in_memory(&key, &policy, now + 30_000.0).allowed);
+
+        // A full period later the whole burst is available again
+        let decision = check_rate_limit_in_memory(&key, &policy, now + 90_000.0);
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 1);
+    }
+
+    #[test]
+    fn fallback_limits_every_key_on_its_own() {
+        let policy = policy(1, 60);
+        let key = format!("rate_limit:test:{}", uuid::Uuid::new_v4());
+        let other_key = format!("rate_limit:test:{}", uuid::Uuid::new_v4());
+        let now = 1_000_000.0;
+
+        assert!(check_rate_limit_in_memory(&key, &policy, now).allowed);
+        assert!(!check_rate_limit_in_memory(&key, &policy, now).allowed);
+        assert!(check_rate_limit_in_memory(&other_key, &policy, now).allowed);
+    }
+
+    #[test]
+    fn fallback_never_tracks_more_than_the_maximum_keys() {
+        let policy = policy(1, 60);
+        let mut store = HashMap::new();
+        let now = 1_000_000.0;
+
+        for i in 0..3 {
+            let decision =
+                check_rate_limit_in_store(&mut store, 3, &i.to_string(), &policy, now + i as f64);
+            assert!(decision.allowed);
+        }
+        assert_eq!(store.len(), 3);
+
+        // The key which recovers soonest makes room for the new one
+        assert!(check_rate_limit_in_store(&mut store, 3, "new", &policy, now + 3.0).allowed);
+        assert_eq!(store.len(), 3);
+        assert!(!store.contains_key("0"));
+        assert!(!check_rate_limit_in_store(&mut store, 3, "1", &policy, now + 4.0).allowed);
+        assert!(!check_rate_limit_in_store(&mut store, 3, "new", &policy, now + 4.0).allowed);
+
+        // Keys whose limit recovered are dropped before any other
+        let later = now + 60_002.0;
+        assert!(check_rate_limit_in_store(&mut store, 3, "late", &policy, later).allowed);
+        assert_eq!(store.len(), 2);
+    }
+
+    #[test]
+    fn policies_are_read_from_the_environment() {
+        std::env::set_var("RATE_LIMIT_TEST_POLICY", "10/30");
+        let policy = RateLimitPolicy::from_env("test_policy", 1, 1);
+        assert_eq!(policy.limit, 10);
+        assert_eq!(policy.period, Duration::from_secs(30));
+
+        let policy = RateLimitPolicy::from_env("unset_test_policy", 5, 60);
+        assert_eq!(policy.limit, 5);
+        assert_eq!(policy.period, Duration::from_secs(60));
+    }
+}
@@ -1,44 +1,288 @@
 use crate::{
-    data::models::{ApiKey, PgPool},
+    data::models::{ApiKey, ApiKeyRevocation, ApiKeyScope, LeakedApiKey, PgPool, RedisPool},
     errors::ServiceError,
 };
 use actix_web::web;
+use base64::Engine as _;
+use bb8_redis::redis;
+use diesel::prelude::*;
 use diesel_async::RunQueryDsl;
+use ipnet::IpNet;
+use oauth2::reqwest::async_http_client;
 use rand::{distributions::Alphanumeric, Rng};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
 
+const BASE62_ALPHABET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+const API_KEY_ID_LENGTH: usize = 12;
+const API_KEY_SECRET_LENGTH: usize = 32;
+const API_KEY_CHECKSUM_LENGTH: usize = 6;
+const LEGACY_API_KEY_PREFIX: &str = "tr-";
+
+/// The first segment of generated api keys, e.g. `tr` in `tr_live_...`. Set with `API_KEY_PREFIX`.
+pub fn get_api_key_prefix() -> String {
+    std::env::var("API_KEY_PREFIX").unwrap_or("tr".to_string())
+}
+
+/// The environment tag of generated api keys, e.g. `live` or `test`. Keys tagged with another environment are rejected. Set with `API_KEY_ENVIRONMENT`.
+pub fn get_api_key_environment() -> String {
+    std::env::var("API_KEY_ENVIRONMENT").unwrap_or("live".to_string())
+}
+
+pub fn random_base62(length: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(length)
+        .map(char::from)
+        .collect()
+}
+
+fn api_key_checksum(body: &str) -> String {
+    let mut crc = crc32fast::hash(body.as_bytes()) as usize;
+    let mut checksum = [b'0'; API_KEY_CHECKSUM_LENGTH];
+    for c in checksum.iter_mut().rev() {
+        *c = BASE62_ALPHABET[crc % 62];
+        crc /= 62;
+    }
+
+    String::from_utf8(checksum.to_vec()).expect("Base62 alphabet is ascii")
+}
+
+#[tracing::instrument]
+pub fn generate_api_key_id() -> String {
+    random_base62(API_KEY_ID_LENGTH)
+}
+
+/// Generates an api key of the form `<prefix>_<environment>_<key id>_<secret><crc32 checksum>`.
 #[tracing::instrument]
-pub fn generate_api_key() -> String {
-    let rng = rand::thread_rng();
-    let api_key: String = format!(
-        "tr-{}",
-        rng.sample_iter(&Alphanumeric)
-            .take(32)
-            .map(char::from)
-            .collect::<String>()
+pub fn generate_api_key(key_id: &str) -> String {
+    let body = format!(
+        "{}_{}_{}_{}",
+        get_api_key_prefix(),
+        get_api_key_environment(),
+        key_id,
+        random_base62(API_KEY_SECRET_LENGTH)
     );
+    let checksum = api_key_checksum(&body);
 
-    api_key
+    format!("{}{}", body, checksum)
 }
 
-#[tracing::instrument]
-pub fn hash_api_key(password: &str) -> String {
-    blake3::hash(password.as_bytes()).to_string()
+#[derive(Debug, PartialEq)]
+pub enum ParsedApiKey {
+    /// A `tr-` key generated before api keys carried a key id. These can only be looked up by hash.
+    Legacy,
+    Structured {
+        key_id: String,
+    },
+}
+
+/// Validates the format and checksum of an api key without touching the database.
+#[tracing::instrument(skip_all)]
+pub fn parse_api_key(api_key: &str) -> Result<ParsedApiKey, ServiceError> {
+    if api_key.starts_with(LEGACY_API_KEY_PREFIX) {
+        return Ok(ParsedApiKey::Legacy);
+    }
+
+    let malformed = || ServiceError::BadRequest("Malformed api key".to_string());
+
+    if !api_key.is_ascii() || api_key.len() <= API_KEY_CHECKSUM_LENGTH {
+        return Err(malformed());
+    }
+    let (body, checksum) = api_key.split_at(api_key.len() - API_KEY_CHECKSUM_LENGTH);
+    if api_key_checksum(body) != checksum {
+        return Err(malformed());
+    }
+
+    let mut segments = body.rsplitn(4, '_');
+    let secret = segments.next().ok_or_else(malformed)?;
+    let key_id = segments.next().ok_or_else(malformed)?;
+    let environment = segments.next().ok_or_else(malformed)?;
+    let prefix = segments.next().ok_or_else(malformed)?;
+
+    if prefix != get_api_key_prefix()
+        || environment != get_api_key_environment()
+        || key_id.len() != API_KEY_ID_LENGTH
+        || secret.len() != API_KEY_SECRET_LENGTH
+        || !key_id
+            .chars()
+            .chain(secret.chars())
+            .all(|c| c.is_ascii_alphanumeric())
+    {
+        return Err(malformed());
+    }
+
+    Ok(ParsedApiKey::Structured {
+        key_id: key_id.to_string(),
+    })
+}
+
+/// Parses a CIDR such as `203.0.113.0/24`. A bare IP address is treated as a network containing only that address.
+pub fn parse_cidr(cidr: &str) -> Result<IpNet, ServiceError> {
+    let cidr = cidr.trim();
+
+    cidr.parse::<IpNet>()
+        .map(|net| net.trunc())
+        .or_else(|_| cidr.parse::<std::net::IpAddr>().map(IpNet::from))
+        .map_err(|_| ServiceError::BadRequest(format!("Invalid CIDR '{}'", cidr)))
+}
+
+pub struct ApiKeyPepper {
+    pub version: i32,
+    key: [u8; 32],
+}
+
+pub struct ApiKeyPeppers {
+    pub peppers: Vec<ApiKeyPepper>,
+    pub current_version: i32,
+    /// Whether keys whose rows still have the unkeyed hash, version 0, are looked up by it.
+    pub allow_unpeppered: bool,
+}
+
+/// Peppers are configured with `API_KEY_PEPPERS` as a comma separated list of `<version>:<secret>`
+/// pairs and at least one is required. New hashes use `API_KEY_PEPPER_VERSION`, or the highest
+/// version if it is not set.
+///
+/// Version 0 is the unkeyed hash used before peppers were introduced. Keys stored with it only
+/// authenticate while `API_KEY_ALLOW_UNPEPPERED_HASHES=true`, and are rehashed with the current
+/// pepper when they are used. Unset it once every row was rehashed.
+pub fn get_api_key_peppers() -> &'static ApiKeyPeppers {
+    static API_KEY_PEPPERS: std::sync::OnceLock<ApiKeyPeppers> = std::sync::OnceLock::new();
+
+    API_KEY_PEPPERS.get_or_init(|| {
+        let peppers = std::env::var("API_KEY_PEPPERS")
+            .unwrap_or_default()
+            .split(',')
+            .filter(|pepper| !pepper.trim().is_empty())
+            .map(|pepper| {
+                let (version, secret) = pepper
+                    .trim()
+                    .split_once(':')
+                    .expect("API_KEY_PEPPERS entries must be <version>:<secret>");
+                let version = version
+                    .parse::<i32>()
+                    .ok()
+                    .filter(|version| *version > 0)
+                    .expect("API_KEY_PEPPERS versions must be positive integers");
+
+                ApiKeyPepper {
+                    version,
+                    key: blake3::derive_key("actix-server api key pepper", secret.as_bytes()),
+                }
+            })
+            .collect::<Vec<ApiKeyPepper>>();
+        if peppers.is_empty() {
+            panic!("API_KEY_PEPPERS must be set, e.g. to 1:<random secret of at least 32 bytes>");
+        }
+
+        let current_version = match std::env::var("API_KEY_PEPPER_VERSION") {
+            Ok(version) => version
+                .parse::<i32>()
+                .ok()
+                .filter(|version| peppers.iter().any(|pepper| pepper.version == *version))
+                .expect("API_KEY_PEPPER_VERSION must be one of the versions in API_KEY_PEPPERS"),
+            Err(_) => peppers
+                .iter()
+                .map(|pepper| pepper.version)
+                .max()
+                .unwrap_or_default(),
+        };
+
+        let allow_unpeppered = match std::env::var("API_KEY_ALLOW_UNPEPPERED_HASHES") {
+            Ok(allow_unpeppered) => allow_unpeppered
+                .parse::<bool>()
+                .expect("API_KEY_ALLOW_UNPEPPERED_HASHES must be true or false"),
+            Err(_) => false,
+        };
+        if allow_unpeppered {
+            log::info!(
+                "Api keys with unpeppered hashes are accepted, set API_KEY_ALLOW_UNPEPPERED_HASHES=false once every api key was rehashed"
+            );
+        }
+
+        ApiKeyPeppers {
+            peppers,
+            current_version,
+            allow_unpeppered,
+        }
+    })
+}
+
+fn hash_api_key_with_pepper(api_key: &str, pepper: Option<&ApiKeyPepper>) -> String {
+    match pepper {
+        Some(pepper) => blake3::keyed_hash(&pepper.key, api_key.as_bytes()).to_string(),
+        None => blake3::hash(api_key.as_bytes()).to_string(),
+    }
+}
+
+/// Hashes an api key with the current pepper. Returns the hash and the pepper version used.
+#[tracing::instrument(skip_all)]
+pub fn hash_api_key(api_key: &str) -> (String, i32) {
+    let api_key_peppers = get_api_key_peppers();
+    let pepper = api_key_peppers
+        .peppers
+        .iter()
+        .find(|pepper| pepper.version == api_key_peppers.current_version);
+
+    (
+        hash_api_key_with_pepper(api_key, pepper),
+        api_key_peppers.current_version,
+    )
+}
+
+/// Every peppered hash a secret may be stored under, one per configured pepper.
+#[tracing::instrument(skip_all)]
+pub fn get_api_key_hash_candidates(api_key: &str) -> Vec<String> {
+    get_api_key_peppers()
+        .peppers
+        .iter()
+        .map(|pepper| hash_api_key_with_pepper(api_key, Some(pepper)))
+        .collect()
+}
+
+/// The unkeyed hash of an api key if `API_KEY_ALLOW_UNPEPPERED_HASHES` is `true`, empty otherwise.
+/// It must only be matched against `api_keys` rows with `hash_version` 0, and previous hashes of
+/// keys rotated from such rows.
+#[tracing::instrument(skip_all)]
+pub fn get_unpeppered_api_key_hashes(api_key: &str) -> Vec<String> {
+    if get_api_key_peppers().allow_unpeppered {
+        vec![hash_api_key_with_pepper(api_key, None)]
+    } else {
+        vec![]
+    }
 }
 
 #[tracing::instrument(skip(pg_pool))]
 pub async fn create_api_key_query(
     user_id: uuid::Uuid,
     name: String,
+    expires_at: Option<chrono::NaiveDateTime>,
+    scopes: Vec<ApiKeyScope>,
+    allowed_cidrs: Vec<IpNet>,
     pg_pool: web::Data<PgPool>,
 ) -> Result<String, ServiceError> {
     use crate::data::schema::api_keys::dsl as api_keys_columns;
 
-    let raw_api_key = generate_api_key();
-    let blake3_hash = hash_api_key(&raw_api_key);
+    let key_id = generate_api_key_id();
+    let raw_api_key = generate_api_key(&key_id);
+    let (blake3_hash, hash_version) = hash_api_key(&raw_api_key);
 
     let mut conn = pg_pool.get().await.unwrap();
 
-    let api_key_struct = ApiKey::from_details(user_id, name, blake3_hash.clone());
+    let api_key_struct = ApiKey {
+        hash_version,
+        ..ApiKey::from_details(
+            user_id,
+            name,
+            key_id,
+            blake3_hash,
+            expires_at,
+            scopes,
+            allowed_cidrs,
+        )
+    };
 
     diesel::insert_into(api_keys_columns::api_keys)
         .values(&api_key_struct)
@@ -48,3 +292,697 @@ pub async fn create_api_key_query(
 
     Ok(raw_api_key)
 }
+
+#[tracing::instrument(skip(pg_pool))]
+pub async fn rotate_api_key_query(
+    api_key_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+    previous_hash_expires_at: chrono::NaiveDateTime,
+    pg_pool: web::Data<PgPool>,
+) -> Result<(String, ApiKey), ServiceError> {
+    use crate::data::schema::api_keys::dsl as api_keys_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    // Legacy keys get a key id on their first rotation
+    let key_id = api_keys_columns::api_keys
+        .filter(api_keys_columns::id.eq(api_key_id))
+        .filter(api_keys_columns::user_id.eq(user_id))
+        .filter(api_keys_columns::revoked_at.is_null())
+        .select(api_keys_columns::key_id)
+        .first::<Option<String>>(&mut conn)
+        .await
+        .optional()
+        .map_err(|_| ServiceError::BadRequest("Error loading api key".to_string()))?
+        .ok_or(ServiceError::NotFound)?
+        .unwrap_or_else(generate_api_key_id);
+
+    let raw_api_key = generate_api_key(&key_id);
+    let (blake3_hash, hash_version) = hash_api_key(&raw_api_key);
+
+    let api_key = diesel::update(
+        api_keys_columns::api_keys
+            .filter(api_keys_columns::id.eq(api_key_id))
+            .filter(api_keys_columns::user_id.eq(user_id))
+            .filter(api_keys_columns::revoked_at.is_null()),
+    )
+    .set((
+        api_keys_columns::previous_blake3_hash.eq(api_keys_columns::blake3_hash.nullable()),
+        api_keys_columns::previous_hash_expires_at.eq(previous_hash_expires_at),
+        api_keys_columns::blake3_hash.eq(blake3_hash),
+        api_keys_columns::hash_version.eq(hash_version),
+        api_keys_columns::key_id.eq(key_id),
+        api_keys_columns::updated_at.eq(chrono::Utc::now().naive_local()),
+    ))
+    .returning(ApiKey::as_returning())
+    .get_result::<ApiKey>(&mut conn)
+    .await
+    .optional()
+    .map_err(|_| ServiceError::BadRequest("Error rotating api key".to_string()))?
+    .ok_or(ServiceError::NotFound)?;
+
+    Ok((raw_api_key, api_key))
+}
+
+#[tracing::instrument(skip(pg_pool))]
+pub async fn get_api_keys_for_user_query(
+    user_id: uuid::Uuid,
+    page: i64,
+    page_size: i64,
+    pg_pool: web::Data<PgPool>,
+) -> Result<(Vec<ApiKey>, i64), ServiceError> {
+    use crate::data::schema::api_keys::dsl as api_keys_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    let api_keys = api_keys_columns::api_keys
+        .filter(api_keys_columns::user_id.eq(user_id))
+        .order(api_keys_columns::created_at.desc())
+        .offset((page - 1) * page_size)
+        .limit(page_size)
+        .select(ApiKey::as_select())
+        .load::<ApiKey>(&mut conn)
+        .await
+        .map_err(|_| ServiceError::BadRequest("Error loading api keys".to_string()))?;
+
+    let total_count = api_keys_columns::api_keys
+        .filter(api_keys_columns::user_id.eq(user_id))
+        .count()
+        .get_result::<i64>(&mut conn)
+        .await
+        .map_err(|_| ServiceError::BadRequest("Error counting api keys".to_string()))?;
+
+    Ok((api_keys, total_count))
+}
+
+#[tracing::instrument(skip(pg_pool))]
+pub async fn get_api_key_by_id_query(
+    api_key_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+    pg_pool: web::Data<PgPool>,
+) -> Result<ApiKey, ServiceError> {
+    use crate::data::schema::api_keys::dsl as api_keys_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    let api_key = api_keys_columns::api_keys
+        .filter(api_keys_columns::id.eq(api_key_id))
+        .filter(api_keys_columns::user_id.eq(user_id))
+        .select(ApiKey::as_select())
+        .first::<ApiKey>(&mut conn)
+        .await
+        .optional()
+        .map_err(|_| ServiceError::BadRequest("Error loading api key".to_string()))?
+        .ok_or(ServiceError::NotFound)?;
+
+    Ok(api_key)
+}
+
+#[tracing::instrument(skip(pg_pool))]
+pub async fn delete_api_key_query(
+    api_key_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+    pg_pool: web::Data<PgPool>,
+) -> Result<(), ServiceError> {
+    use crate::data::schema::api_keys::dsl as api_keys_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    let deleted_count = diesel::delete(
+        api_keys_columns::api_keys
+            .filter(api_keys_columns::id.eq(api_key_id))
+            .filter(api_keys_columns::user_id.eq(user_id)),
+    )
+    .execute(&mut conn)
+    .await
+    .map_err(|_| ServiceError::BadRequest("Error deleting api key".to_string()))?;
+
+    if deleted_count == 0 {
+        return Err(ServiceError::NotFound);
+    }
+
+    Ok(())
+}
+
+const API_KEY_USAGE_PENDING_KEY: &str = "api_key_usage:pending";
+
+fn api_key_usage_key(api_key_id: uuid::Uuid) -> String {
+    format!("api_key_usage:{}", api_key_id)
+}
+
+/// Records a request authenticated with the api key in redis. The counters are written to postgres in batches by `flush_api_key_usage_query`.
+#[tracing::instrument(skip(redis_pool))]
+pub async fn record_api_key_usage_query(
+    api_key_id: uuid::Uuid,
+    ip: Option<String>,
+    redis_pool: web::Data<RedisPool>,
+) -> Result<(), ServiceError> {
+    let mut redis_conn = redis_pool
+        .get()
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Could not get redis connection".into()))?;
+
+    let usage_key = api_key_usage_key(api_key_id);
+
+    let mut pipe = redis::pipe();
+    pipe.atomic()
+        .hincr(&usage_key, "count", 1)
+        .ignore()
+        .hset(&usage_key, "last_used_at", chrono::Utc::now().timestamp())
+        .ignore();
+    if let Some(ip) = ip {
+        pipe.hset(&usage_key, "last_used_ip", ip).ignore();
+    }
+    pipe.sadd(API_KEY_USAGE_PENDING_KEY, api_key_id.to_string())
+        .ignore();
+
+    let _: () = pipe
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Failed to record api key usage".into()))?;
+
+    Ok(())
+}
+
+/// Moves the usage recorded in redis since the last flush into postgres. Returns the number of api keys which were updated.
+///
+/// A key whose usage can not be saved does not abort the flush: its usage is put back and its id is returned to the pending set, so the next flush retries it.
+#[tracing::instrument(skip(redis_pool, pg_pool))]
+pub async fn flush_api_key_usage_query(
+    redis_pool: web::Data<RedisPool>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<usize, ServiceError> {
+    use crate::data::schema::api_keys::dsl as api_keys_columns;
+
+    // Connect to postgres first, so that no usage is taken out of redis without a place to put it
+    let mut conn = pg_pool.get().await.map_err(|_| {
+        ServiceError::InternalServerError("Could not get postgres connection".into())
+    })?;
+
+    let mut redis_conn = redis_pool
+        .get()
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Could not get redis connection".into()))?;
+
+    let api_key_ids: Vec<String> = redis::cmd("SPOP")
+        .arg(API_KEY_USAGE_PENDING_KEY)
+        .arg(1000)
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Failed to read api key usage".into()))?;
+
+    let mut flushed = 0;
+    let mut failed_api_key_ids = vec![];
+    for api_key_id in api_key_ids {
+        let Ok(parsed_api_key_id) = api_key_id.parse::<uuid::Uuid>() else {
+            continue;
+        };
+        let usage_key = api_key_usage_key(parsed_api_key_id);
+
+        let usage: Result<(std::collections::HashMap<String, String>,), _> = redis::pipe()
+            .atomic()
+            .hgetall(&usage_key)
+            .del(&usage_key)
+            .ignore()
+            .query_async(&mut *redis_conn)
+            .await;
+        let (usage,) = match usage {
+            Ok(usage) => usage,
+            Err(e) => {
+                log::error!("Failed to read usage of api key {}: {:?}", api_key_id, e);
+                failed_api_key_ids.push(api_key_id);
+                continue;
+            }
+        };
+
+        let count = usage
+            .get("count")
+            .and_then(|count| count.parse::<i64>().ok())
+            .unwrap_or(0);
+        if count == 0 {
+            continue;
+        }
+        let last_used_at = usage
+            .get("last_used_at")
+            .and_then(|last_used_at| last_used_at.parse::<i64>().ok())
+            .and_then(|last_used_at| chrono::DateTime::from_timestamp(last_used_at, 0))
+            .map(|last_used_at| last_used_at.naive_utc())
+            .unwrap_or_else(|| chrono::Utc::now().naive_utc());
+        let last_used_ip = usage.get("last_used_ip").cloned();
+
+        let saved = diesel::update(
+            api_keys_columns::api_keys.filter(api_keys_columns::id.eq(parsed_api_key_id)),
+        )
+        .set((
+            api_keys_columns::usage_count.eq(api_keys_columns::usage_count + count),
+            api_keys_columns::last_used_at.eq(last_used_at),
+            last_used_ip.map(|last_used_ip| api_keys_columns::last_used_ip.eq(last_used_ip)),
+        ))
+        .execute(&mut conn)
+        .await;
+        if let Err(e) = saved {
+            log::error!("Failed to save usage of api key {}: {:?}", api_key_id, e);
+
+            // Requests recorded since the usage was read are kept, only missing fields are restored
+            let mut pipe = redis::pipe();
+            pipe.atomic().hincr(&usage_key, "count", count).ignore();
+            for (field, value) in usage.iter().filter(|(field, _)| *field != "count") {
+                pipe.hset_nx(&usage_key, field, value).ignore();
+            }
+            let restored: Result<(), _> = pipe.query_async(&mut *redis_conn).await;
+            if let Err(e) = restored {
+                log::error!(
+                    "Lost {} uses of api key {} while putting them back: {:?}",
+                    count,
+                    api_key_id,
+                    e
+                );
+            }
+
+            failed_api_key_ids.push(api_key_id);
+            continue;
+        }
+
+        flushed += 1;
+    }
+
+    if !failed_api_key_ids.is_empty() {
+        let _: () = redis::cmd("SADD")
+            .arg(API_KEY_USAGE_PENDING_KEY)
+            .arg(&failed_api_key_ids)
+            .query_async(&mut *redis_conn)
+            .await
+            .map_err(|_| {
+                ServiceError::InternalServerError("Failed to requeue api key usage".into())
+            })?;
+    }
+
+    Ok(flushed)
+}
+
+/// How often the public keys of secret scanning partners are fetched at most, to pick up a new key
+/// when a report is signed with an unknown one.
+const LEAK_REPORTER_KEYS_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Where the public keys reports of leaked api keys are signed with are published, in the format
+/// of GitHub secret scanning. Set with `LEAKED_API_KEY_REPORTER_KEYS_URL`.
+fn get_leak_reporter_keys_url() -> String {
+    std::env::var("LEAKED_API_KEY_REPORTER_KEYS_URL")
+        .unwrap_or("https://api.github.com/meta/public_keys/secret_scanning".to_string())
+}
+
+#[derive(Deserialize)]
+struct LeakReporterKey {
+    key_identifier: String,
+    key: String,
+}
+
+#[derive(Deserialize)]
+struct LeakReporterKeys {
+    public_keys: Vec<LeakReporterKey>,
+}
+
+#[derive(Default)]
+struct LeakReporterKeyCache {
+    /// PEM encoded public keys by their identifier.
+    keys: HashMap<String, String>,
+    fetched_at: Option<Instant>,
+}
+
+async fn fetch_leak_reporter_keys() -> Result<HashMap<String, String>, ServiceError> {
+    let unavailable = || {
+        ServiceError::InternalServerError(
+            "Could not load the public keys of secret scanning partners".into(),
+        )
+    };
+
+    let url = oauth2::url::Url::parse(&get_leak_reporter_keys_url()).map_err(|_| unavailable())?;
+    let mut headers = oauth2::http::HeaderMap::new();
+    headers.insert(
+        oauth2::http::header::USER_AGENT,
+        oauth2::http::HeaderValue::from_static("actix-server"),
+    );
+    headers.insert(
+        oauth2::http::header::ACCEPT,
+        oauth2::http::HeaderValue::from_static("application/json"),
+    );
+
+    let response = async_http_client(oauth2::HttpRequest {
+        url,
+        method: oauth2::http::Method::GET,
+        headers,
+        body: vec![],
+    })
+    .await
+    .map_err(|e| {
+        log::error!(
+            "Failed to fetch the keys of secret scanning partners: {:?}",
+            e
+        );
+        unavailable()
+    })?;
+    if !response.status_code.is_success() {
+        log::error!(
+            "Failed to fetch the keys of secret scanning partners: {}",
+            response.status_code
+        );
+        return Err(unavailable());
+    }
+
+    let leak_reporter_keys =
+        serde_json::from_slice::<LeakReporterKeys>(&response.body).map_err(|_| unavailable())?;
+
+    Ok(leak_reporter_keys
+        .public_keys
+        .into_iter()
+        .map(|key| (key.key_identifier, key.key))
+        .collect())
+}
+
+/// The PEM encoded public key with the identifier. The keys are fetched again when the identifier
+/// is unknown, at most once per `LEAK_REPORTER_KEYS_REFRESH_INTERVAL`.
+async fn get_leak_reporter_key(key_identifier: &str) -> Result<Option<String>, ServiceError> {
+    static LEAK_REPORTER_KEYS: OnceLock<RwLock<LeakReporterKeyCache>> = OnceLock::new();
+    let cache = LEAK_REPORTER_KEYS.get_or_init(Default::default);
+
+    {
+        let mut cache = cache
+            .write()
+            .expect("Leak reporter key cache lock is poisoned");
+        if let Some(key) = cache.keys.get(key_identifier) {
+            return Ok(Some(key.clone()));
+        }
+        if cache
+            .fetched_at
+            .is_some_and(|fetched_at| fetched_at.elapsed() < LEAK_REPORTER_KEYS_REFRESH_INTERVAL)
+        {
+            return Ok(None);
+        }
+        cache.fetched_at = Some(Instant::now());
+    }
+
+    let keys = fetch_leak_reporter_keys().await?;
+    let key = keys.get(key_identifier).cloned();
+    cache
+        .write()
+        .expect("Leak reporter key cache lock is poisoned")
+        .keys = keys;
+
+    Ok(key)
+}
+
+/// Verifies that a report of leaked api keys was signed by a secret scanning partner. The signature
+/// is a base64 encoded ECDSA signature over the SHA-256 of the raw request body.
+#[tracing::instrument(skip(body, signature))]
+pub async fn verify_leaked_api_key_report_query(
+    body: &[u8],
+    key_identifier: &str,
+    signature: &str,
+) -> Result<(), ServiceError> {
+    let Some(public_key) = get_leak_reporter_key(key_identifier).await? else {
+        return Err(ServiceError::Unauthorized);
+    };
+    let signature = base64::engine::general_purpose::STANDARD
+        .decode(signature.trim())
+        .map_err(|_| ServiceError::Unauthorized)?;
+
+    let verified = openssl::pkey::PKey::public_key_from_pem(public_key.as_bytes())
+        .and_then(|public_key| {
+            openssl::sign::Verifier::new(openssl::hash::MessageDigest::sha256(), &public_key)?
+                .verify_oneshot(&signature, body)
+        })
+        .unwrap_or(false);
+    if !verified {
+        return Err(ServiceError::Unauthorized);
+    }
+
+    Ok(())
+}
+
+/// Revokes every api key matching one of the reported raw keys and records an audit entry for each. Returns the number of keys which were revoked.
+#[tracing::instrument(skip_all)]
+pub async fn revoke_leaked_api_keys_query(
+    leaked_api_keys: Vec<LeakedApiKey>,
+    reporter_ip: Option<String>,
+    reporter_user_agent: Option<String>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<usize, ServiceError> {
+    use crate::data::schema::api_key_revocations::dsl as api_key_revocations_columns;
+    use crate::data::schema::api_keys::dsl as api_keys_columns;
+
+    let leaked_api_keys = leaked_api_keys
+        .into_iter()
+        .filter(|leaked_api_key| parse_api_key(&leaked_api_key.token).is_ok())
+        .collect::<Vec<LeakedApiKey>>();
+    let hashes = leaked_api_keys
+        .iter()
+        .flat_map(|leaked_api_key| get_api_key_hash_candidates(&leaked_api_key.token))
+        .collect::<Vec<String>>();
+    let unpeppered_hashes = leaked_api_keys
+        .iter()
+        .flat_map(|leaked_api_key| get_unpeppered_api_key_hashes(&leaked_api_key.token))
+        .collect::<Vec<String>>();
+    let leaked_api_keys = leaked_api_keys
+        .into_iter()
+        .flat_map(|leaked_api_key| {
+            get_api_key_hash_candidates(&leaked_api_key.token)
+                .into_iter()
+                .chain(get_unpeppered_api_key_hashes(&leaked_api_key.token))
+                .map(move |hash| (hash, leaked_api_key.clone()))
+        })
+        .collect::<std::collections::HashMap<String, LeakedApiKey>>();
+
+    if leaked_api_keys.is_empty() {
+        return Ok(0);
+    }
+
+    let now = chrono::Utc::now().naive_local();
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    // A previous secret only matters while it still authenticates during the rotation grace period
+    let revoked_api_keys = diesel::update(
+        api_keys_columns::api_keys
+            .filter(api_keys_columns::revoked_at.is_null())
+            .filter(
+                api_keys_columns::blake3_hash
+                    .eq_any(hashes.clone())
+                    .or(api_keys_columns::blake3_hash
+                        .eq_any(unpeppered_hashes.clone())
+                        .and(api_keys_columns::hash_version.eq(0)))
+                    .or(api_keys_columns::previous_blake3_hash
+                        .eq_any(hashes.into_iter().chain(unpeppered_hashes))
+                        .and(
+                            api_keys_columns::previous_hash_expires_at
+                                .gt(chrono::Utc::now().naive_utc()),
+                        )),
+            ),
+    )
+    .set((
+        api_keys_columns::revoked_at.eq(now),
+        api_keys_columns::updated_at.eq(now),
+    ))
+    .returning(ApiKey::as_returning())
+    .get_results::<ApiKey>(&mut conn)
+    .await
+    .map_err(|_| ServiceError::InternalServerError("Error revoking leaked api keys".into()))?;
+
+    let revocations = revoked_api_keys
+        .iter()
+        .filter_map(|api_key| {
+            leaked_api_keys
+                .get(&api_key.blake3_hash)
+                .or_else(|| {
+                    api_key
+                        .previous_blake3_hash
+                        .as_ref()
+                        .and_then(|hash| leaked_api_keys.get(hash))
+                })
+                .map(|leaked_api_key| {
+                    ApiKeyRevocation::from_details(
+                        api_key,
+                        leaked_api_key,
+                        reporter_ip.clone(),
+                        reporter_user_agent.clone(),
+                    )
+                })
+        })
+        .collect::<Vec<ApiKeyRevocation>>();
+
+    diesel::insert_into(api_key_revocations_columns::api_key_revocations)
+        .values(&revocations)
+        .execute(&mut conn)
+        .await
+        .map_err(|_| {
+            ServiceError::InternalServerError("Error recording api key revocations".into())
+        })?;
+
+    for revocation in revocations.iter() {
+        log::warn!(
+            "Revoked leaked api key {:?} of user {} reported from {:?}",
+            revocation.api_key_id,
+            revocation.user_id,
+            revocation.reported_url
+        );
+    }
+
+    Ok(revocations.len())
+}
+
+/// Replaces the stored hash of an api key which was hashed with an old pepper. The update is
+/// skipped if the key was rotated in the meantime.
+#[tracing::instrument(skip(blake3_hash, pg_pool))]
+pub async fn rehash_api_key_query(
+    api_key_id: uuid::Uuid,
+    previous_blake3_hash: String,
+    blake3_hash: String,
+    hash_version: i32,
+    pg_pool: web::Data<PgPool>,
+) -> Result<(), ServiceError> {
+    use crate::data::schema::api_keys::dsl as api_keys_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    diesel::update(
+        api_keys_columns::api_keys
+            .filter(api_keys_columns::id.eq(api_key_id))
+            .filter(api_keys_columns::blake3_hash.eq(previous_blake3_hash)),
+    )
+    .set((
+        api_keys_columns::blake3_hash.eq(blake3_hash),
+        api_keys_columns::hash_version.eq(hash_version),
+    ))
+    .execute(&mut conn)
+    .await
+    .map_err(|_| ServiceError::BadRequest("Error rehashing api key".to_string()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_api_keys_parse_to_their_key_id() {
+        let key_id = generate_api_key_id();
+        let api_key = generate_api_key(&key_id);
+
+        assert!(api_key.starts_with("tr_live_"));
+        assert_eq!(
+            parse_api_key(&api_key).unwrap(),
+            ParsedApiKey::Structured { key_id }
+        );
+    }
+
+    #[test]
+    fn api_keys_with_a_wrong_checksum_are_rejected() {
+        let api_key = generate_api_key(&generate_api_key_id());
+        let (body, checksum) = api_key.split_at(api_key.len() - API_KEY_CHECKSUM_LENGTH);
+
+        // Flip one character of the secret
+        let mut typo = body.to_string();
+        let last = typo.pop().unwrap();
+        typo.push(if last == 'a' { 'b' } else { 'a' });
+        assert!(parse_api_key(&format!("{}{}", typo, checksum)).is_err());
+
+        assert!(parse_api_key(body).is_err());
+        assert!(parse_api_key(&api_key[..api_key.len() - 1]).is_err());
+        assert!(parse_api_key("").is_err());
+        assert!(parse_api_key("tr_live_é").is_err());
+    }
+
+    #[test]
+    fn api_keys_of_another_environment_are_rejected() {
+        let body = format!(
+            "tr_test_{}_{}",
+            generate_api_key_id(),
+            random_base62(API_KEY_SECRET_LENGTH)
+        );
+        let api_key = format!("{}{}", body, api_key_checksum(&body));
+
+        assert!(parse_api_key(&api_key).is_err());
+    }
+
+    #[test]
+    fn api_keys_with_a_valid_checksum_must_still_be_well_formed() {
+        for body in [
+            format!("tr_live_short_{}", random_base62(API_KEY_SECRET_LENGTH)),
+            format!("tr_live_{}_short", generate_api_key_id()),
+            format!("tr_live_{}", random_base62(API_KEY_SECRET_LENGTH)),
+            format!(
+                "tr_live_{}_{}-",
+                generate_api_key_id(),
+                random_base62(API_KEY_SECRET_LENGTH - 1)
+            ),
+        ] {
+            let api_key = format!("{}{}", body, api_key_checksum(&body));
+            assert!(parse_api_key(&api_key).is_err(), "{}", api_key);
+        }
+    }
+
+    #[test]
+    fn legacy_api_keys_are_looked_up_by_hash() {
+        assert_eq!(
+            parse_api_key("tr-0123456789abcdef").unwrap(),
+            ParsedApiKey::Legacy
+        );
+    }
+
+    #[test]
+    fn cidrs_are_normalized_and_bare_addresses_accepted() {
+        assert_eq!(
+            parse_cidr(" 203.0.113.7/24 ").unwrap().to_string(),
+            "203.0.113.0/24"
+        );
+        assert_eq!(
+            parse_cidr("203.0.113.7").unwrap().to_string(),
+            "203.0.113.7/32"
+        );
+        assert_eq!(
+            parse_cidr("2001:db8::1").unwrap().to_string(),
+            "2001:db8::1/128"
+        );
+
+        for cidr in ["", "203.0.113.0/33", "203.0.113", "example.com"] {
+            assert!(parse_cidr(cidr).is_err(), "{}", cidr);
+        }
+    }
+
+    fn api_key_allowed_from(allowed_cidrs: &[&str]) -> ApiKey {
+        ApiKey::from_details(
+            uuid::Uuid::new_v4(),
+            "key".to_string(),
+            generate_api_key_id(),
+            "hash".to_string(),
+            None,
+            ApiKeyScope::ALL.to_vec(),
+            allowed_cidrs
+                .iter()
+                .map(|cidr| parse_cidr(cidr).unwrap())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn api_keys_are_only_allowed_from_their_cidrs() {
+        let api_key = api_key_allowed_from(&["203.0.113.0/24", "2001:db8::/32"]);
+
+        for ip in ["203.0.113.1", "203.0.113.255", "2001:db8::1"] {
+            assert!(api_key.allows_ip(Some(ip.parse().unwrap())), "{}", ip);
+        }
+        for ip in ["203.0.114.1", "10.0.0.1", "2001:db9::1"] {
+            assert!(!api_key.allows_ip(Some(ip.parse().unwrap())), "{}", ip);
+        }
+        // Requests whose address is unknown can not be matched against the allowlist
+        assert!(!api_key.allows_ip(None));
+    }
+
+    #[test]
+    fn api_keys_without_cidrs_are_allowed_from_anywhere() {
+        let api_key = api_key_allowed_from(&[]);
+
+        assert!(api_key.allows_ip(Some("203.0.113.1".parse().unwrap())));
+        assert!(api_key.allows_ip(None));
+    }
+}
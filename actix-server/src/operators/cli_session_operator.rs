@@ -0,0 +1,458 @@
+use crate::{
+    data::models::{ApiKeyScope, CliSession, CliSessionRefreshToken, PgPool, User},
+    errors::ServiceError,
+    operators::api_key_operator::{
+        get_api_key_hash_candidates, get_api_key_prefix, hash_api_key, random_base62,
+    },
+};
+use actix_web::web;
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+
+const CLI_ACCESS_TOKEN_KIND: &str = "cliat";
+const CLI_REFRESH_TOKEN_KIND: &str = "clirt";
+const CLI_TOKEN_SECRET_LENGTH: usize = 48;
+
+/// How long an access token of a CLI session authenticates. Set with `CLI_ACCESS_TOKEN_TTL_SECONDS`.
+pub fn get_cli_access_token_ttl() -> chrono::Duration {
+    chrono::Duration::seconds(
+        std::env::var("CLI_ACCESS_TOKEN_TTL_SECONDS")
+            .ok()
+            .and_then(|ttl| ttl.parse().ok())
+            .unwrap_or(3600),
+    )
+}
+
+/// How long a CLI session can go without being refreshed before it expires. Set with `CLI_SESSION_TTL_DAYS`.
+pub fn get_cli_session_ttl() -> chrono::Duration {
+    chrono::Duration::days(
+        std::env::var("CLI_SESSION_TTL_DAYS")
+            .ok()
+            .and_then(|ttl| ttl.parse().ok())
+            .unwrap_or(30),
+    )
+}
+
+/// How long a CLI session can be kept alive by refreshing it, counted from the login. Set with
+/// `CLI_SESSION_MAX_LIFETIME_DAYS`.
+pub fn get_cli_session_max_lifetime() -> chrono::Duration {
+    chrono::Duration::days(
+        std::env::var("CLI_SESSION_MAX_LIFETIME_DAYS")
+            .ok()
+            .and_then(|lifetime| lifetime.parse().ok())
+            .unwrap_or(90),
+    )
+}
+
+/// How long an already exchanged refresh token can be presented again, e.g. by a CLI retrying a
+/// refresh whose response got lost, without the session being revoked. Set with
+/// `CLI_REFRESH_TOKEN_REUSE_GRACE_SECONDS`.
+pub fn get_cli_refresh_token_reuse_grace_period() -> chrono::Duration {
+    chrono::Duration::seconds(
+        std::env::var("CLI_REFRESH_TOKEN_REUSE_GRACE_SECONDS")
+            .ok()
+            .and_then(|grace_period| grace_period.parse().ok())
+            .unwrap_or(30),
+    )
+}
+
+fn cli_token_prefix(kind: &str) -> String {
+    format!("{}_{}_", get_api_key_prefix(), kind)
+}
+
+/// Whether the credential is the access token of a CLI session rather than an api key.
+pub fn is_cli_access_token(token: &str) -> bool {
+    token.starts_with(&cli_token_prefix(CLI_ACCESS_TOKEN_KIND))
+}
+
+fn is_cli_refresh_token(token: &str) -> bool {
+    token.starts_with(&cli_token_prefix(CLI_REFRESH_TOKEN_KIND))
+}
+
+/// The raw tokens of a CLI session. Only their hashes are stored.
+pub struct CliSessionTokens {
+    pub access_token: String,
+    pub refresh_token: String,
+}
+
+impl CliSessionTokens {
+    fn generate() -> Self {
+        CliSessionTokens {
+            access_token: format!(
+                "{}{}",
+                cli_token_prefix(CLI_ACCESS_TOKEN_KIND),
+                random_base62(CLI_TOKEN_SECRET_LENGTH)
+            ),
+            refresh_token: format!(
+                "{}{}",
+                cli_token_prefix(CLI_REFRESH_TOKEN_KIND),
+                random_base62(CLI_TOKEN_SECRET_LENGTH)
+            ),
+        }
+    }
+}
+
+/// The expiry of the session and of its access token for a session created or refreshed now. The
+/// session never outlives its maximum lifetime, counted from `created_at`.
+fn cli_session_expiry(
+    created_at: chrono::NaiveDateTime,
+) -> (chrono::NaiveDateTime, chrono::NaiveDateTime) {
+    let now = chrono::Utc::now().naive_utc();
+    let expires_at = (now + get_cli_session_ttl()).min(created_at + get_cli_session_max_lifetime());
+
+    (
+        (now + get_cli_access_token_ttl()).min(expires_at),
+        expires_at,
+    )
+}
+
+#[tracing::instrument(skip(pg_pool))]
+pub async fn create_cli_session_query(
+    user_id: uuid::Uuid,
+    hostname: String,
+    cli_version: String,
+    scopes: Vec<ApiKeyScope>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<(CliSessionTokens, CliSession), ServiceError> {
+    use crate::data::schema::cli_sessions::dsl as cli_sessions_columns;
+
+    let tokens = CliSessionTokens::generate();
+    let (access_token_expires_at, expires_at) = cli_session_expiry(chrono::Utc::now().naive_utc());
+    let cli_session = CliSession::from_details(
+        user_id,
+        hostname,
+        cli_version,
+        scopes,
+        hash_api_key(&tokens.access_token).0,
+        access_token_expires_at,
+        hash_api_key(&tokens.refresh_token).0,
+        expires_at,
+    );
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    diesel::insert_into(cli_sessions_columns::cli_sessions)
+        .values(&cli_session)
+        .execute(&mut conn)
+        .await
+        .map_err(|_| ServiceError::BadRequest("Error creating CLI session".to_string()))?;
+
+    Ok((tokens, cli_session))
+}
+
+/// Resolves the user of an access token of a CLI session which is neither expired nor revoked.
+#[tracing::instrument(skip_all)]
+pub async fn get_user_from_cli_access_token_query(
+    access_token: &str,
+    pg_pool: web::Data<PgPool>,
+) -> Result<(User, CliSession), ServiceError> {
+    use crate::data::schema::cli_sessions::dsl as cli_sessions_columns;
+    use crate::data::schema::users::dsl as users_columns;
+
+    let now = chrono::Utc::now().naive_utc();
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    users_columns::users
+        .inner_join(cli_sessions_columns::cli_sessions)
+        .filter(
+            cli_sessions_columns::access_token_hash
+                .eq_any(get_api_key_hash_candidates(access_token)),
+        )
+        .filter(cli_sessions_columns::access_token_expires_at.gt(now))
+        .filter(cli_sessions_columns::expires_at.gt(now))
+        .filter(cli_sessions_columns::revoked_at.is_null())
+        .select((User::as_select(), CliSession::as_select()))
+        .first::<(User, CliSession)>(&mut conn)
+        .await
+        .optional()
+        .map_err(|_| {
+            ServiceError::BadRequest("Error loading user for CLI access token".to_string())
+        })?
+        .ok_or(ServiceError::Unauthorized)
+}
+
+/// Whether an already exchanged refresh token presented at `now` is a retry of its refresh or was
+/// copied by someone else.
+fn is_reused_refresh_token(
+    used_refresh_token: &CliSessionRefreshToken,
+    now: chrono::NaiveDateTime,
+) -> bool {
+    used_refresh_token.used_at + get_cli_refresh_token_reuse_grace_period() < now
+}
+
+/// Exchanges a refresh token for a new pair of tokens and extends the session. Every refresh
+/// token can only be used once. A refresh token presented again within the reuse grace period
+/// rotates the session once more, so a CLI can retry a refresh whose response got lost. Presented
+/// later, it was copied by someone else and the whole session is revoked.
+#[tracing::instrument(skip_all)]
+pub async fn refresh_cli_session_query(
+    refresh_token: &str,
+    pg_pool: web::Data<PgPool>,
+) -> Result<(CliSessionTokens, CliSession), ServiceError> {
+    use crate::data::schema::cli_session_refresh_tokens::dsl as cli_session_refresh_tokens_columns;
+    use crate::data::schema::cli_sessions::dsl as cli_sessions_columns;
+
+    if !is_cli_refresh_token(refresh_token) {
+        return Err(ServiceError::Unauthorized);
+    }
+
+    let refresh_token_hashes = get_api_key_hash_candidates(refresh_token);
+    let tokens = CliSessionTokens::generate();
+    let access_token_hash = hash_api_key(&tokens.access_token).0;
+    let new_refresh_token_hash = hash_api_key(&tokens.refresh_token).0;
+    let now = chrono::Utc::now().naive_utc();
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        async move {
+            let used_refresh_token = cli_session_refresh_tokens_columns::cli_session_refresh_tokens
+                .filter(
+                    cli_session_refresh_tokens_columns::refresh_token_hash
+                        .eq_any(refresh_token_hashes.clone()),
+                )
+                .select(CliSessionRefreshToken::as_select())
+                .first::<CliSessionRefreshToken>(conn)
+                .await
+                .optional()?;
+
+            let cli_session_id = match used_refresh_token {
+                Some(ref used_refresh_token)
+                    if is_reused_refresh_token(used_refresh_token, now) =>
+                {
+                    let revoked_cli_session = diesel::update(
+                        cli_sessions_columns::cli_sessions
+                            .filter(cli_sessions_columns::id.eq(used_refresh_token.cli_session_id))
+                            .filter(cli_sessions_columns::revoked_at.is_null()),
+                    )
+                    .set((
+                        cli_sessions_columns::revoked_at.eq(now),
+                        cli_sessions_columns::updated_at.eq(chrono::Utc::now().naive_local()),
+                    ))
+                    .returning(CliSession::as_returning())
+                    .get_result::<CliSession>(conn)
+                    .await
+                    .optional()?;
+
+                    if let Some(cli_session) = revoked_cli_session {
+                        log::warn!(
+                            "Revoked CLI session {} of user {} because a refresh token was reused",
+                            cli_session.id,
+                            cli_session.user_id
+                        );
+                    }
+
+                    return Ok(Err(ServiceError::Unauthorized));
+                }
+                Some(ref used_refresh_token) => Some(used_refresh_token.cli_session_id),
+                None => cli_sessions_columns::cli_sessions
+                    .filter(
+                        cli_sessions_columns::refresh_token_hash
+                            .eq_any(refresh_token_hashes.clone()),
+                    )
+                    .select(cli_sessions_columns::id)
+                    .first::<uuid::Uuid>(conn)
+                    .await
+                    .optional()?,
+            };
+            let Some(cli_session_id) = cli_session_id else {
+                return Ok(Err(ServiceError::Unauthorized));
+            };
+
+            let Some(cli_session) = cli_sessions_columns::cli_sessions
+                .filter(cli_sessions_columns::id.eq(cli_session_id))
+                .filter(cli_sessions_columns::expires_at.gt(now))
+                .filter(cli_sessions_columns::revoked_at.is_null())
+                .select(CliSession::as_select())
+                .for_update()
+                .first::<CliSession>(conn)
+                .await
+                .optional()?
+            else {
+                return Ok(Err(ServiceError::Unauthorized));
+            };
+
+            let (access_token_expires_at, expires_at) = cli_session_expiry(cli_session.created_at);
+            if expires_at <= now {
+                return Ok(Err(ServiceError::Unauthorized));
+            }
+
+            // Only the refresh token which was exchanged is recorded. The one a retry within the
+            // grace period replaces was never handed out, presenting it fails without revoking.
+            if refresh_token_hashes.contains(&cli_session.refresh_token_hash) {
+                diesel::insert_into(cli_session_refresh_tokens_columns::cli_session_refresh_tokens)
+                    .values(&CliSessionRefreshToken::from_details(
+                        cli_session.id,
+                        cli_session.refresh_token_hash.clone(),
+                    ))
+                    .on_conflict_do_nothing()
+                    .execute(conn)
+                    .await?;
+            }
+
+            let cli_session = diesel::update(
+                cli_sessions_columns::cli_sessions
+                    .filter(cli_sessions_columns::id.eq(cli_session.id)),
+            )
+            .set((
+                cli_sessions_columns::refresh_token_hash.eq(new_refresh_token_hash),
+                cli_sessions_columns::access_token_hash.eq(access_token_hash),
+                cli_sessions_columns::access_token_expires_at.eq(access_token_expires_at),
+                cli_sessions_columns::expires_at.eq(expires_at),
+                cli_sessions_columns::updated_at.eq(chrono::Utc::now().naive_local()),
+            ))
+            .returning(CliSession::as_returning())
+            .get_result::<CliSession>(conn)
+            .await?;
+
+            Ok(Ok(cli_session))
+        }
+        .scope_boxed()
+    })
+    .await
+    .map_err(|_| ServiceError::BadRequest("Error refreshing CLI session".to_string()))?
+    .map(|cli_session| (tokens, cli_session))
+}
+
+/// Revokes the session of a refresh token. Unknown tokens are ignored.
+#[tracing::instrument(skip_all)]
+pub async fn revoke_cli_session_query(
+    refresh_token: &str,
+    pg_pool: web::Data<PgPool>,
+) -> Result<(), ServiceError> {
+    use crate::data::schema::cli_sessions::dsl as cli_sessions_columns;
+
+    if !is_cli_refresh_token(refresh_token) {
+        return Ok(());
+    }
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    diesel::update(
+        cli_sessions_columns::cli_sessions
+            .filter(
+                cli_sessions_columns::refresh_token_hash
+                    .eq_any(get_api_key_hash_candidates(refresh_token)),
+            )
+            .filter(cli_sessions_columns::revoked_at.is_null()),
+    )
+    .set((
+        cli_sessions_columns::revoked_at.eq(chrono::Utc::now().naive_utc()),
+        cli_sessions_columns::updated_at.eq(chrono::Utc::now().naive_local()),
+    ))
+    .execute(&mut conn)
+    .await
+    .map_err(|_| ServiceError::BadRequest("Error revoking CLI session".to_string()))?;
+
+    Ok(())
+}
+
+/// The CLI sessions of a user which are neither expired nor revoked, newest first.
+#[tracing::instrument(skip(pg_pool))]
+pub async fn get_cli_sessions_for_user_query(
+    user_id: uuid::Uuid,
+    pg_pool: web::Data<PgPool>,
+) -> Result<Vec<CliSession>, ServiceError> {
+    use crate::data::schema::cli_sessions::dsl as cli_sessions_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    cli_sessions_columns::cli_sessions
+        .filter(cli_sessions_columns::user_id.eq(user_id))
+        .filter(cli_sessions_columns::expires_at.gt(chrono::Utc::now().naive_utc()))
+        .filter(cli_sessions_columns::revoked_at.is_null())
+        .order(cli_sessions_columns::created_at.desc())
+        .select(CliSession::as_select())
+        .load::<CliSession>(&mut conn)
+        .await
+        .map_err(|_| ServiceError::BadRequest("Error loading CLI sessions".to_string()))
+}
+
+/// Revokes CLI sessions of a user, only the one with `cli_session_id` if given. Returns the number
+/// of sessions which were revoked.
+#[tracing::instrument(skip(pg_pool))]
+pub async fn revoke_cli_sessions_for_user_query(
+    user_id: uuid::Uuid,
+    cli_session_id: Option<uuid::Uuid>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<usize, ServiceError> {
+    use crate::data::schema::cli_sessions::dsl as cli_sessions_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    let mut query = diesel::update(cli_sessions_columns::cli_sessions)
+        .filter(cli_sessions_columns::user_id.eq(user_id))
+        .filter(cli_sessions_columns::revoked_at.is_null())
+        .into_boxed();
+    if let Some(cli_session_id) = cli_session_id {
+        query = query.filter(cli_sessions_columns::id.eq(cli_session_id));
+    }
+
+    query
+        .set((
+            cli_sessions_columns::revoked_at.eq(chrono::Utc::now().naive_utc()),
+            cli_sessions_columns::updated_at.eq(chrono::Utc::now().naive_local()),
+        ))
+        .execute(&mut conn)
+        .await
+        .map_err(|_| ServiceError::BadRequest("Error revoking CLI sessions".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn used_refresh_token(seconds_ago: i64) -> CliSessionRefreshToken {
+        CliSessionRefreshToken {
+            used_at: chrono::Utc::now().naive_utc() - chrono::Duration::seconds(seconds_ago),
+            ..CliSessionRefreshToken::from_details(uuid::Uuid::new_v4(), "hash".to_string())
+        }
+    }
+
+    #[test]
+    fn refresh_tokens_presented_again_within_the_grace_period_are_retries() {
+        let now = chrono::Utc::now().naive_utc();
+
+        assert!(!is_reused_refresh_token(&used_refresh_token(0), now));
+        assert!(!is_reused_refresh_token(&used_refresh_token(29), now));
+    }
+
+    #[test]
+    fn refresh_tokens_presented_again_after_the_grace_period_are_reused() {
+        let now = chrono::Utc::now().naive_utc();
+
+        assert!(is_reused_refresh_token(&used_refresh_token(31), now));
+        assert!(is_reused_refresh_token(
+            &used_refresh_token(7 * 24 * 3600),
+            now
+        ));
+    }
+
+    #[test]
+    fn access_and_refresh_tokens_can_not_be_mixed_up() {
+        let tokens = CliSessionTokens::generate();
+
+        assert!(is_cli_access_token(&tokens.access_token));
+        assert!(!is_cli_refresh_token(&tokens.access_token));
+        assert!(is_cli_refresh_token(&tokens.refresh_token));
+        assert!(!is_cli_access_token(&tokens.refresh_token));
+        assert_ne!(tokens.access_token, tokens.refresh_token);
+    }
+
+    #[test]
+    fn sessions_never_outlive_their_maximum_lifetime() {
+        let now = chrono::Utc::now().naive_utc();
+
+        let (access_token_expires_at, expires_at) = cli_session_expiry(now);
+        assert!(access_token_expires_at <= expires_at);
+        assert!(expires_at <= now + get_cli_session_max_lifetime());
+
+        // A session refreshed right before its maximum lifetime ends with it
+        let created_at = now - get_cli_session_max_lifetime() + chrono::Duration::minutes(1);
+        let (access_token_expires_at, expires_at) = cli_session_expiry(created_at);
+        assert_eq!(expires_at, created_at + get_cli_session_max_lifetime());
+        assert_eq!(access_token_expires_at, expires_at);
+    }
+}
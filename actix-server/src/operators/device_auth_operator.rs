@@ -0,0 +1,332 @@
+use crate::{
+    data::models::{ApiKeyScope, DeviceAuthorization, DeviceAuthorizationStatus, RedisPool},
+    errors::ServiceError,
+};
+use actix_web::web;
+use bb8_redis::redis;
+use rand::{distributions::Alphanumeric, Rng};
+
+const DEVICE_CODE_LENGTH: usize = 40;
+/// Consonants only, so that user codes can not spell words and are easy to type (RFC 8628
+/// section 6.1).
+const USER_CODE_ALPHABET: &[u8] = b"BCDFGHJKLMNPQRSTVWXZ";
+const USER_CODE_LENGTH: usize = 8;
+
+fn device_authorization_key(id: &str) -> String {
+    format!("device_authorization:{}", id)
+}
+
+fn device_authorization_poll_key(id: &str) -> String {
+    format!("device_authorization_poll:{}", id)
+}
+
+fn device_user_code_key(user_code: &str) -> String {
+    format!("device_user_code:{}", user_code)
+}
+
+fn hash_device_code(device_code: &str) -> String {
+    blake3::hash(device_code.as_bytes()).to_string()
+}
+
+fn generate_user_code() -> String {
+    let mut rng = rand::thread_rng();
+    let code = (0..USER_CODE_LENGTH)
+        .map(|_| USER_CODE_ALPHABET[rng.gen_range(0..USER_CODE_ALPHABET.len())] as char)
+        .collect::<String>();
+
+    format!(
+        "{}-{}",
+        &code[..USER_CODE_LENGTH / 2],
+        &code[USER_CODE_LENGTH / 2..]
+    )
+}
+
+/// Brings a user code typed by a user into the form it was generated in, ignoring case, spaces
+/// and dashes.
+pub fn normalize_user_code(user_code: &str) -> String {
+    let code = user_code
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_uppercase())
+        .collect::<String>();
+
+    if code.len() != USER_CODE_LENGTH {
+        return code;
+    }
+
+    format!(
+        "{}-{}",
+        &code[..USER_CODE_LENGTH / 2],
+        &code[USER_CODE_LENGTH / 2..]
+    )
+}
+
+/// Starts a device authorization which expires after `ttl_seconds`. Returns the device code,
+/// which is only returned to the device, together with the authorization.
+#[tracing::instrument(skip(redis_pool))]
+pub async fn create_device_authorization_query(
+    client_id: String,
+    hostname: String,
+    cli_version: String,
+    scopes: Vec<ApiKeyScope>,
+    ttl_seconds: u64,
+    redis_pool: web::Data<RedisPool>,
+) -> Result<(String, DeviceAuthorization), ServiceError> {
+    let mut redis_conn = redis_pool
+        .get()
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Could not get redis connection".into()))?;
+
+    let device_code = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(DEVICE_CODE_LENGTH)
+        .map(char::from)
+        .collect::<String>();
+    let id = hash_device_code(&device_code);
+
+    // User codes are short, so a code which is still in use may be generated again
+    let mut user_code = None;
+    for _ in 0..5 {
+        let candidate = generate_user_code();
+        let reserved: Option<String> = redis::cmd("SET")
+            .arg(device_user_code_key(&candidate))
+            .arg(&id)
+            .arg("NX")
+            .arg("EX")
+            .arg(ttl_seconds)
+            .query_async(&mut *redis_conn)
+            .await
+            .map_err(|_| {
+                ServiceError::InternalServerError("Failed to reserve device user code".into())
+            })?;
+        if reserved.is_some() {
+            user_code = Some(candidate);
+            break;
+        }
+    }
+    let user_code = user_code.ok_or_else(|| {
+        ServiceError::InternalServerError("Could not generate a unique device user code".into())
+    })?;
+
+    let authorization = DeviceAuthorization {
+        id,
+        user_code,
+        client_id,
+        hostname,
+        cli_version,
+        scopes,
+        status: DeviceAuthorizationStatus::Pending,
+    };
+    let authorization_json = serde_json::to_string(&authorization).map_err(|_| {
+        ServiceError::InternalServerError("Failed to serialize device authorization".into())
+    })?;
+
+    let _: () = redis::cmd("SET")
+        .arg(device_authorization_key(&authorization.id))
+        .arg(authorization_json)
+        .arg("EX")
+        .arg(ttl_seconds)
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| {
+            ServiceError::InternalServerError("Failed to create device authorization".into())
+        })?;
+
+    Ok((device_code, authorization))
+}
+
+/// The device authorization a user code was issued for, as long as it was not approved or
+/// denied yet.
+#[tracing::instrument(skip(redis_pool))]
+pub async fn get_device_authorization_by_user_code_query(
+    user_code: &str,
+    redis_pool: web::Data<RedisPool>,
+) -> Result<DeviceAuthorization, ServiceError> {
+    let mut redis_conn = redis_pool
+        .get()
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Could not get redis connection".into()))?;
+
+    let id: Option<String> = redis::cmd("GET")
+        .arg(device_user_code_key(&normalize_user_code(user_code)))
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| {
+            ServiceError::InternalServerError("Failed to get device authorization".into())
+        })?;
+    let id = id.ok_or(ServiceError::NotFound)?;
+
+    let authorization_json: Option<String> = redis::cmd("GET")
+        .arg(device_authorization_key(&id))
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| {
+            ServiceError::InternalServerError("Failed to get device authorization".into())
+        })?;
+
+    authorization_json
+        .and_then(|authorization_json| {
+            serde_json::from_str::<DeviceAuthorization>(&authorization_json).ok()
+        })
+        .filter(|authorization| authorization.status == DeviceAuthorizationStatus::Pending)
+        .ok_or(ServiceError::NotFound)
+}
+
+/// Records the decision of the user. The user code can not be used again afterwards.
+#[tracing::instrument(skip(redis_pool))]
+pub async fn decide_device_authorization_query(
+    mut authorization: DeviceAuthorization,
+    status: DeviceAuthorizationStatus,
+    redis_pool: web::Data<RedisPool>,
+) -> Result<(), ServiceError> {
+    let mut redis_conn = redis_pool
+        .get()
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Could not get redis connection".into()))?;
+
+    authorization.status = status;
+    let authorization_json = serde_json::to_string(&authorization).map_err(|_| {
+        ServiceError::InternalServerError("Failed to serialize device authorization".into())
+    })?;
+
+    let reply: redis::Value = redis::pipe()
+        .atomic()
+        .cmd("SET")
+        .arg(device_authorization_key(&authorization.id))
+        .arg(authorization_json)
+        .arg("XX")
+        .arg("KEEPTTL")
+        .del(device_user_code_key(&authorization.user_code))
+        .ignore()
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| {
+            ServiceError::InternalServerError("Failed to update device authorization".into())
+        })?;
+
+    check_device_authorization_decided(&reply)
+}
+
+/// Checks the reply of the transaction recording a decision. Only the reply of the `SET` is
+/// left since the `DEL` of the user code is ignored.
+fn check_device_authorization_decided(reply: &redis::Value) -> Result<(), ServiceError> {
+    let (updated,): (Option<String>,) = redis::from_redis_value(reply).map_err(|_| {
+        ServiceError::InternalServerError("Failed to update device authorization".into())
+    })?;
+
+    // The authorization expired while the user was deciding
+    if updated.is_none() {
+        return Err(ServiceError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// The outcome of a device polling for its authorization.
+#[derive(Debug)]
+pub enum DeviceAuthorizationPoll {
+    Pending,
+    /// The device polled again before the polling interval passed.
+    SlowDown,
+    Denied,
+    /// The user approved the login. Returned only once, the authorization is deleted.
+    Approved(DeviceAuthorization),
+    /// The device code expired or is unknown.
+    Expired,
+}
+
+/// Checks whether the user decided on the authorization of `device_code`. Devices must wait
+/// `interval_seconds` between polls.
+#[tracing::instrument(skip(device_code, redis_pool))]
+pub async fn poll_device_authorization_query(
+    device_code: &str,
+    interval_seconds: u64,
+    redis_pool: web::Data<RedisPool>,
+) -> Result<DeviceAuthorizationPoll, ServiceError> {
+    let mut redis_conn = redis_pool
+        .get()
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Could not get redis connection".into()))?;
+
+    let id = hash_device_code(device_code);
+
+    // Polls sent right on time arrive slightly less than an interval apart
+    let polled: Option<String> = redis::cmd("SET")
+        .arg(device_authorization_poll_key(&id))
+        .arg(1)
+        .arg("NX")
+        .arg("PX")
+        .arg((interval_seconds * 1000).saturating_sub(500).max(1))
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| {
+            ServiceError::InternalServerError("Failed to record device authorization poll".into())
+        })?;
+    if polled.is_none() {
+        return Ok(DeviceAuthorizationPoll::SlowDown);
+    }
+
+    let authorization_json: Option<String> = redis::cmd("GET")
+        .arg(device_authorization_key(&id))
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| {
+            ServiceError::InternalServerError("Failed to get device authorization".into())
+        })?;
+    let Some(authorization) = authorization_json.and_then(|authorization_json| {
+        serde_json::from_str::<DeviceAuthorization>(&authorization_json).ok()
+    }) else {
+        return Ok(DeviceAuthorizationPoll::Expired);
+    };
+
+    if authorization.status == DeviceAuthorizationStatus::Pending {
+        return Ok(DeviceAuthorizationPoll::Pending);
+    }
+
+    // Deleting makes sure that concurrent polls can not both exchange the authorization
+    let deleted: usize = redis::cmd("DEL")
+        .arg(device_authorization_key(&id))
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| {
+            ServiceError::InternalServerError("Failed to delete device authorization".into())
+        })?;
+    if deleted == 0 {
+        return Ok(DeviceAuthorizationPoll::Expired);
+    }
+
+    match authorization.status {
+        DeviceAuthorizationStatus::Approved { .. } => {
+            Ok(DeviceAuthorizationPoll::Approved(authorization))
+        }
+        _ => Ok(DeviceAuthorizationPoll::Denied),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decisions_on_pending_authorizations_are_recorded() {
+        // EXEC replies with `OK` for the `SET`, the ignored `DEL` is dropped from the reply
+        let approved = redis::Value::Bulk(vec![redis::Value::Okay]);
+        assert!(check_device_authorization_decided(&approved).is_ok());
+    }
+
+    #[test]
+    fn decisions_on_expired_authorizations_are_rejected() {
+        let expired = redis::Value::Bulk(vec![redis::Value::Nil]);
+        assert!(matches!(
+            check_device_authorization_decided(&expired),
+            Err(ServiceError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn user_codes_are_normalized() {
+        assert_eq!(normalize_user_code("bcdf ghjk"), "BCDF-GHJK");
+        assert_eq!(normalize_user_code("BCDF-GHJK"), "BCDF-GHJK");
+        assert_eq!(normalize_user_code(&generate_user_code()).len(), 9);
+    }
+}
@@ -1,2 +1,6 @@
 pub mod user_operator;
 pub mod api_key_operator;
+pub mod session_operator;
+pub mod device_auth_operator;
+pub mod cli_session_operator;
+pub mod organization_operator;
@@ -0,0 +1,677 @@
+use crate::{
+    data::models::{
+        Organization, OrganizationInvitation, OrganizationMembership, OrganizationRole,
+        OrganizationUser, PgPool, User,
+    },
+    errors::ServiceError,
+};
+use actix_web::web;
+use diesel::prelude::*;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+
+/// Creates an organization with the user as its owner.
+#[tracing::instrument(skip(pg_pool))]
+pub async fn create_organization_query(
+    name: String,
+    user_id: uuid::Uuid,
+    pg_pool: web::Data<PgPool>,
+) -> Result<OrganizationMembership, ServiceError> {
+    use crate::data::schema::organization_users::dsl as organization_users_columns;
+    use crate::data::schema::organizations::dsl as organizations_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        async move {
+            let organization = diesel::insert_into(organizations_columns::organizations)
+                .values(&Organization::from_details(name))
+                .get_result::<Organization>(conn)
+                .await?;
+
+            let organization_user =
+                diesel::insert_into(organization_users_columns::organization_users)
+                    .values(&OrganizationUser::from_details(
+                        organization.id,
+                        user_id,
+                        OrganizationRole::Owner,
+                    ))
+                    .get_result::<OrganizationUser>(conn)
+                    .await?;
+
+            Ok(OrganizationMembership::from((
+                organization,
+                organization_user,
+            )))
+        }
+        .scope_boxed()
+    })
+    .await
+    .map_err(|_| ServiceError::BadRequest("Error creating organization".to_string()))
+}
+
+/// The organizations the user belongs to, oldest first.
+#[tracing::instrument(skip(pg_pool))]
+pub async fn get_organizations_for_user_query(
+    user_id: uuid::Uuid,
+    pg_pool: web::Data<PgPool>,
+) -> Result<Vec<OrganizationMembership>, ServiceError> {
+    use crate::data::schema::organization_users::dsl as organization_users_columns;
+    use crate::data::schema::organizations::dsl as organizations_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    let organizations = organizations_columns::organizations
+        .inner_join(organization_users_columns::organization_users)
+        .filter(organization_users_columns::user_id.eq(user_id))
+        .order(organizations_columns::created_at.asc())
+        .select((Organization::as_select(), OrganizationUser::as_select()))
+        .load::<(Organization, OrganizationUser)>(&mut conn)
+        .await
+        .map_err(|_| ServiceError::BadRequest("Error loading organizations".to_string()))?;
+
+    Ok(organizations
+        .into_iter()
+        .map(OrganizationMembership::from)
+        .collect())
+}
+
+/// The membership of the user in the organization, `None` if the user does not belong to it or
+/// the organization does not exist.
+#[tracing::instrument(skip(pg_pool))]
+pub async fn get_organization_membership_query(
+    organization_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+    pg_pool: web::Data<PgPool>,
+) -> Result<Option<OrganizationMembership>, ServiceError> {
+    use crate::data::schema::organization_users::dsl as organization_users_columns;
+    use crate::data::schema::organizations::dsl as organizations_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    let membership = organizations_columns::organizations
+        .inner_join(organization_users_columns::organization_users)
+        .filter(organizations_columns::id.eq(organization_id))
+        .filter(organization_users_columns::user_id.eq(user_id))
+        .select((Organization::as_select(), OrganizationUser::as_select()))
+        .first::<(Organization, OrganizationUser)>(&mut conn)
+        .await
+        .optional()
+        .map_err(|_| {
+            ServiceError::BadRequest("Error loading organization membership".to_string())
+        })?;
+
+    Ok(membership.map(OrganizationMembership::from))
+}
+
+#[tracing::instrument(skip(pg_pool))]
+pub async fn update_organization_query(
+    organization_id: uuid::Uuid,
+    name: String,
+    pg_pool: web::Data<PgPool>,
+) -> Result<Organization, ServiceError> {
+    use crate::data::schema::organizations::dsl as organizations_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    diesel::update(
+        organizations_columns::organizations.filter(organizations_columns::id.eq(organization_id)),
+    )
+    .set((
+        organizations_columns::name.eq(name),
+        organizations_columns::updated_at.eq(chrono::Utc::now().naive_local()),
+    ))
+    .get_result::<Organization>(&mut conn)
+    .await
+    .optional()
+    .map_err(|_| ServiceError::BadRequest("Error updating organization".to_string()))?
+    .ok_or(ServiceError::NotFound)
+}
+
+/// Deletes the organization together with its memberships.
+#[tracing::instrument(skip(pg_pool))]
+pub async fn delete_organization_query(
+    organization_id: uuid::Uuid,
+    pg_pool: web::Data<PgPool>,
+) -> Result<(), ServiceError> {
+    use crate::data::schema::organizations::dsl as organizations_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    let deleted_count = diesel::delete(
+        organizations_columns::organizations.filter(organizations_columns::id.eq(organization_id)),
+    )
+    .execute(&mut conn)
+    .await
+    .map_err(|_| ServiceError::BadRequest("Error deleting organization".to_string()))?;
+
+    if deleted_count == 0 {
+        return Err(ServiceError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// The members of the organization, in the order they were added.
+#[tracing::instrument(skip(pg_pool))]
+pub async fn get_organization_users_query(
+    organization_id: uuid::Uuid,
+    pg_pool: web::Data<PgPool>,
+) -> Result<Vec<(OrganizationUser, User)>, ServiceError> {
+    use crate::data::schema::organization_users::dsl as organization_users_columns;
+    use crate::data::schema::users::dsl as users_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    organization_users_columns::organization_users
+        .inner_join(users_columns::users)
+        .filter(organization_users_columns::organization_id.eq(organization_id))
+        .order(organization_users_columns::created_at.asc())
+        .select((OrganizationUser::as_select(), User::as_select()))
+        .load::<(OrganizationUser, User)>(&mut conn)
+        .await
+        .map_err(|_| ServiceError::BadRequest("Error loading organization users".to_string()))
+}
+
+/// How long an invitation can be accepted. Set with `ORGANIZATION_INVITATION_TTL_DAYS`.
+pub fn get_organization_invitation_ttl() -> chrono::Duration {
+    chrono::Duration::days(
+        std::env::var("ORGANIZATION_INVITATION_TTL_DAYS")
+            .ok()
+            .and_then(|ttl| ttl.parse().ok())
+            .unwrap_or(7),
+    )
+}
+
+/// Invites the email to the organization. Inviting an email again replaces the role and restarts
+/// the expiry of its invitation. Whether a user with the email exists is never looked at, so that
+/// inviting can not be used to find out who has an account.
+#[tracing::instrument(skip(pg_pool))]
+pub async fn create_organization_invitation_query(
+    organization_id: uuid::Uuid,
+    email: String,
+    role: OrganizationRole,
+    invited_by: uuid::Uuid,
+    pg_pool: web::Data<PgPool>,
+) -> Result<(), ServiceError> {
+    use crate::data::schema::organization_invitations::dsl as organization_invitations_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    let invitation = OrganizationInvitation::from_details(
+        organization_id,
+        email.to_lowercase(),
+        role,
+        invited_by,
+        chrono::Utc::now().naive_utc() + get_organization_invitation_ttl(),
+    );
+
+    diesel::insert_into(organization_invitations_columns::organization_invitations)
+        .values(&invitation)
+        .on_conflict((
+            organization_invitations_columns::organization_id,
+            organization_invitations_columns::email,
+        ))
+        .do_update()
+        .set((
+            organization_invitations_columns::role.eq(&invitation.role),
+            organization_invitations_columns::invited_by.eq(invitation.invited_by),
+            organization_invitations_columns::expires_at.eq(invitation.expires_at),
+            organization_invitations_columns::updated_at.eq(invitation.updated_at),
+        ))
+        .execute(&mut conn)
+        .await
+        .map_err(|_| {
+            ServiceError::BadRequest("Error creating organization invitation".to_string())
+        })?;
+
+    Ok(())
+}
+
+/// The pending invitations of the organization, newest first.
+#[tracing::instrument(skip(pg_pool))]
+pub async fn get_organization_invitations_query(
+    organization_id: uuid::Uuid,
+    pg_pool: web::Data<PgPool>,
+) -> Result<Vec<OrganizationInvitation>, ServiceError> {
+    use crate::data::schema::organization_invitations::dsl as organization_invitations_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    organization_invitations_columns::organization_invitations
+        .filter(organization_invitations_columns::organization_id.eq(organization_id))
+        .filter(organization_invitations_columns::expires_at.gt(chrono::Utc::now().naive_utc()))
+        .order(organization_invitations_columns::created_at.desc())
+        .select(OrganizationInvitation::as_select())
+        .load::<OrganizationInvitation>(&mut conn)
+        .await
+        .map_err(|_| ServiceError::BadRequest("Error loading organization invitations".to_string()))
+}
+
+/// Withdraws an invitation of the organization.
+#[tracing::instrument(skip(pg_pool))]
+pub async fn delete_organization_invitation_query(
+    organization_id: uuid::Uuid,
+    invitation_id: uuid::Uuid,
+    pg_pool: web::Data<PgPool>,
+) -> Result<(), ServiceError> {
+    use crate::data::schema::organization_invitations::dsl as organization_invitations_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    let deleted_count = diesel::delete(
+        organization_invitations_columns::organization_invitations
+            .filter(organization_invitations_columns::id.eq(invitation_id))
+            .filter(organization_invitations_columns::organization_id.eq(organization_id)),
+    )
+    .execute(&mut conn)
+    .await
+    .map_err(|_| ServiceError::BadRequest("Error deleting organization invitation".to_string()))?;
+
+    if deleted_count == 0 {
+        return Err(ServiceError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// The lowercased emails that a provider reported as verified for one of the identities of the
+/// user. Invitations can only be seen and answered through these emails.
+async fn get_verified_emails(
+    user_id: uuid::Uuid,
+    conn: &mut diesel_async::AsyncPgConnection,
+) -> Result<Vec<String>, diesel::result::Error> {
+    use crate::data::schema::identities::dsl as identities_columns;
+
+    let emails = identities_columns::identities
+        .filter(identities_columns::user_id.eq(user_id))
+        .filter(identities_columns::email_verified.eq(true))
+        .select(identities_columns::email)
+        .load::<Option<String>>(conn)
+        .await?;
+
+    Ok(emails
+        .into_iter()
+        .flatten()
+        .map(|email| email.to_lowercase())
+        .collect())
+}
+
+/// The pending invitations sent to the verified emails of the user, along with the organizations
+/// they are for.
+#[tracing::instrument(skip(pg_pool))]
+pub async fn get_invitations_for_user_query(
+    user_id: uuid::Uuid,
+    pg_pool: web::Data<PgPool>,
+) -> Result<Vec<(OrganizationInvitation, Organization)>, ServiceError> {
+    use crate::data::schema::organization_invitations::dsl as organization_invitations_columns;
+    use crate::data::schema::organizations::dsl as organizations_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    let emails = get_verified_emails(user_id, &mut conn)
+        .await
+        .map_err(|_| ServiceError::BadRequest("Error loading invitations".to_string()))?;
+
+    organization_invitations_columns::organization_invitations
+        .inner_join(organizations_columns::organizations)
+        .filter(organization_invitations_columns::email.eq_any(emails))
+        .filter(organization_invitations_columns::expires_at.gt(chrono::Utc::now().naive_utc()))
+        .order(organization_invitations_columns::created_at.desc())
+        .select((
+            OrganizationInvitation::as_select(),
+            Organization::as_select(),
+        ))
+        .load::<(OrganizationInvitation, Organization)>(&mut conn)
+        .await
+        .map_err(|_| ServiceError::BadRequest("Error loading invitations".to_string()))
+}
+
+/// Accepts an invitation sent to a verified email of the user, adding the user to the
+/// organization with the invited role. Fails with `NotFound` if the invitation does not exist, was
+/// sent to another or unverified email or has expired.
+#[tracing::instrument(skip(pg_pool))]
+pub async fn accept_organization_invitation_query(
+    invitation_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+    pg_pool: web::Data<PgPool>,
+) -> Result<OrganizationMembership, ServiceError> {
+    use crate::data::schema::organization_invitations::dsl as organization_invitations_columns;
+    use crate::data::schema::organization_users::dsl as organization_users_columns;
+    use crate::data::schema::organizations::dsl as organizations_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        async move {
+            let emails = get_verified_emails(user_id, conn).await?;
+
+            let Some(invitation) = organization_invitations_columns::organization_invitations
+                .filter(organization_invitations_columns::id.eq(invitation_id))
+                .filter(organization_invitations_columns::email.eq_any(emails))
+                .filter(
+                    organization_invitations_columns::expires_at.gt(chrono::Utc::now().naive_utc()),
+                )
+                .select(OrganizationInvitation::as_select())
+                .for_update()
+                .first::<OrganizationInvitation>(conn)
+                .await
+                .optional()?
+            else {
+                return Ok(Err(ServiceError::NotFound));
+            };
+
+            // Nothing is written before this point, so users who are already members keep
+            // the invitation
+            let Some(organization_user) =
+                diesel::insert_into(organization_users_columns::organization_users)
+                    .values(&OrganizationUser::from_details(
+                        invitation.organization_id,
+                        user_id,
+                        invitation.organization_role(),
+                    ))
+                    .on_conflict_do_nothing()
+                    .get_result::<OrganizationUser>(conn)
+                    .await
+                    .optional()?
+            else {
+                return Ok(Err(ServiceError::BadRequest(
+                    "You are already a member of the organization".to_string(),
+                )));
+            };
+
+            diesel::delete(
+                organization_invitations_columns::organization_invitations
+                    .filter(organization_invitations_columns::id.eq(invitation.id)),
+            )
+            .execute(conn)
+            .await?;
+
+            let organization = organizations_columns::organizations
+                .filter(organizations_columns::id.eq(invitation.organization_id))
+                .select(Organization::as_select())
+                .first::<Organization>(conn)
+                .await?;
+
+            Ok(Ok(OrganizationMembership::from((
+                organization,
+                organization_user,
+            ))))
+        }
+        .scope_boxed()
+    })
+    .await
+    .map_err(|_| ServiceError::BadRequest("Error accepting organization invitation".to_string()))?
+}
+
+/// Declines an invitation sent to a verified email of the user.
+#[tracing::instrument(skip(pg_pool))]
+pub async fn decline_organization_invitation_query(
+    invitation_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+    pg_pool: web::Data<PgPool>,
+) -> Result<(), ServiceError> {
+    use crate::data::schema::organization_invitations::dsl as organization_invitations_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    let emails = get_verified_emails(user_id, &mut conn).await.map_err(|_| {
+        ServiceError::BadRequest("Error declining organization invitation".to_string())
+    })?;
+
+    let deleted_count = diesel::delete(
+        organization_invitations_columns::organization_invitations
+            .filter(organization_invitations_columns::id.eq(invitation_id))
+            .filter(organization_invitations_columns::email.eq_any(emails)),
+    )
+    .execute(&mut conn)
+    .await
+    .map_err(|_| ServiceError::BadRequest("Error declining organization invitation".to_string()))?;
+
+    if deleted_count == 0 {
+        return Err(ServiceError::NotFound);
+    }
+
+    Ok(())
+}
+
+/// Locks the memberships of the organization so that concurrent changes can not remove its last
+/// owner, and returns the membership of the user along with the number of owners.
+async fn lock_organization_user(
+    organization_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+    conn: &mut diesel_async::AsyncPgConnection,
+) -> Result<Option<(OrganizationUser, usize)>, diesel::result::Error> {
+    use crate::data::schema::organization_users::dsl as organization_users_columns;
+
+    let organization_users = organization_users_columns::organization_users
+        .filter(organization_users_columns::organization_id.eq(organization_id))
+        .select(OrganizationUser::as_select())
+        .for_update()
+        .load::<OrganizationUser>(conn)
+        .await?;
+
+    let owner_count = organization_users
+        .iter()
+        .filter(|organization_user| {
+            organization_user.organization_role() == OrganizationRole::Owner
+        })
+        .count();
+
+    Ok(organization_users
+        .into_iter()
+        .find(|organization_user| organization_user.user_id == user_id)
+        .map(|organization_user| (organization_user, owner_count)))
+}
+
+/// Whether a member with `acting_role` can change the role of a member from `current_role` to
+/// `role` while the organization has `owner_count` owners.
+fn check_role_change(
+    current_role: OrganizationRole,
+    role: OrganizationRole,
+    acting_role: OrganizationRole,
+    owner_count: usize,
+) -> Result<(), ServiceError> {
+    if current_role > acting_role || role > acting_role {
+        return Err(ServiceError::Forbidden);
+    }
+    if current_role == OrganizationRole::Owner
+        && role != OrganizationRole::Owner
+        && owner_count == 1
+    {
+        return Err(ServiceError::BadRequest(
+            "The last owner of an organization can not be demoted".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Whether a member with `acting_role` can remove a member with `current_role`, which is itself
+/// if `is_self`, while the organization has `owner_count` owners.
+fn check_removal(
+    current_role: OrganizationRole,
+    is_self: bool,
+    acting_role: OrganizationRole,
+    owner_count: usize,
+) -> Result<(), ServiceError> {
+    if !is_self && (acting_role < OrganizationRole::Admin || current_role > acting_role) {
+        return Err(ServiceError::Forbidden);
+    }
+    if current_role == OrganizationRole::Owner && owner_count == 1 {
+        return Err(ServiceError::BadRequest(
+            "The last owner of an organization can not be removed, delete the organization instead"
+                .to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Changes the role of a member. Members can only be given, and taken away, roles up to
+/// `acting_role`, and the last owner can not be demoted.
+#[tracing::instrument(skip(pg_pool))]
+pub async fn update_organization_user_role_query(
+    organization_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+    role: OrganizationRole,
+    acting_role: OrganizationRole,
+    pg_pool: web::Data<PgPool>,
+) -> Result<(OrganizationUser, User), ServiceError> {
+    use crate::data::schema::organization_users::dsl as organization_users_columns;
+    use crate::data::schema::users::dsl as users_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        async move {
+            let Some((organization_user, owner_count)) =
+                lock_organization_user(organization_id, user_id, conn).await?
+            else {
+                return Ok(Err(ServiceError::NotFound));
+            };
+
+            if let Err(e) = check_role_change(
+                organization_user.organization_role(),
+                role,
+                acting_role,
+                owner_count,
+            ) {
+                return Ok(Err(e));
+            }
+
+            let organization_user = diesel::update(
+                organization_users_columns::organization_users
+                    .filter(organization_users_columns::id.eq(organization_user.id)),
+            )
+            .set((
+                organization_users_columns::role.eq(role.as_str()),
+                organization_users_columns::updated_at.eq(chrono::Utc::now().naive_local()),
+            ))
+            .get_result::<OrganizationUser>(conn)
+            .await?;
+
+            let user = users_columns::users
+                .filter(users_columns::id.eq(user_id))
+                .select(User::as_select())
+                .first::<User>(conn)
+                .await?;
+
+            Ok(Ok((organization_user, user)))
+        }
+        .scope_boxed()
+    })
+    .await
+    .map_err(|_| ServiceError::BadRequest("Error updating organization user".to_string()))?
+}
+
+/// Removes a member from the organization. Users can always leave an organization, removing
+/// someone else requires the admin role and at least the role of the removed member. The last
+/// owner can not be removed.
+#[tracing::instrument(skip(pg_pool))]
+pub async fn remove_organization_user_query(
+    organization_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+    acting_user_id: uuid::Uuid,
+    acting_role: OrganizationRole,
+    pg_pool: web::Data<PgPool>,
+) -> Result<(), ServiceError> {
+    use crate::data::schema::organization_users::dsl as organization_users_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        async move {
+            let Some((organization_user, owner_count)) =
+                lock_organization_user(organization_id, user_id, conn).await?
+            else {
+                return Ok(Err(ServiceError::NotFound));
+            };
+
+            if let Err(e) = check_removal(
+                organization_user.organization_role(),
+                user_id == acting_user_id,
+                acting_role,
+                owner_count,
+            ) {
+                return Ok(Err(e));
+            }
+
+            diesel::delete(
+                organization_users_columns::organization_users
+                    .filter(organization_users_columns::id.eq(organization_user.id)),
+            )
+            .execute(conn)
+            .await?;
+
+            Ok(Ok(()))
+        }
+        .scope_boxed()
+    })
+    .await
+    .map_err(|_| ServiceError::BadRequest("Error removing organization user".to_string()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use OrganizationRole::{Admin, Member, Owner};
+
+    #[test]
+    fn roles_can_only_be_changed_up_to_the_acting_role() {
+        assert!(check_role_change(Member, Admin, Admin, 1).is_ok());
+        assert!(check_role_change(Admin, Member, Admin, 1).is_ok());
+        assert!(check_role_change(Member, Owner, Owner, 1).is_ok());
+
+        // Admins can neither make owners nor touch them
+        assert!(matches!(
+            check_role_change(Member, Owner, Admin, 1),
+            Err(ServiceError::Forbidden)
+        ));
+        assert!(matches!(
+            check_role_change(Owner, Member, Admin, 2),
+            Err(ServiceError::Forbidden)
+        ));
+        assert!(matches!(
+            check_role_change(Member, Admin, Member, 1),
+            Err(ServiceError::Forbidden)
+        ));
+    }
+
+    #[test]
+    fn the_last_owner_can_not_be_demoted() {
+        assert!(matches!(
+            check_role_change(Owner, Admin, Owner, 1),
+            Err(ServiceError::BadRequest(_))
+        ));
+        assert!(check_role_change(Owner, Owner, Owner, 1).is_ok());
+        assert!(check_role_change(Owner, Admin, Owner, 2).is_ok());
+    }
+
+    #[test]
+    fn members_can_leave_but_only_admins_remove_others() {
+        assert!(check_removal(Member, true, Member, 1).is_ok());
+        assert!(check_removal(Admin, true, Admin, 1).is_ok());
+        assert!(check_removal(Member, false, Admin, 1).is_ok());
+        assert!(check_removal(Admin, false, Admin, 1).is_ok());
+        assert!(check_removal(Owner, false, Owner, 2).is_ok());
+
+        assert!(matches!(
+            check_removal(Member, false, Member, 1),
+            Err(ServiceError::Forbidden)
+        ));
+        assert!(matches!(
+            check_removal(Owner, false, Admin, 2),
+            Err(ServiceError::Forbidden)
+        ));
+    }
+
+    #[test]
+    fn the_last_owner_can_not_leave() {
+        assert!(matches!(
+            check_removal(Owner, true, Owner, 1),
+            Err(ServiceError::BadRequest(_))
+        ));
+        assert!(check_removal(Owner, true, Owner, 2).is_ok());
+    }
+}
@@ -0,0 +1,350 @@
+use crate::{
+    data::models::{BrowserSession, RedisPool},
+    errors::ServiceError,
+    SECONDS_IN_DAY,
+};
+use actix_web::web;
+use bb8_redis::redis;
+use std::collections::HashMap;
+
+/// User agents are cut off after this many bytes.
+const MAX_USER_AGENT_LENGTH: usize = 512;
+
+/// Updates `last_seen_at` of a session if it exists. Sessions created before sessions were
+/// tracked per user are plain strings and are only checked for existence.
+const TOUCH_SESSION_SCRIPT: &str = r#"
+local session_type = redis.call("TYPE", KEYS[1]).ok
+if session_type == "hash" then
+  redis.call("HSET", KEYS[1], "last_seen_at", ARGV[1])
+end
+return session_type ~= "none" and 1 or 0
+"#;
+
+fn oidc_session_key(session_id: &str) -> String {
+    format!("oidc_session:{}", session_id)
+}
+
+fn user_sessions_key(user_id: uuid::Uuid) -> String {
+    format!("user_sessions:{}", user_id)
+}
+
+fn oidc_sessions_by_sid_key(provider: &str, sid: &str) -> String {
+    format!("oidc_sessions:{}:sid:{}", provider, sid)
+}
+
+fn oidc_sessions_by_sub_key(provider: &str, sub: &str) -> String {
+    format!("oidc_sessions:{}:sub:{}", provider, sub)
+}
+
+/// Registers a cookie session created by logging in at an OpenID provider, indexed by the user
+/// so that `get_user_sessions_query` can list it, and by the provider's session id (`sid`) and
+/// subject (`sub`) so that `end_oidc_sessions_query` can find it. Returns the id to store in the
+/// session.
+#[tracing::instrument(skip(redis_pool))]
+pub async fn create_oidc_session_query(
+    provider: String,
+    sid: Option<String>,
+    sub: String,
+    user_id: uuid::Uuid,
+    user_agent: Option<String>,
+    ip: Option<String>,
+    redis_pool: web::Data<RedisPool>,
+) -> Result<String, ServiceError> {
+    let mut redis_conn = redis_pool
+        .get()
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Could not get redis connection".into()))?;
+
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let session_key = oidc_session_key(&session_id);
+    let user_key = user_sessions_key(user_id);
+    let sub_key = oidc_sessions_by_sub_key(&provider, &sub);
+    let now = chrono::Utc::now().timestamp();
+
+    let mut fields = vec![
+        ("user_id", user_id.to_string()),
+        ("provider", provider.clone()),
+        ("subject", sub.clone()),
+        ("created_at", now.to_string()),
+        ("last_seen_at", now.to_string()),
+    ];
+    if let Some(mut user_agent) = user_agent {
+        if user_agent.len() > MAX_USER_AGENT_LENGTH {
+            let mut end = MAX_USER_AGENT_LENGTH;
+            while !user_agent.is_char_boundary(end) {
+                end -= 1;
+            }
+            user_agent.truncate(end);
+        }
+        fields.push(("user_agent", user_agent));
+    }
+    if let Some(ip) = ip {
+        fields.push(("ip", ip));
+    }
+
+    let mut pipe = redis::pipe();
+    pipe.atomic()
+        .hset_multiple(&session_key, &fields)
+        .ignore()
+        .expire(&session_key, SECONDS_IN_DAY as i64)
+        .ignore()
+        .sadd(&user_key, &session_id)
+        .ignore()
+        .expire(&user_key, SECONDS_IN_DAY as i64)
+        .ignore()
+        .sadd(&sub_key, &session_id)
+        .ignore()
+        .expire(&sub_key, SECONDS_IN_DAY as i64)
+        .ignore();
+    if let Some(sid) = sid {
+        let sid_key = oidc_sessions_by_sid_key(&provider, &sid);
+        pipe.sadd(&sid_key, &session_id)
+            .ignore()
+            .expire(&sid_key, SECONDS_IN_DAY as i64)
+            .ignore();
+    }
+
+    let _: () = pipe
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Failed to create OIDC session".into()))?;
+
+    Ok(session_id)
+}
+
+/// Whether the session was neither ended nor expired. Records the time the session was last seen.
+#[tracing::instrument(skip(redis_pool))]
+pub async fn touch_oidc_session_query(
+    session_id: &str,
+    redis_pool: web::Data<RedisPool>,
+) -> Result<bool, ServiceError> {
+    let mut redis_conn = redis_pool
+        .get()
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Could not get redis connection".into()))?;
+
+    redis::cmd("EVAL")
+        .arg(TOUCH_SESSION_SCRIPT)
+        .arg(1)
+        .arg(oidc_session_key(session_id))
+        .arg(chrono::Utc::now().timestamp())
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Failed to get OIDC session".into()))
+}
+
+fn parse_browser_session(
+    session_id: &str,
+    fields: &HashMap<String, String>,
+) -> Option<BrowserSession> {
+    let timestamp = |name: &str| {
+        fields
+            .get(name)
+            .and_then(|timestamp| timestamp.parse::<i64>().ok())
+            .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+            .map(|timestamp| timestamp.naive_utc())
+    };
+
+    Some(BrowserSession {
+        id: session_id.parse().ok()?,
+        user_id: fields.get("user_id")?.parse().ok()?,
+        provider: fields.get("provider")?.clone(),
+        created_at: timestamp("created_at")?,
+        last_seen_at: timestamp("last_seen_at")?,
+        user_agent: fields.get("user_agent").cloned(),
+        ip: fields.get("ip").cloned(),
+    })
+}
+
+/// The cookie sessions of a user which were neither ended nor expired. Ended sessions are removed
+/// from the index of the user.
+#[tracing::instrument(skip(redis_pool))]
+pub async fn get_user_sessions_query(
+    user_id: uuid::Uuid,
+    redis_pool: web::Data<RedisPool>,
+) -> Result<Vec<BrowserSession>, ServiceError> {
+    let mut redis_conn = redis_pool
+        .get()
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Could not get redis connection".into()))?;
+
+    let user_key = user_sessions_key(user_id);
+    let session_ids: Vec<String> = redis::cmd("SMEMBERS")
+        .arg(&user_key)
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Failed to get sessions".into()))?;
+    if session_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut pipe = redis::pipe();
+    for session_id in session_ids.iter() {
+        pipe.hgetall(oidc_session_key(session_id));
+    }
+    let sessions: Vec<HashMap<String, String>> = pipe
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Failed to get sessions".into()))?;
+
+    let mut browser_sessions = vec![];
+    let mut ended_session_ids = vec![];
+    for (session_id, fields) in session_ids.iter().zip(sessions.iter()) {
+        match parse_browser_session(session_id, fields) {
+            Some(browser_session) if browser_session.user_id == user_id => {
+                browser_sessions.push(browser_session)
+            }
+            _ => ended_session_ids.push(session_id),
+        }
+    }
+
+    if !ended_session_ids.is_empty() {
+        let _: () = redis::cmd("SREM")
+            .arg(&user_key)
+            .arg(&ended_session_ids)
+            .query_async(&mut *redis_conn)
+            .await
+            .map_err(|_| ServiceError::InternalServerError("Failed to prune sessions".into()))?;
+    }
+
+    browser_sessions.sort_by_key(|session| std::cmp::Reverse(session.created_at));
+
+    Ok(browser_sessions)
+}
+
+/// Ends a cookie session of the user. Returns false if the user has no such session.
+#[tracing::instrument(skip(redis_pool))]
+pub async fn end_user_session_query(
+    user_id: uuid::Uuid,
+    session_id: &str,
+    redis_pool: web::Data<RedisPool>,
+) -> Result<bool, ServiceError> {
+    let mut redis_conn = redis_pool
+        .get()
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Could not get redis connection".into()))?;
+
+    // Removing the id from the index first makes sure users can only end their own sessions
+    let removed: usize = redis::cmd("SREM")
+        .arg(user_sessions_key(user_id))
+        .arg(session_id)
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Failed to end session".into()))?;
+    if removed == 0 {
+        return Ok(false);
+    }
+
+    let ended: usize = redis::cmd("DEL")
+        .arg(oidc_session_key(session_id))
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Failed to end session".into()))?;
+
+    Ok(ended > 0)
+}
+
+/// Ends every cookie session of the user. Returns the number of sessions which were ended.
+#[tracing::instrument(skip(redis_pool))]
+pub async fn end_user_sessions_query(
+    user_id: uuid::Uuid,
+    redis_pool: web::Data<RedisPool>,
+) -> Result<usize, ServiceError> {
+    let mut redis_conn = redis_pool
+        .get()
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Could not get redis connection".into()))?;
+
+    let user_key = user_sessions_key(user_id);
+    let session_ids: Vec<String> = redis::cmd("SMEMBERS")
+        .arg(&user_key)
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Failed to get sessions".into()))?;
+
+    let mut pipe = redis::pipe();
+    pipe.atomic();
+    for session_id in session_ids.iter() {
+        pipe.del(oidc_session_key(session_id));
+    }
+    pipe.del(&user_key).ignore();
+
+    let ended: Vec<usize> = pipe
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Failed to end sessions".into()))?;
+
+    Ok(ended.iter().sum())
+}
+
+/// Ends the sessions of the provider session `sid`, or every session of the subject `sub` if no
+/// `sid` is given. Returns the number of sessions which were ended.
+#[tracing::instrument(skip(redis_pool))]
+pub async fn end_oidc_sessions_query(
+    provider: String,
+    sid: Option<String>,
+    sub: Option<String>,
+    redis_pool: web::Data<RedisPool>,
+) -> Result<usize, ServiceError> {
+    let index_key = match (sid, sub) {
+        (Some(sid), _) => oidc_sessions_by_sid_key(&provider, &sid),
+        (None, Some(sub)) => oidc_sessions_by_sub_key(&provider, &sub),
+        (None, None) => return Ok(0),
+    };
+
+    let mut redis_conn = redis_pool
+        .get()
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Could not get redis connection".into()))?;
+
+    let session_ids: Vec<String> = redis::cmd("SMEMBERS")
+        .arg(&index_key)
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Failed to get OIDC sessions".into()))?;
+
+    let mut pipe = redis::pipe();
+    pipe.atomic();
+    for session_id in session_ids.iter() {
+        pipe.del(oidc_session_key(session_id)).ignore();
+    }
+    pipe.del(&index_key).ignore();
+
+    let _: () = pipe
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Failed to end OIDC sessions".into()))?;
+
+    Ok(session_ids.len())
+}
+
+/// Remembers the `jti` of a logout token until it expires. Returns false if the token was
+/// already used.
+#[tracing::instrument(skip(redis_pool))]
+pub async fn record_logout_token_query(
+    provider: &str,
+    jti: &str,
+    ttl_seconds: u64,
+    redis_pool: web::Data<RedisPool>,
+) -> Result<bool, ServiceError> {
+    let mut redis_conn = redis_pool
+        .get()
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Could not get redis connection".into()))?;
+
+    let recorded: Option<String> = redis::cmd("SET")
+        .arg(format!(
+            "oidc_logout_token:{}:{}",
+            provider,
+            blake3::hash(jti.as_bytes())
+        ))
+        .arg(1)
+        .arg("NX")
+        .arg("EX")
+        .arg(ttl_seconds.max(1))
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Failed to record logout token".into()))?;
+
+    Ok(recorded.is_some())
+}
@@ -1,11 +1,16 @@
 use crate::{
-    data::models::{PgPool, User},
+    data::models::{ApiKey, PgPool, RedisPool, User, UserIdentity},
     errors::ServiceError,
-    operators::api_key_operator::hash_api_key,
+    operators::api_key_operator::{
+        get_api_key_hash_candidates, get_unpeppered_api_key_hashes, hash_api_key, parse_api_key,
+        rehash_api_key_query, ParsedApiKey,
+    },
 };
 use actix_web::web;
+use bb8_redis::redis;
 use diesel::prelude::*;
-use diesel_async::RunQueryDsl;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
 
 #[tracing::instrument(skip(pg_pool))]
 pub async fn get_user_by_id_query(
@@ -28,51 +33,500 @@ pub async fn get_user_by_id_query(
     Ok(user)
 }
 
-#[tracing::instrument(skip(pg_pool))]
-pub async fn create_user_query(
+fn user_cache_version_key(user_id: uuid::Uuid) -> String {
+    format!("user_cache_version:{}", user_id)
+}
+
+fn user_cache_key(user_id: uuid::Uuid, version: u64) -> String {
+    format!("user:{}:{}", user_id, version)
+}
+
+/// How long a user stays cached for cookie sessions. Set with `USER_CACHE_TTL_SECONDS`. Changes
+/// made through the server invalidate the cache, changes made directly in the database apply
+/// after at most this long.
+pub fn get_user_cache_ttl() -> u64 {
+    std::env::var("USER_CACHE_TTL_SECONDS")
+        .ok()
+        .and_then(|ttl| ttl.parse().ok())
+        .unwrap_or(300)
+}
+
+/// Reads the user from the cache entry of the current cache version, returning the version along
+/// with the user if there is one.
+async fn get_user_from_cache(
+    user_id: uuid::Uuid,
+    redis_pool: &RedisPool,
+) -> Result<(u64, Option<User>), ServiceError> {
+    let mut redis_conn = redis_pool
+        .get()
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Could not get redis connection".into()))?;
+
+    let version: Option<u64> = redis::cmd("GET")
+        .arg(user_cache_version_key(user_id))
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| {
+            ServiceError::InternalServerError("Failed to read user cache version".into())
+        })?;
+    let version = version.unwrap_or(0);
+
+    let cached_user: Option<String> = redis::cmd("GET")
+        .arg(user_cache_key(user_id, version))
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Failed to read cached user".into()))?;
+
+    Ok((
+        version,
+        cached_user.and_then(|user| serde_json::from_str::<User>(&user).ok()),
+    ))
+}
+
+async fn set_user_in_cache(
+    user: &User,
+    version: u64,
+    redis_pool: &RedisPool,
+) -> Result<(), ServiceError> {
+    let mut redis_conn = redis_pool
+        .get()
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Could not get redis connection".into()))?;
+
+    let user_json = serde_json::to_string(user).map_err(|_| {
+        ServiceError::InternalServerError("Failed to serialize user to JSON".into())
+    })?;
+    let _: () = redis::cmd("SET")
+        .arg(user_cache_key(user.id, version))
+        .arg(user_json)
+        .arg("EX")
+        .arg(get_user_cache_ttl())
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Failed to cache user".into()))?;
+
+    Ok(())
+}
+
+/// Loads the user with a cache in redis in front of postgres. Returns `None` if the user does not
+/// exist (anymore). Cache entries are keyed by a per user version which invalidating bumps, the
+/// version is read before postgres so that an entry written from a read that raced an
+/// invalidation lands under the old version and is never read. If redis is unavailable the user
+/// is loaded from postgres alone.
+#[tracing::instrument(skip(redis_pool, pg_pool))]
+pub async fn get_cached_user_query(
+    user_id: uuid::Uuid,
+    redis_pool: web::Data<RedisPool>,
+    pg_pool: web::Data<PgPool>,
+) -> Result<Option<User>, ServiceError> {
+    use crate::data::schema::users::dsl as users_columns;
+
+    let version = match get_user_from_cache(user_id, &redis_pool).await {
+        Ok((_, Some(user))) => return Ok(Some(user)),
+        Ok((version, None)) => Some(version),
+        Err(err) => {
+            log::warn!(
+                "Reading the user cache failed, loading the user from postgres: {}",
+                err
+            );
+            None
+        }
+    };
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    let user = users_columns::users
+        .filter(users_columns::id.eq(user_id))
+        .select(User::as_select())
+        .first::<User>(&mut conn)
+        .await
+        .optional()
+        .map_err(|_| {
+            ServiceError::BadRequest("Error loading user for get_cached_user_query".to_string())
+        })?;
+
+    if let (Some(user), Some(version)) = (&user, version) {
+        if let Err(err) = set_user_in_cache(user, version, &redis_pool).await {
+            log::warn!("Caching the user failed: {}", err);
+        }
+    }
+
+    Ok(user)
+}
+
+/// Invalidates the cached copy of the user by bumping its cache version. Must be called whenever
+/// the user is changed or deleted, after the change is committed.
+#[tracing::instrument(skip(redis_pool))]
+pub async fn invalidate_user_cache_query(
     user_id: uuid::Uuid,
+    redis_pool: web::Data<RedisPool>,
+) -> Result<(), ServiceError> {
+    let mut redis_conn = redis_pool
+        .get()
+        .await
+        .map_err(|_| ServiceError::InternalServerError("Could not get redis connection".into()))?;
+
+    let _: u64 = redis::cmd("INCR")
+        .arg(user_cache_version_key(user_id))
+        .query_async(&mut *redis_conn)
+        .await
+        .map_err(|_| {
+            ServiceError::InternalServerError("Failed to invalidate cached user".into())
+        })?;
+
+    Ok(())
+}
+
+/// Finds the user of the identity and syncs the email and name the provider reported if they
+/// changed since the last login, or creates the user and identity if the identity is new.
+/// Concurrent logins with the same identity are serialized so that only one of them creates the
+/// user.
+#[tracing::instrument(skip(pg_pool))]
+pub async fn upsert_user_for_identity_query(
+    provider: String,
+    subject: String,
     email: String,
     name: Option<String>,
+    email_verified: bool,
     pg_pool: web::Data<PgPool>,
 ) -> Result<User, ServiceError> {
+    use crate::data::schema::identities::dsl as identities_columns;
+    use crate::data::schema::users::dsl as users_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        async move {
+            diesel::sql_query("SELECT pg_advisory_xact_lock(hashtext($1))")
+                .bind::<diesel::sql_types::Text, _>(format!("identity:{}:{}", provider, subject))
+                .execute(conn)
+                .await?;
+
+            let existing = users_columns::users
+                .inner_join(identities_columns::identities)
+                .filter(identities_columns::provider.eq(&provider))
+                .filter(identities_columns::subject.eq(&subject))
+                .select((User::as_select(), UserIdentity::as_select()))
+                .first::<(User, UserIdentity)>(conn)
+                .await
+                .optional()?;
+
+            let Some((user, identity)) = existing else {
+                let email_taken = users_columns::users
+                    .filter(users_columns::email.eq(&email))
+                    .count()
+                    .get_result::<i64>(conn)
+                    .await?
+                    > 0;
+                if email_taken {
+                    return Ok(Err(ServiceError::BadRequest(
+                        "An account with this email already exists. Log in with the provider you used before and link this one to it.".to_string(),
+                    )));
+                }
+
+                let user = diesel::insert_into(users_columns::users)
+                    .values(&User::from_details(email.clone(), name.clone()))
+                    .get_result::<User>(conn)
+                    .await?;
+
+                diesel::insert_into(identities_columns::identities)
+                    .values(&UserIdentity::from_details(
+                        user.id,
+                        provider,
+                        subject,
+                        Some(email),
+                        name,
+                        email_verified,
+                    ))
+                    .execute(conn)
+                    .await?;
+
+                return Ok(Ok(user));
+            };
+
+            // Only values which changed at the provider are synced, so that identities of other
+            // providers linked to the same user do not overwrite each other on every login
+            let mut new_email = user.email.clone();
+            if identity.email.as_ref() != Some(&email) && email != user.email {
+                let email_taken = users_columns::users
+                    .filter(users_columns::email.eq(&email))
+                    .filter(users_columns::id.ne(user.id))
+                    .count()
+                    .get_result::<i64>(conn)
+                    .await?
+                    > 0;
+                if email_taken {
+                    log::warn!(
+                        "Not syncing the email of user {} from {}, it is used by another account",
+                        user.id,
+                        provider
+                    );
+                } else {
+                    new_email = email.clone();
+                }
+            }
+            let new_name = match identity.name != name && name.is_some() {
+                true => name.clone(),
+                false => user.name.clone(),
+            };
+
+            diesel::update(identities_columns::identities.filter(identities_columns::id.eq(identity.id)))
+                .set((
+                    identities_columns::email.eq(Some(&email)),
+                    identities_columns::name.eq(&name),
+                    identities_columns::email_verified.eq(email_verified),
+                    identities_columns::updated_at.eq(chrono::Utc::now().naive_local()),
+                ))
+                .execute(conn)
+                .await?;
+
+            if new_email == user.email && new_name == user.name {
+                return Ok(Ok(user));
+            }
+
+            let user = diesel::update(users_columns::users.filter(users_columns::id.eq(user.id)))
+                .set((
+                    users_columns::email.eq(new_email),
+                    users_columns::name.eq(new_name),
+                    users_columns::updated_at.eq(chrono::Utc::now().naive_local()),
+                ))
+                .get_result::<User>(conn)
+                .await?;
+
+            Ok(Ok(user))
+        }
+        .scope_boxed()
+    })
+    .await
+    .map_err(|_| {
+        ServiceError::BadRequest("Error creating user for upsert_user_for_identity_query".to_string())
+    })?
+}
+
+#[tracing::instrument(skip(pg_pool))]
+pub async fn get_user_by_identity_query(
+    provider: &str,
+    subject: &str,
+    pg_pool: web::Data<PgPool>,
+) -> Result<Option<User>, ServiceError> {
+    use crate::data::schema::identities::dsl as identities_columns;
     use crate::data::schema::users::dsl as users_columns;
 
     let mut conn = pg_pool.get().await.unwrap();
 
-    let user = User::from_details_with_id(user_id, email, name);
+    users_columns::users
+        .inner_join(identities_columns::identities)
+        .filter(identities_columns::provider.eq(provider))
+        .filter(identities_columns::subject.eq(subject))
+        .select(User::as_select())
+        .first::<User>(&mut conn)
+        .await
+        .optional()
+        .map_err(|_| {
+            ServiceError::BadRequest(
+                "Error loading user for get_user_by_identity_query".to_string(),
+            )
+        })
+}
+
+/// Links another provider identity to the user. Fails if the identity already belongs to a
+/// different user, an identity which is already linked to the user gets its email and name
+/// updated.
+#[tracing::instrument(skip(pg_pool))]
+pub async fn link_identity_query(
+    user_id: uuid::Uuid,
+    provider: String,
+    subject: String,
+    email: String,
+    name: Option<String>,
+    email_verified: bool,
+    pg_pool: web::Data<PgPool>,
+) -> Result<UserIdentity, ServiceError> {
+    use crate::data::schema::identities::dsl as identities_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
 
-    let user = diesel::insert_into(users_columns::users)
-        .values(&user)
-        .get_result::<User>(&mut conn)
+    let existing_identity = identities_columns::identities
+        .filter(identities_columns::provider.eq(&provider))
+        .filter(identities_columns::subject.eq(&subject))
+        .select(UserIdentity::as_select())
+        .first::<UserIdentity>(&mut conn)
         .await
+        .optional()
         .map_err(|_| {
-            ServiceError::BadRequest("Error creating user for create_user_query".to_string())
+            ServiceError::BadRequest("Error loading identity for link_identity_query".to_string())
         })?;
 
-    Ok(user)
+    match existing_identity {
+        Some(existing_identity) if existing_identity.user_id != user_id => {
+            Err(ServiceError::BadRequest(
+                "This identity is already linked to another account".to_string(),
+            ))
+        }
+        Some(existing_identity) => diesel::update(
+            identities_columns::identities.filter(identities_columns::id.eq(existing_identity.id)),
+        )
+        .set((
+            identities_columns::email.eq(Some(email)),
+            identities_columns::name.eq(name),
+            identities_columns::email_verified.eq(email_verified),
+            identities_columns::updated_at.eq(chrono::Utc::now().naive_local()),
+        ))
+        .get_result::<UserIdentity>(&mut conn)
+        .await
+        .map_err(|_| ServiceError::BadRequest("Error linking identity".to_string())),
+        None => diesel::insert_into(identities_columns::identities)
+            .values(&UserIdentity::from_details(
+                user_id,
+                provider,
+                subject,
+                Some(email),
+                name,
+                email_verified,
+            ))
+            .on_conflict_do_nothing()
+            .get_result::<UserIdentity>(&mut conn)
+            .await
+            .optional()
+            .map_err(|_| ServiceError::BadRequest("Error linking identity".to_string()))?
+            // Linked by someone else since it was looked up
+            .ok_or(ServiceError::BadRequest(
+                "This identity is already linked to another account".to_string(),
+            )),
+    }
+}
+
+#[tracing::instrument(skip(pg_pool))]
+pub async fn get_identities_for_user_query(
+    user_id: uuid::Uuid,
+    pg_pool: web::Data<PgPool>,
+) -> Result<Vec<UserIdentity>, ServiceError> {
+    use crate::data::schema::identities::dsl as identities_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    identities_columns::identities
+        .filter(identities_columns::user_id.eq(user_id))
+        .order(identities_columns::created_at.asc())
+        .select(UserIdentity::as_select())
+        .load::<UserIdentity>(&mut conn)
+        .await
+        .map_err(|_| ServiceError::BadRequest("Error loading identities".to_string()))
+}
+
+/// Unlinks an identity from the user. The last identity of a user can not be unlinked, the user
+/// would not be able to log in anymore.
+#[tracing::instrument(skip(pg_pool))]
+pub async fn delete_identity_query(
+    identity_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+    pg_pool: web::Data<PgPool>,
+) -> Result<(), ServiceError> {
+    use crate::data::schema::identities::dsl as identities_columns;
+
+    let mut conn = pg_pool.get().await.unwrap();
+
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        async move {
+            // Locks the identities of the user so that concurrent unlinks can not remove all of them
+            let identity_ids = identities_columns::identities
+                .filter(identities_columns::user_id.eq(user_id))
+                .select(identities_columns::id)
+                .for_update()
+                .load::<uuid::Uuid>(conn)
+                .await?;
+
+            if !identity_ids.contains(&identity_id) {
+                return Ok(Err(ServiceError::NotFound));
+            }
+            if identity_ids.len() == 1 {
+                return Ok(Err(ServiceError::BadRequest(
+                    "The last identity of an account can not be unlinked".to_string(),
+                )));
+            }
+
+            diesel::delete(
+                identities_columns::identities.filter(identities_columns::id.eq(identity_id)),
+            )
+            .execute(conn)
+            .await?;
+
+            Ok(Ok(()))
+        }
+        .scope_boxed()
+    })
+    .await
+    .map_err(|_| ServiceError::BadRequest("Error deleting identity".to_string()))?
 }
 
 #[tracing::instrument(skip(pg_pool))]
 pub async fn get_user_from_api_key(
     api_key: &str,
     pg_pool: web::Data<PgPool>,
-) -> Result<User, ServiceError> {
+) -> Result<(User, ApiKey), ServiceError> {
     use crate::data::schema::api_keys::dsl as api_keys_columns;
     use crate::data::schema::users::dsl as users_columns;
 
-    let blake3_hash = hash_api_key(api_key);
+    let parsed_api_key = parse_api_key(api_key)?;
+    let blake3_hashes = get_api_key_hash_candidates(api_key);
+    let unpeppered_hashes = get_unpeppered_api_key_hashes(api_key);
+    let now = chrono::Utc::now().naive_utc();
 
     let mut conn = pg_pool.get().await.unwrap();
 
-    let user: User = users_columns::users
+    let mut query = users_columns::users
         .inner_join(api_keys_columns::api_keys)
-        .filter(api_keys_columns::blake3_hash.eq(blake3_hash))
-        .select(User::as_select())
-        .first::<User>(&mut conn)
+        .into_boxed();
+    if let ParsedApiKey::Structured { key_id } = parsed_api_key {
+        query = query.filter(api_keys_columns::key_id.eq(key_id));
+    }
+
+    let user_and_api_key: (User, ApiKey) = query
+        .filter(
+            api_keys_columns::blake3_hash
+                .eq_any(blake3_hashes.clone())
+                // Only rows which were never rehashed are looked up by the unkeyed hash
+                .or(api_keys_columns::blake3_hash
+                    .eq_any(unpeppered_hashes.clone())
+                    .and(api_keys_columns::hash_version.eq(0)))
+                .or(api_keys_columns::previous_blake3_hash
+                    .eq_any(blake3_hashes.iter().chain(&unpeppered_hashes).cloned())
+                    .and(api_keys_columns::previous_hash_expires_at.gt(now))),
+        )
+        .filter(
+            api_keys_columns::expires_at
+                .is_null()
+                .or(api_keys_columns::expires_at.gt(now)),
+        )
+        .filter(api_keys_columns::revoked_at.is_null())
+        .select((User::as_select(), ApiKey::as_select()))
+        .first::<(User, ApiKey)>(&mut conn)
         .await
         .map_err(|_| {
             ServiceError::BadRequest("Error loading user for get_user_from_api_key".to_string())
         })?;
 
-    Ok(user)
+    let (blake3_hash, hash_version) = hash_api_key(api_key);
+    let (_, matched_api_key) = &user_and_api_key;
+    // Keys authenticated with their previous secret are not rehashed, that hash expires anyway
+    if matched_api_key.hash_version != hash_version
+        && blake3_hashes
+            .iter()
+            .chain(&unpeppered_hashes)
+            .any(|hash| *hash == matched_api_key.blake3_hash)
+    {
+        if let Err(e) = rehash_api_key_query(
+            matched_api_key.id,
+            matched_api_key.blake3_hash.clone(),
+            blake3_hash,
+            hash_version,
+            pg_pool,
+        )
+        .await
+        {
+            log::error!("Failed to rehash api key {}: {:?}", matched_api_key.id, e);
+        }
+    }
+
+    Ok(user_and_api_key)
 }
@@ -1,34 +1,148 @@
 use actix_web_starter_client::{
-    apis::{api_key_api::CreateApiKeyParams, configuration::Configuration},
-    models::{CreateApiKeyReqPayload, CreateApiKeyRespPayload},
+    apis::{
+        api_key_api::{
+            CreateApiKeyParams, DeleteApiKeyParams, GetApiKeysParams, GetApiKeysSuccess,
+            GetCurrentApiKeySuccess, RotateApiKeyParams, RotateApiKeySuccess,
+        },
+        configuration::Configuration,
+    },
+    models::{ApiKeyRespBody, ApiKeyScope, CreateApiKeyReqPayload, RotateApiKeyReqPayload},
 };
+use tabled::{Table, Tabled};
 
-use super::configure::ActixTemplateConfiguration;
-use crate::ApiKeyData;
+use super::configure::{
+    ActixTemplateConfiguration, ActixTemplateProfile, ActixTemplateProfileInner,
+};
+use crate::{ApiKeyData, DeleteApiKey, ListApiKeys, RotateApiKey};
+
+#[derive(Tabled)]
+struct ApiKeyRow {
+    id: String,
+    key_id: String,
+    name: String,
+    scopes: String,
+    created_at: String,
+    expires_at: String,
+    last_used_at: String,
+    last_used_ip: String,
+    usage_count: i64,
+    allowed_cidrs: String,
+    revoked_at: String,
+}
+
+const ALL_SCOPES: [ApiKeyScope; 4] = [
+    ApiKeyScope::ApiKeyRead,
+    ApiKeyScope::ApiKeyWrite,
+    ApiKeyScope::UserRead,
+    ApiKeyScope::UserWrite,
+];
+
+impl From<ApiKeyRespBody> for ApiKeyRow {
+    fn from(api_key: ApiKeyRespBody) -> Self {
+        ApiKeyRow {
+            id: api_key.id.to_string(),
+            key_id: api_key.key_id.flatten().unwrap_or_default(),
+            name: api_key.name,
+            scopes: api_key
+                .scopes
+                .iter()
+                .map(|scope| scope.to_string())
+                .collect::<Vec<String>>()
+                .join(", "),
+            created_at: api_key.created_at,
+            expires_at: api_key
+                .expires_at
+                .flatten()
+                .unwrap_or_else(|| "never".to_string()),
+            last_used_at: api_key
+                .last_used_at
+                .flatten()
+                .unwrap_or_else(|| "never".to_string()),
+            last_used_ip: api_key.last_used_ip.flatten().unwrap_or_default(),
+            usage_count: api_key.usage_count,
+            allowed_cidrs: api_key.allowed_cidrs.join(", "),
+            revoked_at: api_key.revoked_at.flatten().unwrap_or_default(),
+        }
+    }
+}
+
+fn get_configuration(settings: &ActixTemplateConfiguration) -> Configuration {
+    Configuration {
+        base_path: settings.api_url.clone(),
+        api_key: Some(actix_web_starter_client::apis::configuration::ApiKey {
+            prefix: None,
+            key: settings.api_key.clone(),
+        }),
+        ..Default::default()
+    }
+}
+
+fn parse_duration(expires_in: &str) -> Result<chrono::Duration, String> {
+    let expires_in = expires_in.trim();
+    let (amount, unit) = expires_in.split_at(expires_in.len().saturating_sub(1));
+    let amount = amount
+        .parse::<i64>()
+        .map_err(|_| format!("Invalid duration '{}'", expires_in))?;
+
+    match unit {
+        "m" => Ok(chrono::Duration::minutes(amount)),
+        "h" => Ok(chrono::Duration::hours(amount)),
+        "d" => Ok(chrono::Duration::days(amount)),
+        "w" => Ok(chrono::Duration::weeks(amount)),
+        _ => Err(format!(
+            "Invalid duration unit in '{}'. Use m, h, d or w.",
+            expires_in
+        )),
+    }
+}
+
+fn parse_scope(scope: &str) -> Result<ApiKeyScope, String> {
+    ALL_SCOPES
+        .into_iter()
+        .find(|s| s.to_string() == scope.trim())
+        .ok_or_else(|| format!("Unknown scope '{}'", scope))
+}
 
 pub async fn generate_api_key(
     settings: ActixTemplateConfiguration,
     api_key_data: ApiKeyData,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let name = if api_key_data.name.is_none() {
-        inquire::Text::new("Enter a name for the API Key:")
+    let name = match api_key_data.name {
+        Some(name) => name,
+        None => inquire::Text::new("Enter a name for the API Key:")
             .with_help_message("This name will help you identify the API Key in the future.")
             .prompt()
-            .unwrap()
-    } else {
-        api_key_data.name.unwrap()
+            .unwrap(),
     };
 
-    let config = Configuration {
-        base_path: settings.api_url.clone(),
-        api_key: Some(actix_web_starter_client::apis::configuration::ApiKey {
-            prefix: None,
-            key: settings.api_key.clone(),
-        }),
-        ..Default::default()
+    let scopes = match api_key_data.scopes {
+        Some(scopes) => scopes
+            .iter()
+            .map(|scope| parse_scope(scope))
+            .collect::<Result<Vec<ApiKeyScope>, String>>()?,
+        None => inquire::MultiSelect::new(
+            "Select the scopes to grant the API Key:",
+            ALL_SCOPES
+                .iter()
+                .map(|scope| scope.to_string())
+                .collect::<Vec<String>>(),
+        )
+        .with_all_selected_by_default()
+        .prompt()
+        .unwrap()
+        .iter()
+        .map(|scope| parse_scope(scope))
+        .collect::<Result<Vec<ApiKeyScope>, String>>()?,
     };
 
-    let payload = CreateApiKeyReqPayload::new(name.clone());
+    let config = get_configuration(&settings);
+
+    let mut payload = CreateApiKeyReqPayload::new(name.clone(), scopes);
+    payload.allowed_cidrs = api_key_data.allowed_cidrs.map(Some);
+    if let Some(expires_in) = api_key_data.expires_in {
+        let expires_at = chrono::Utc::now().naive_utc() + parse_duration(&expires_in)?;
+        payload.expires_at = Some(Some(expires_at.format("%Y-%m-%dT%H:%M:%S").to_string()));
+    }
     let data = CreateApiKeyParams {
         create_api_key_req_payload: payload,
     };
@@ -44,10 +158,30 @@ pub async fn generate_api_key(
         .unwrap();
 
     match user {
-        actix_web_starter_client::apis::api_key_api::CreateApiKeySuccess::Status200(api_key) => {
+        actix_web_starter_client::apis::api_key_api::CreateApiKeySuccess::Status201(api_key) => {
             println!("\nAPI Key generated successfully!\n");
             println!("Name: {}", name);
             println!("API Key: {}", api_key.api_key);
+            println!(
+                "Scopes: {}",
+                api_key
+                    .scopes
+                    .iter()
+                    .map(|scope| scope.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            );
+            if !api_key.allowed_cidrs.is_empty() {
+                println!("Allowed CIDRs: {}", api_key.allowed_cidrs.join(", "));
+            }
+            println!(
+                "Expires At: {}",
+                api_key
+                    .expires_at
+                    .flatten()
+                    .map(|e| format!("{} UTC", e))
+                    .unwrap_or_else(|| "never".to_string())
+            );
         }
         actix_web_starter_client::apis::api_key_api::CreateApiKeySuccess::UnknownValue(_) => {
             eprintln!("Error generating API Key.");
@@ -57,3 +191,229 @@ pub async fn generate_api_key(
 
     Ok(())
 }
+
+pub async fn list_api_keys(
+    settings: ActixTemplateConfiguration,
+    list: ListApiKeys,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = get_configuration(&settings);
+
+    let data = GetApiKeysParams {
+        page: Some(list.page),
+        page_size: None,
+    };
+
+    let response = actix_web_starter_client::apis::api_key_api::get_api_keys(&config, data)
+        .await
+        .map_err(|e| {
+            eprintln!("Error listing API Keys: {:?}", e);
+            std::process::exit(1);
+        })
+        .unwrap()
+        .entity
+        .unwrap();
+
+    match response {
+        GetApiKeysSuccess::Status200(payload) => {
+            if payload.api_keys.is_empty() {
+                println!("No API Keys found.");
+                return Ok(());
+            }
+
+            let rows = payload
+                .api_keys
+                .into_iter()
+                .map(ApiKeyRow::from)
+                .collect::<Vec<ApiKeyRow>>();
+
+            println!("{}", Table::new(rows));
+            println!("Page {} of {}", list.page, payload.total_pages);
+        }
+        GetApiKeysSuccess::UnknownValue(_) => {
+            eprintln!("Error listing API Keys.");
+            std::process::exit(1);
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn delete_api_key(
+    settings: ActixTemplateConfiguration,
+    delete: DeleteApiKey,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = get_configuration(&settings);
+
+    let api_key_id = match delete.id {
+        Some(id) => id,
+        None => {
+            let response = actix_web_starter_client::apis::api_key_api::get_api_keys(
+                &config,
+                GetApiKeysParams {
+                    page: Some(1),
+                    page_size: Some(100),
+                },
+            )
+            .await
+            .map_err(|e| {
+                eprintln!("Error listing API Keys: {:?}", e);
+                std::process::exit(1);
+            })
+            .unwrap()
+            .entity
+            .unwrap();
+
+            let api_keys = match response {
+                GetApiKeysSuccess::Status200(payload) => payload.api_keys,
+                GetApiKeysSuccess::UnknownValue(_) => {
+                    eprintln!("Error listing API Keys.");
+                    std::process::exit(1);
+                }
+            };
+
+            if api_keys.is_empty() {
+                println!("No API Keys found.");
+                return Ok(());
+            }
+
+            let options = api_keys
+                .iter()
+                .map(|k| format!("{} ({})", k.name, k.id))
+                .collect::<Vec<String>>();
+
+            let selected = inquire::Select::new("Select an API Key to delete:", options.clone())
+                .prompt()
+                .unwrap();
+
+            let index = options.iter().position(|o| *o == selected).unwrap();
+            api_keys[index].id.to_string()
+        }
+    };
+
+    let confirm = inquire::Confirm::new(&format!(
+        "Are you sure you want to delete API Key {}?",
+        api_key_id
+    ))
+    .with_default(false)
+    .prompt()
+    .unwrap();
+
+    if !confirm {
+        return Ok(());
+    }
+
+    actix_web_starter_client::apis::api_key_api::delete_api_key(
+        &config,
+        DeleteApiKeyParams {
+            api_key_id: api_key_id.clone(),
+        },
+    )
+    .await
+    .map_err(|e| {
+        eprintln!("Error deleting API Key: {:?}", e);
+        std::process::exit(1);
+    })
+    .unwrap();
+
+    println!("Deleted API Key '{}'.", api_key_id);
+
+    Ok(())
+}
+
+pub async fn rotate_api_key(
+    settings: ActixTemplateConfiguration,
+    rotate: RotateApiKey,
+    profiles: Vec<ActixTemplateProfileInner>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = get_configuration(&settings);
+
+    let current_api_key_id =
+        match actix_web_starter_client::apis::api_key_api::get_current_api_key(&config)
+            .await
+            .ok()
+            .and_then(|response| response.entity)
+        {
+            Some(GetCurrentApiKeySuccess::Status200(api_key)) => Some(api_key.id.to_string()),
+            _ => None,
+        };
+
+    let api_key_id = match rotate.id.or(current_api_key_id.clone()) {
+        Some(id) => id,
+        None => {
+            eprintln!("Could not determine the API Key of the current profile. Pass the id of the API Key to rotate.");
+            std::process::exit(1);
+        }
+    };
+
+    let mut payload = RotateApiKeyReqPayload::new();
+    if let Some(grace_period) = rotate.grace_period {
+        payload.grace_period_seconds = Some(Some(parse_duration(&grace_period)?.num_seconds()));
+    }
+
+    let response = actix_web_starter_client::apis::api_key_api::rotate_api_key(
+        &config,
+        RotateApiKeyParams {
+            api_key_id: api_key_id.clone(),
+            rotate_api_key_req_payload: payload,
+        },
+    )
+    .await
+    .map_err(|e| {
+        eprintln!("Error rotating API Key: {:?}", e);
+        std::process::exit(1);
+    })
+    .unwrap()
+    .entity
+    .unwrap();
+
+    let rotated = match response {
+        RotateApiKeySuccess::Status200(rotated) => rotated,
+        RotateApiKeySuccess::UnknownValue(_) => {
+            eprintln!("Error rotating API Key.");
+            std::process::exit(1);
+        }
+    };
+
+    println!("\nAPI Key rotated successfully!\n");
+    println!("API Key: {}", rotated.api_key);
+    println!(
+        "New secret expires at: {}",
+        rotated
+            .expires_at
+            .flatten()
+            .map(|e| format!("{} UTC", e))
+            .unwrap_or_else(|| "never".to_string())
+    );
+    println!(
+        "Previous secret expires at: {} UTC",
+        rotated.previous_key_expires_at
+    );
+
+    if current_api_key_id.as_deref() == Some(api_key_id.as_str()) {
+        let profiles = profiles
+            .into_iter()
+            .map(|mut p| {
+                if p.settings.api_key == settings.api_key && p.settings.api_url == settings.api_url
+                {
+                    p.settings.api_key = rotated.api_key.clone();
+                }
+                p
+            })
+            .collect::<Vec<ActixTemplateProfileInner>>();
+
+        confy::store(
+            "actix_template",
+            "profiles",
+            ActixTemplateProfile { inner: profiles },
+        )
+        .map_err(|e| {
+            eprintln!("Error saving configuration: {:?}", e);
+            std::process::exit(1);
+        })
+        .unwrap();
+
+        println!("\nUpdated the stored API Key of the current profile.");
+    }
+
+    Ok(())
+}
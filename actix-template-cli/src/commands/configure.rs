@@ -1,4 +1,10 @@
-use crate::{commands::login_server::server, Login};
+use crate::{
+    commands::{
+        device_login::device_login,
+        login_server::{url_encode, LoginServer},
+    },
+    Login,
+};
 use actix_web_starter_client::apis::{
     auth_api::{whoami, WhoamiSuccess},
     configuration::{ApiKey, Configuration},
@@ -6,12 +12,17 @@ use actix_web_starter_client::apis::{
 use inquire::{Confirm, Text};
 use serde::{Deserialize, Serialize};
 use std::ops::{Deref, DerefMut};
-use tokio::sync::mpsc;
+use std::time::Duration;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 pub struct ActixTemplateConfiguration {
+    /// The api key, or the access token of the CLI session if `refresh_token` is set.
     pub api_key: String,
     pub api_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_token_expires_at: Option<chrono::NaiveDateTime>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -63,6 +74,8 @@ impl Default for ActixTemplateConfiguration {
         ActixTemplateConfiguration {
             api_key: "".to_string(),
             api_url: "http://localhost:8090".to_string(),
+            refresh_token: None,
+            access_token_expires_at: None,
         }
     }
 }
@@ -91,21 +104,65 @@ pub async fn get_user(
         .unwrap()
 }
 
-async fn configure(api_url: String, mut api_key: Option<String>) -> ActixTemplateConfiguration {
-    if api_key.is_none() {
-        let (tx, mut rx) = mpsc::channel::<String>(100);
+async fn configure(
+    api_url: String,
+    mut api_key: Option<String>,
+    provider: Option<String>,
+    device: bool,
+    login_timeout: Duration,
+) -> ActixTemplateConfiguration {
+    let mut refresh_token = None;
+    let mut access_token_expires_at = None;
+    let hostname = hostname::get()
+        .ok()
+        .and_then(|hostname| hostname.into_string().ok())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    if api_key.is_none() && device {
+        let session = device_login(api_url.clone(), provider, hostname)
+            .await
+            .map_err(|e| {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            })
+            .unwrap();
 
-        let server = tokio::spawn(async move {
-            server(tx.clone()).await.map_err(|e| {
+        api_key = Some(session.access_token);
+        refresh_token = Some(session.refresh_token);
+        access_token_expires_at = session.access_token_expires_at;
+    } else if api_key.is_none() {
+        let server = LoginServer::bind()
+            .await
+            .map_err(|e| {
                 eprintln!("Error starting server: {:?}", e);
                 std::process::exit(1);
             })
-        });
+            .unwrap();
+        let port = server
+            .port()
+            .map_err(|e| {
+                eprintln!("Error starting server: {:?}", e);
+                std::process::exit(1);
+            })
+            .unwrap();
 
-        let auth_url = format!(
-            "{api_url}/api/auth?redirect_uri={api_url}/auth/cli%3Fhost={api_url}",
-            api_url = api_url
+        let redirect_uri = format!(
+            "{}/auth/cli?host={}&port={}&state={}&hostname={}&cli_version={}",
+            api_url,
+            url_encode(&api_url),
+            port,
+            server.state(),
+            url_encode(&hostname),
+            env!("CARGO_PKG_VERSION")
+        );
+        let mut auth_url = format!(
+            "{}/api/auth?redirect_uri={}",
+            api_url,
+            url_encode(&redirect_uri)
         );
+        if let Some(provider) = provider {
+            auth_url.push_str(&format!("&provider={}", url_encode(&provider)));
+        }
 
         let _ = Text::new("Press Enter to authenticate in browser: ")
             .prompt()
@@ -113,26 +170,34 @@ async fn configure(api_url: String, mut api_key: Option<String>) -> ActixTemplat
 
         if open::that(auth_url.clone()).is_err() {
             eprintln!("Error opening browser. Please visit the URL manually.");
-            println!(
-                "\nPlease go to the following URL to get a Trieve API Key: {}",
-                auth_url
-            );
+            println!("\nPlease go to the following URL to log in: {}", auth_url);
         }
 
-        api_key = Some(rx.recv().await.unwrap());
+        let session = server
+            .wait_for_session(login_timeout)
+            .await
+            .map_err(|e| {
+                eprintln!("Error logging in: {}", e);
+                std::process::exit(1);
+            })
+            .unwrap();
 
-        server.abort();
+        api_key = Some(session.access_token);
+        refresh_token = Some(session.refresh_token);
+        access_token_expires_at = session.access_token_expires_at;
     }
 
     let result = get_user(api_url.clone(), api_key.clone().unwrap()).await;
 
     match result {
         WhoamiSuccess::Status200(user) => {
-            // println!("\nWelcome, {}!", user.email.unwrap().unwrap());
+            println!("\nWelcome, {}!", user.email);
 
             ActixTemplateConfiguration {
                 api_key: api_key.unwrap(),
                 api_url: api_url.clone(),
+                refresh_token,
+                access_token_expires_at,
             }
         }
         _ => {
@@ -142,11 +207,6 @@ async fn configure(api_url: String, mut api_key: Option<String>) -> ActixTemplat
     }
 }
 
-#[derive(Serialize, Deserialize, Clone)]
-struct ApiKeyResponse {
-    api_key: String,
-}
-
 pub async fn login(init: Login, settings: ActixTemplateConfiguration) {
     let api_key = init.api_key;
     let mut api_url = init.api_url;
@@ -176,9 +236,18 @@ pub async fn login(init: Login, settings: ActixTemplateConfiguration) {
         }
     }
 
-    let config = configure(api_url.unwrap().clone(), api_key).await;
+    let config = configure(
+        api_url.unwrap().clone(),
+        api_key,
+        init.provider,
+        init.device,
+        Duration::from_secs(init.login_timeout),
+    )
+    .await;
 
-    let profile_name = if init.profile_name.is_none() {
+    let profile_name = if let Some(profile_name) = init.profile_name {
+        profile_name
+    } else {
         let profile_name = Text::new("Enter a name for this profile:")
             .with_default("default")
             .prompt()
@@ -188,8 +257,6 @@ pub async fn login(init: Login, settings: ActixTemplateConfiguration) {
             profile_name
         );
         profile_name
-    } else {
-        init.profile_name.unwrap()
     };
 
     let mut profiles: ActixTemplateProfile = confy::load("actix_template", "profiles")
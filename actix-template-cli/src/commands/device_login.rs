@@ -0,0 +1,113 @@
+use super::login_server::LoginCallback;
+use actix_web_starter_client::apis::{
+    auth_api::{
+        create_device_code, device_token, CreateDeviceCodeParams, CreateDeviceCodeSuccess,
+        DeviceTokenError, DeviceTokenParams, DeviceTokenSuccess,
+    },
+    configuration::Configuration,
+    Error, ResponseContent,
+};
+use std::time::{Duration, Instant};
+
+const CLIENT_ID: &str = "actix-template-cli";
+const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+const DEVICE_SCOPES: &str = "api_key:read api_key:write user:read";
+
+/// Logs in through the OAuth device authorization flow, which only needs a browser on any
+/// device instead of one on this machine. Returns the tokens of the CLI session the server created
+/// once the login was approved.
+pub async fn device_login(
+    api_url: String,
+    provider: Option<String>,
+    hostname: String,
+) -> Result<LoginCallback, String> {
+    let configuration = Configuration {
+        base_path: api_url,
+        ..Default::default()
+    };
+
+    let device_code = match create_device_code(
+        &configuration,
+        CreateDeviceCodeParams {
+            client_id: CLIENT_ID.to_string(),
+            scope: Some(DEVICE_SCOPES.to_string()),
+            hostname: Some(hostname),
+            cli_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        },
+    )
+    .await
+    .map_err(|e| format!("Error starting device login: {:?}", e))?
+    .entity
+    {
+        Some(CreateDeviceCodeSuccess::Status200(device_code)) => device_code,
+        response => return Err(format!("Unexpected response: {:?}", response)),
+    };
+
+    let mut verification_uri_complete = url::Url::parse(&device_code.verification_uri_complete)
+        .map_err(|e| format!("Invalid verification URI: {}", e))?;
+    if let Some(provider) = provider {
+        verification_uri_complete
+            .query_pairs_mut()
+            .append_pair("provider", &provider);
+    }
+
+    println!(
+        "\nOpen {} on any device and enter the code\n\n    {}\n\nor open {}\n",
+        device_code.verification_uri, device_code.user_code, verification_uri_complete
+    );
+    println!("Waiting for the login to be approved...");
+
+    let deadline = Instant::now() + Duration::from_secs(device_code.expires_in.max(0) as u64);
+    let mut interval = Duration::from_secs(device_code.interval.max(1) as u64);
+
+    loop {
+        tokio::time::sleep(interval).await;
+        if Instant::now() > deadline {
+            return Err("The login code expired, run login again".to_string());
+        }
+
+        let response = device_token(
+            &configuration,
+            DeviceTokenParams {
+                client_id: CLIENT_ID.to_string(),
+                device_code: device_code.device_code.clone(),
+                grant_type: DEVICE_CODE_GRANT_TYPE.to_string(),
+            },
+        )
+        .await;
+
+        match response {
+            Ok(response) => match response.entity {
+                Some(DeviceTokenSuccess::Status200(token)) => {
+                    return Ok(LoginCallback {
+                        access_token_expires_at: chrono::Duration::from_std(Duration::from_secs(
+                            token.expires_in.max(0) as u64,
+                        ))
+                        .ok()
+                        .and_then(|expires_in| {
+                            chrono::Utc::now()
+                                .naive_utc()
+                                .checked_add_signed(expires_in)
+                        }),
+                        access_token: token.access_token,
+                        refresh_token: token.refresh_token,
+                    })
+                }
+                response => return Err(format!("Unexpected response: {:?}", response)),
+            },
+            Err(Error::ResponseError(ResponseContent {
+                entity: Some(DeviceTokenError::Status400(error)),
+                ..
+            })) => match error.error.as_str() {
+                "authorization_pending" => continue,
+                "slow_down" => interval += Duration::from_secs(5),
+                "access_denied" => return Err("The login was denied".to_string()),
+                "expired_token" => {
+                    return Err("The login code expired, run login again".to_string())
+                }
+                _ => return Err(error.error_description),
+            },
+            Err(e) => return Err(format!("Error polling device login: {:?}", e)),
+        }
+    }
+}
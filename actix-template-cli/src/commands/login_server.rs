@@ -1,61 +1,359 @@
-use tokio::io::{self, AsyncReadExt, AsyncWriteExt};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::io::{self, AsyncRead, AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::mpsc;
 
-fn extract_api_key(request: &str) -> Option<String> {
-    let request_line = request.lines().next()?;
-    let api_key_prefix = "apiKey=";
+/// Requests larger than this are not login callbacks.
+const MAX_REQUEST_SIZE: usize = 16 * 1024;
+/// How long a single connection may take to send its request.
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
 
-    let url_part = request_line.split_whitespace().nth(1)?;
+const SUCCESS_PAGE: &str = "<!DOCTYPE html><html lang=\"en\"><head><meta charset=\"UTF-8\"><meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\"><title>Login Success</title><link href=\"https://fonts.googleapis.com/css2?family=Roboto:wght@400;500&display=swap\" rel=\"stylesheet\"><style>body {font-family: 'Roboto', sans-serif; text-align: center; margin-top: 50px;} img {max-width: 200px;} h1, p {margin: 20px 0;}</style></head><body><img src=\"https://cdn.trieve.ai/trieve-logo.png\" alt=\"Trieve Logo\"><h1>Login Succeeded</h1><p>Return to your terminal to continue setup.</p></body></html>";
 
-    if let Some(start_pos) = url_part.find(api_key_prefix) {
-        let value_start_pos = start_pos + api_key_prefix.len();
+const FAILURE_PAGE: &str = "<!DOCTYPE html><html lang=\"en\"><head><meta charset=\"UTF-8\"><title>Login Failed</title></head><body style=\"font-family: sans-serif; text-align: center; margin-top: 50px;\"><h1>Login Failed</h1><p>This login was not started from your terminal. Run login again.</p></body></html>";
 
-        let value_end_pos = url_part[value_start_pos..]
-            .find('&')
-            .map_or(url_part.len(), |pos| value_start_pos + pos);
-
-        let api_key_value = &url_part[value_start_pos..value_end_pos];
+/// Percent-encodes everything but the unreserved characters of RFC 3986.
+pub fn url_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
 
-        Some(api_key_value.to_owned())
-    } else {
-        None
+/// Decodes a form-urlencoded value. None if it is not valid UTF-8 or has malformed escapes.
+fn url_decode(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut chars = value.bytes();
+    while let Some(byte) = chars.next() {
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                // `from_str_radix` alone would also accept a sign, e.g. `%+1`
+                let hex = [chars.next()?, chars.next()?];
+                if !hex.iter().all(u8::is_ascii_hexdigit) {
+                    return None;
+                }
+                bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            }
+            _ => bytes.push(byte),
+        }
     }
+
+    String::from_utf8(bytes).ok()
+}
+
+fn parse_form(body: &str) -> HashMap<String, String> {
+    body.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .filter_map(|(name, value)| Some((url_decode(name)?, url_decode(value)?)))
+        .collect()
+}
+
+struct CallbackRequest {
+    method: String,
+    path: String,
+    body: String,
 }
 
-async fn handle_read(stream: &mut TcpStream, tx: mpsc::Sender<String>) {
-    let mut buf = [0u8; 4096];
-    match stream.read(&mut buf).await {
-        Ok(_) => {
-            let req_str = String::from_utf8_lossy(&buf);
-            let _ = tx.send(extract_api_key(&req_str.as_ref()).unwrap()).await;
+fn find_header_end(buf: &[u8]) -> Option<usize> {
+    buf.windows(4).position(|window| window == b"\r\n\r\n")
+}
+
+async fn read_request<S: AsyncRead + Unpin>(stream: &mut S) -> io::Result<CallbackRequest> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        if let Some(header_end) = find_header_end(&buf) {
+            break header_end;
+        }
+        if buf.len() > MAX_REQUEST_SIZE {
+            return Err(invalid("Request headers are too large"));
         }
-        Err(e) => println!("Unable to read stream: {}", e),
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Err(invalid("Connection closed before the request was complete"));
+        }
+        buf.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = std::str::from_utf8(&buf[..header_end]).map_err(|_| invalid("Invalid request"))?;
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines
+        .next()
+        .ok_or_else(|| invalid("Missing request line"))?
+        .split_whitespace();
+    let method = request_line
+        .next()
+        .ok_or_else(|| invalid("Missing method"))?
+        .to_string();
+    let target = request_line
+        .next()
+        .ok_or_else(|| invalid("Missing request target"))?;
+    let path = target.split('?').next().unwrap_or_default().to_string();
+
+    let content_length = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .map(|(_, value)| value.trim().parse::<usize>())
+        .transpose()
+        .map_err(|_| invalid("Invalid Content-Length"))?
+        .unwrap_or(0);
+    if content_length > MAX_REQUEST_SIZE {
+        return Err(invalid("Request body is too large"));
     }
+
+    let body_start = header_end + 4;
+    while buf.len() < body_start + content_length {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Err(invalid("Connection closed before the request was complete"));
+        }
+        buf.extend_from_slice(&chunk[..read]);
+    }
+
+    let body = String::from_utf8(buf[body_start..body_start + content_length].to_vec())
+        .map_err(|_| invalid("Request body is not UTF-8"))?;
+
+    Ok(CallbackRequest { method, path, body })
 }
 
-async fn handle_write(mut stream: TcpStream) {
-    let response = b"HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=UTF-8\r\n\r\n<!DOCTYPE html><html lang=\"en\"><head><meta charset=\"UTF-8\"><meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\"><title>Login Success</title><link href=\"https://fonts.googleapis.com/css2?family=Roboto:wght@400;500&display=swap\" rel=\"stylesheet\"><style>body {font-family: 'Roboto', sans-serif; text-align: center; margin-top: 50px;} img {max-width: 200px;} h1, p {margin: 20px 0;}</style></head><body><img src=\"https://cdn.trieve.ai/trieve-logo.png\" alt=\"Trieve Logo\"><h1>Login Succeeded</h1><p>Return to your terminal to continue setup.</p></body></html>\r\n";
-    match stream.write(response).await {
-        Ok(_) => (),
-        Err(e) => println!("Failed sending response: {}", e),
+async fn respond(stream: &mut TcpStream, status: &str, page: &str) {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=UTF-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        page.len(),
+        page
+    );
+    if let Err(e) = stream.write_all(response.as_bytes()).await {
+        eprintln!("Failed sending response: {}", e);
     }
+    let _ = stream.shutdown().await;
+}
+
+/// The tokens of the CLI session a login created.
+pub struct LoginCallback {
+    pub access_token: String,
+    pub refresh_token: String,
+    /// None if the page sent an unparsable time, the token is refreshed on first use then.
+    pub access_token_expires_at: Option<chrono::NaiveDateTime>,
 }
 
-async fn handle_client(mut stream: TcpStream, tx: mpsc::Sender<String>) {
-    handle_read(&mut stream, tx).await;
-    handle_write(stream).await;
+/// Receives the CLI session from the browser after logging in. Listens on an ephemeral loopback port
+/// which is passed to the login page together with a random state, and only accepts a callback
+/// which returns that state.
+pub struct LoginServer {
+    listener: TcpListener,
+    state: String,
 }
 
-pub async fn server(tx: mpsc::Sender<String>) -> io::Result<()> {
-    let listener = TcpListener::bind("127.0.0.1:65535").await?;
+impl LoginServer {
+    pub async fn bind() -> io::Result<Self> {
+        Ok(LoginServer {
+            listener: TcpListener::bind("127.0.0.1:0").await?,
+            state: format!(
+                "{}{}",
+                uuid::Uuid::new_v4().simple(),
+                uuid::Uuid::new_v4().simple()
+            ),
+        })
+    }
+
+    pub fn port(&self) -> io::Result<u16> {
+        Ok(self.listener.local_addr()?.port())
+    }
+
+    pub fn state(&self) -> &str {
+        &self.state
+    }
+
+    /// Waits for the login page to post the session to `/callback`, giving up after `timeout`.
+    /// The server stops listening once this returns.
+    pub async fn wait_for_session(self, timeout: Duration) -> io::Result<LoginCallback> {
+        tokio::time::timeout(timeout, self.accept_callback())
+            .await
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "Timed out waiting for the login in the browser",
+                )
+            })?
+    }
+
+    async fn accept_callback(&self) -> io::Result<LoginCallback> {
+        loop {
+            let (mut stream, _) = self.listener.accept().await?;
+
+            let request =
+                match tokio::time::timeout(CONNECTION_TIMEOUT, read_request(&mut stream)).await {
+                    Ok(Ok(request)) => request,
+                    _ => {
+                        respond(&mut stream, "400 Bad Request", FAILURE_PAGE).await;
+                        continue;
+                    }
+                };
+
+            if request.path != "/callback" {
+                respond(&mut stream, "404 Not Found", FAILURE_PAGE).await;
+                continue;
+            }
+            if request.method != "POST" {
+                respond(&mut stream, "405 Method Not Allowed", FAILURE_PAGE).await;
+                continue;
+            }
+
+            let form = parse_form(&request.body);
+            if form.get("state") != Some(&self.state) {
+                respond(&mut stream, "400 Bad Request", FAILURE_PAGE).await;
+                continue;
+            }
+            let token = |name: &str| form.get(name).filter(|token| !token.is_empty()).cloned();
+            let (Some(access_token), Some(refresh_token)) =
+                (token("access_token"), token("refresh_token"))
+            else {
+                respond(&mut stream, "400 Bad Request", FAILURE_PAGE).await;
+                continue;
+            };
+
+            respond(&mut stream, "200 OK", SUCCESS_PAGE).await;
+            return Ok(LoginCallback {
+                access_token,
+                refresh_token,
+                access_token_expires_at: form
+                    .get("access_token_expires_at")
+                    .and_then(|expires_at| expires_at.parse().ok()),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn read(parts: &[&str]) -> io::Result<CallbackRequest> {
+        // Every part is returned by its own read, like a request split across TCP segments
+        let mut stream: Box<dyn AsyncRead + Unpin> = Box::new(io::empty());
+        for part in parts {
+            stream = Box::new(stream.chain(part.as_bytes()));
+        }
+        read_request(&mut stream).await
+    }
+
+    async fn post(port: u16, body: &str) -> String {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        let request = format!(
+            "POST /callback HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[test]
+    fn form_values_are_percent_decoded() {
+        let form = parse_form("state=a%2Bb+c&token=%E2%9C%93&empty=&novalue");
+        assert_eq!(form.get("state").map(String::as_str), Some("a+b c"));
+        assert_eq!(form.get("token").map(String::as_str), Some("\u{2713}"));
+        assert_eq!(form.get("empty").map(String::as_str), Some(""));
+        assert!(!form.contains_key("novalue"));
+
+        for malformed in ["%+1", "%-1", "%1", "%", "%zz", "%FF"] {
+            assert_eq!(url_decode(malformed), None, "{}", malformed);
+        }
+        assert_eq!(
+            url_decode(&url_encode("a b/+\u{2713}")).as_deref(),
+            Some("a b/+\u{2713}")
+        );
+    }
+
+    #[tokio::test]
+    async fn requests_split_across_reads_are_joined() {
+        let request = read(&[
+            "POST /callback?ignored HTTP/1.1\r\nContent-Le",
+            "ngth: 11\r\n\r",
+            "\nstate=",
+            "abc&",
+        ])
+        .await;
+        assert!(
+            request.is_err(),
+            "the body is shorter than its Content-Length"
+        );
+
+        let request = read(&[
+            "POST /callback?ignored HTTP/1.1\r\nContent-Le",
+            "ngth: 11\r\n\r",
+            "\nstate=",
+            "abc&x=1",
+        ])
+        .await
+        .unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/callback");
+        assert_eq!(request.body, "state=abc&x");
+    }
+
+    #[tokio::test]
+    async fn requests_are_bounded_in_size() {
+        let too_long = format!(
+            "POST /callback HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            MAX_REQUEST_SIZE + 1
+        );
+        assert!(read(&[&too_long]).await.is_err());
+
+        let at_limit = format!(
+            "POST /callback HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+            MAX_REQUEST_SIZE,
+            "a".repeat(MAX_REQUEST_SIZE)
+        );
+        assert_eq!(
+            read(&[&at_limit]).await.unwrap().body.len(),
+            MAX_REQUEST_SIZE
+        );
+
+        let endless_headers = format!("GET / HTTP/1.1\r\n{}", "a".repeat(MAX_REQUEST_SIZE + 1));
+        assert!(read(&[&endless_headers]).await.is_err());
+        assert!(read(&["POST / HTTP/1.1\r\nContent-Length: -1\r\n\r\n"])
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn only_callbacks_with_the_state_are_accepted() {
+        let server = LoginServer::bind().await.unwrap();
+        let port = server.port().unwrap();
+        let state = server.state().to_string();
+        let session = tokio::spawn(server.wait_for_session(Duration::from_secs(10)));
+
+        let response = post(port, "state=other&access_token=a&refresh_token=r").await;
+        assert!(response.starts_with("HTTP/1.1 400"), "{}", response);
+
+        let response = post(
+            port,
+            &format!(
+                "state={}&access_token=a%2Fb&refresh_token=r&access_token_expires_at=2026-10-18T20%3A00%3A00",
+                state
+            ),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
 
-    loop {
-        let (socket, _) = listener.accept().await?;
-        let tx = tx.clone();
-        tokio::spawn(async move {
-            // Process each socket concurrently.
-            handle_client(socket, tx).await
-        });
+        let callback = session.await.unwrap().unwrap();
+        assert_eq!(callback.access_token, "a/b");
+        assert_eq!(callback.refresh_token, "r");
+        assert_eq!(
+            callback.access_token_expires_at,
+            "2026-10-18T20:00:00".parse().ok()
+        );
     }
 }
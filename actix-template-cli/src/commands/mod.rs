@@ -1,4 +1,6 @@
 pub mod api_key;
 pub mod configure;
+pub mod device_login;
 pub mod login_server;
 pub mod profile;
+pub mod session;
@@ -82,7 +82,7 @@ pub fn delete_profile(
     let mut profiles = profiles
         .iter()
         .filter(|p| p.name != profile_name)
-        .map(|p| p.clone())
+        .cloned()
         .collect::<Vec<ActixTemplateProfileInner>>();
 
     if profile.selected {
@@ -0,0 +1,146 @@
+use actix_web_starter_client::{
+    apis::{
+        auth_api::{
+            refresh_cli_session, revoke_cli_session, RefreshCliSessionParams,
+            RefreshCliSessionSuccess, RevokeCliSessionParams,
+        },
+        configuration::Configuration,
+        Error, ResponseContent,
+    },
+    models::CliSessionRefreshTokenReqPayload,
+};
+
+use super::configure::{
+    ActixTemplateConfiguration, ActixTemplateProfile, ActixTemplateProfileInner,
+};
+
+/// Access tokens expiring sooner than this are refreshed before they are used.
+const REFRESH_MARGIN_SECONDS: i64 = 60;
+
+fn store_settings(
+    previous: &ActixTemplateConfiguration,
+    settings: ActixTemplateConfiguration,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut profiles: ActixTemplateProfile = confy::load("actix_template", "profiles")?;
+    profiles
+        .iter_mut()
+        .filter(|p| p.settings == *previous)
+        .for_each(|p| p.settings = settings.clone());
+
+    confy::store("actix_template", "profiles", profiles)?;
+
+    Ok(())
+}
+
+/// Refreshes the CLI session of the profile if its access token expires soon and stores the new
+/// tokens. Profiles configured with an API Key are returned as is.
+pub async fn refresh_session(settings: ActixTemplateConfiguration) -> ActixTemplateConfiguration {
+    let Some(refresh_token) = settings.refresh_token.clone() else {
+        return settings;
+    };
+    let refresh_at =
+        chrono::Utc::now().naive_utc() + chrono::Duration::seconds(REFRESH_MARGIN_SECONDS);
+    if settings
+        .access_token_expires_at
+        .is_some_and(|expires_at| expires_at > refresh_at)
+    {
+        return settings;
+    }
+
+    let configuration = Configuration {
+        base_path: settings.api_url.clone(),
+        ..Default::default()
+    };
+
+    let response = refresh_cli_session(
+        &configuration,
+        RefreshCliSessionParams {
+            cli_session_refresh_token_req_payload: CliSessionRefreshTokenReqPayload::new(
+                refresh_token,
+            ),
+        },
+    )
+    .await;
+
+    let tokens = match response.map(|response| response.entity) {
+        Ok(Some(RefreshCliSessionSuccess::Status200(tokens))) => tokens,
+        Err(Error::ResponseError(ResponseContent { status, .. })) if status.as_u16() == 401 => {
+            eprintln!("Your session expired or was revoked. Run login again.");
+            std::process::exit(1);
+        }
+        response => {
+            eprintln!("Error refreshing session: {:?}", response);
+            std::process::exit(1);
+        }
+    };
+
+    let refreshed = ActixTemplateConfiguration {
+        api_key: tokens.access_token,
+        api_url: settings.api_url.clone(),
+        refresh_token: Some(tokens.refresh_token),
+        access_token_expires_at: tokens.access_token_expires_at.parse().ok(),
+    };
+
+    // The previous refresh token stopped working, without the new one the profile is logged out
+    if let Err(e) = store_settings(&settings, refreshed.clone()) {
+        eprintln!("Error saving configuration: {:?}", e);
+        std::process::exit(1);
+    }
+
+    refreshed
+}
+
+/// Logs out the profile. The CLI session is revoked on the server, API Keys keep working until
+/// they expire or are deleted.
+pub async fn logout(
+    settings: ActixTemplateConfiguration,
+    profiles: Vec<ActixTemplateProfileInner>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if settings.api_key.is_empty() {
+        println!("Not logged in.");
+        return Ok(());
+    }
+
+    if let Some(refresh_token) = settings.refresh_token.clone() {
+        let configuration = Configuration {
+            base_path: settings.api_url.clone(),
+            ..Default::default()
+        };
+
+        revoke_cli_session(
+            &configuration,
+            RevokeCliSessionParams {
+                cli_session_refresh_token_req_payload: CliSessionRefreshTokenReqPayload::new(
+                    refresh_token,
+                ),
+            },
+        )
+        .await
+        .map_err(|e| format!("Error revoking session: {:?}", e))?;
+
+        println!("Logged out. The session was revoked.");
+    } else {
+        println!("Removed the API Key from the profile. It keeps working until it expires or is deleted with `api-key delete`.");
+    }
+
+    let profiles = profiles
+        .into_iter()
+        .map(|mut p| {
+            if p.settings == settings {
+                p.settings = ActixTemplateConfiguration {
+                    api_url: settings.api_url.clone(),
+                    ..Default::default()
+                };
+            }
+            p
+        })
+        .collect::<Vec<ActixTemplateProfileInner>>();
+
+    confy::store(
+        "actix_template",
+        "profiles",
+        ActixTemplateProfile { inner: profiles },
+    )?;
+
+    Ok(())
+}
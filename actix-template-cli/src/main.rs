@@ -25,6 +25,8 @@ struct Cli {
 enum Commands {
     /// Configures the Trieve CLI with your API key
     Login(Login),
+    /// Logs out the current profile, revoking its CLI session
+    Logout,
     #[command(subcommand, about = "Commands for managing API Keys")]
     ApiKey(ApiKeyCommands),
     /// Command to manage profiles
@@ -44,7 +46,12 @@ enum Profile {
 enum ApiKeyCommands {
     /// Generate a new API Key
     Generate(ApiKeyData),
-    //TODO: List API Keys, Delete API Key
+    /// List your API Keys
+    List(ListApiKeys),
+    /// Delete an API Key
+    Delete(DeleteApiKey),
+    /// Rotate the secret of an API Key
+    Rotate(RotateApiKey),
 }
 
 #[derive(Args)]
@@ -58,6 +65,16 @@ struct Login {
     /// Name the profile you are configuring
     #[arg(long, required = false)]
     profile_name: Option<String>,
+    /// The OpenID provider to log in with. Defaults to the server's default provider
+    #[arg(long, required = false)]
+    provider: Option<String>,
+    /// Log in by entering a code in a browser on any device instead of opening a browser on
+    /// this machine. Use this over SSH or in containers
+    #[arg(long, default_value_t = false)]
+    device: bool,
+    /// How many seconds to wait for the login in the browser to finish
+    #[arg(long, env = "ACTIX_TEMPLATE_LOGIN_TIMEOUT", default_value_t = 300)]
+    login_timeout: u64,
 }
 
 #[derive(Args)]
@@ -65,6 +82,37 @@ struct ApiKeyData {
     /// The name of the API Key
     #[arg(short, long)]
     name: Option<String>,
+    /// How long the API Key stays valid, e.g. 30d, 12h, 90m or 2w
+    #[arg(long)]
+    expires_in: Option<String>,
+    /// The scopes to grant the API Key, e.g. api_key:read,user:read
+    #[arg(long, value_delimiter = ',')]
+    scopes: Option<Vec<String>>,
+    /// Restrict the API Key to these CIDRs, e.g. 203.0.113.0/24,198.51.100.7
+    #[arg(long, value_delimiter = ',')]
+    allowed_cidrs: Option<Vec<String>>,
+}
+
+#[derive(Args)]
+struct ListApiKeys {
+    /// The page of API Keys to list
+    #[arg(long, default_value_t = 1)]
+    page: i64,
+}
+
+#[derive(Args)]
+struct DeleteApiKey {
+    /// The id of the API Key to delete
+    id: Option<String>,
+}
+
+#[derive(Args)]
+struct RotateApiKey {
+    /// The id of the API Key to rotate. Defaults to the API Key of the current profile
+    id: Option<String>,
+    /// How long the previous secret keeps working, e.g. 1h, 30m or 1d
+    #[arg(long)]
+    grace_period: Option<String>,
 }
 
 #[derive(Args)]
@@ -89,8 +137,7 @@ async fn main() {
         })
         .unwrap_or_default();
 
-    let settings = if args.profile.is_some() {
-        let profile_name = args.profile.unwrap();
+    let settings = if let Some(profile_name) = args.profile {
         let profile = profiles
             .inner
             .iter()
@@ -112,10 +159,24 @@ async fn main() {
             .settings
     };
 
+    let settings = match &args.command {
+        Some(Commands::ApiKey(_)) => commands::session::refresh_session(settings).await,
+        _ => settings,
+    };
+
     match args.command {
         Some(Commands::Login(login)) => {
             commands::configure::login(login, settings).await;
         }
+        Some(Commands::Logout) => {
+            commands::session::logout(settings, profiles.to_vec())
+                .await
+                .map_err(|e| {
+                    eprintln!("Error logging out: {:?}", e);
+                    std::process::exit(1);
+                })
+                .unwrap();
+        }
         Some(Commands::ApiKey(api_key)) => match api_key {
             ApiKeyCommands::Generate(api_key_data) => {
                 commands::api_key::generate_api_key(settings, api_key_data)
@@ -126,6 +187,33 @@ async fn main() {
                     })
                     .unwrap();
             }
+            ApiKeyCommands::List(list) => {
+                commands::api_key::list_api_keys(settings, list)
+                    .await
+                    .map_err(|e| {
+                        eprintln!("Error listing API Keys: {:?}", e);
+                        std::process::exit(1);
+                    })
+                    .unwrap();
+            }
+            ApiKeyCommands::Delete(delete) => {
+                commands::api_key::delete_api_key(settings, delete)
+                    .await
+                    .map_err(|e| {
+                        eprintln!("Error deleting API Key: {:?}", e);
+                        std::process::exit(1);
+                    })
+                    .unwrap();
+            }
+            ApiKeyCommands::Rotate(rotate) => {
+                commands::api_key::rotate_api_key(settings, rotate, profiles.to_vec())
+                    .await
+                    .map_err(|e| {
+                        eprintln!("Error rotating API Key: {:?}", e);
+                        std::process::exit(1);
+                    })
+                    .unwrap();
+            }
         },
         Some(Commands::Profile(profile)) => match profile {
             Profile::Switch(switch) => {
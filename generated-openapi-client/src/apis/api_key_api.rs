@@ -21,12 +21,103 @@ pub struct CreateApiKeyParams {
     pub create_api_key_req_payload: models::CreateApiKeyReqPayload
 }
 
+/// struct for passing parameters to the method [`delete_api_key`]
+#[derive(Clone, Debug)]
+pub struct DeleteApiKeyParams {
+    /// The id of the api key to delete
+    pub api_key_id: String
+}
+
+/// struct for passing parameters to the method [`get_api_key`]
+#[derive(Clone, Debug)]
+pub struct GetApiKeyParams {
+    /// The id of the api key to fetch
+    pub api_key_id: String
+}
+
+/// struct for passing parameters to the method [`get_api_keys`]
+#[derive(Clone, Debug)]
+pub struct GetApiKeysParams {
+    /// Page of api keys to fetch. Pages are 1-indexed and default to 1.
+    pub page: Option<i64>,
+    /// Number of api keys per page. Defaults to 10, max 100.
+    pub page_size: Option<i64>
+}
+
+/// struct for passing parameters to the method [`revoke_leaked_api_keys`]
+#[derive(Clone, Debug)]
+pub struct RevokeLeakedApiKeysParams {
+    /// Identifier of the public key the report was signed with
+    pub github_public_key_identifier: String,
+    /// Base64 encoded ECDSA signature of the SHA-256 of the request body
+    pub github_public_key_signature: String,
+    /// JSON array of the api keys which were found
+    pub leaked_api_key: Vec<models::LeakedApiKey>
+}
+
+/// struct for passing parameters to the method [`rotate_api_key`]
+#[derive(Clone, Debug)]
+pub struct RotateApiKeyParams {
+    /// The id of the api key to rotate
+    pub api_key_id: String,
+    /// JSON request payload to rotate a user api key
+    pub rotate_api_key_req_payload: models::RotateApiKeyReqPayload
+}
+
 
 /// struct for typed successes of method [`create_api_key`]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum CreateApiKeySuccess {
-    Status200(models::CreateApiKeyRespPayload),
+    Status201(models::CreateApiKeyRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`delete_api_key`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeleteApiKeySuccess {
+    Status204(),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`get_api_key`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetApiKeySuccess {
+    Status200(models::ApiKeyRespBody),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`get_api_keys`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetApiKeysSuccess {
+    Status200(models::GetApiKeysRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`get_current_api_key`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetCurrentApiKeySuccess {
+    Status200(models::ApiKeyRespBody),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`revoke_leaked_api_keys`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RevokeLeakedApiKeysSuccess {
+    Status200(models::RevokeLeakedApiKeysRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`rotate_api_key`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RotateApiKeySuccess {
+    Status200(models::RotateApiKeyRespPayload),
     UnknownValue(serde_json::Value),
 }
 
@@ -35,11 +126,73 @@ pub enum CreateApiKeySuccess {
 #[serde(untagged)]
 pub enum CreateApiKeyError {
     Status400(models::ErrorRespPayload),
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    Status500(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`delete_api_key`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeleteApiKeyError {
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    Status404(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`get_api_key`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetApiKeyError {
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    Status404(models::ErrorRespPayload),
     UnknownValue(serde_json::Value),
 }
 
+/// struct for typed errors of method [`get_api_keys`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetApiKeysError {
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`get_current_api_key`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetCurrentApiKeyError {
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    Status404(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
 
-/// Set User Api Key  Create a new api key for the auth'ed user. Successful response will contain the newly created api key. The api key will have permission level of the auth'ed user who calls this endpoint.
+/// struct for typed errors of method [`revoke_leaked_api_keys`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RevokeLeakedApiKeysError {
+    Status400(models::ErrorRespPayload),
+    Status401(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`rotate_api_key`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RotateApiKeyError {
+    Status400(models::ErrorRespPayload),
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    Status404(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+
+/// Set User Api Key  Create a new api key for the auth'ed user. Successful response will contain the newly created api key. The api key can only act on behalf of the auth'ed user within the scopes it was granted. Expired api keys and keys used from outside their allowed CIDRs are rejected by the server. Api keys restricted to CIDRs or with an expiry can only create keys restricted to CIDRs within theirs and expiring no later than them.
 pub async fn create_api_key(configuration: &configuration::Configuration, params: CreateApiKeyParams) -> Result<ResponseContent<CreateApiKeySuccess>, Error<CreateApiKeyError>> {
     let local_var_configuration = configuration;
 
@@ -55,6 +208,9 @@ pub async fn create_api_key(configuration: &configuration::Configuration, params
     if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
         local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
     }
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
     if let Some(ref local_var_apikey) = local_var_configuration.api_key {
         let local_var_key = local_var_apikey.key.clone();
         let local_var_value = match local_var_apikey.prefix {
@@ -82,3 +238,275 @@ pub async fn create_api_key(configuration: &configuration::Configuration, params
     }
 }
 
+/// Delete User Api Key  Revoke an api key belonging to the auth'ed user. The key will stop authenticating immediately.
+pub async fn delete_api_key(configuration: &configuration::Configuration, params: DeleteApiKeyParams) -> Result<ResponseContent<DeleteApiKeySuccess>, Error<DeleteApiKeyError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let api_key_id = params.api_key_id;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/api_key/{api_key_id}", local_var_configuration.base_path, api_key_id=crate::apis::urlencode(api_key_id));
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::DELETE, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<DeleteApiKeySuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<DeleteApiKeyError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Get User Api Key  Get a single api key belonging to the auth'ed user by its id. The secret value of the key is never returned.
+pub async fn get_api_key(configuration: &configuration::Configuration, params: GetApiKeyParams) -> Result<ResponseContent<GetApiKeySuccess>, Error<GetApiKeyError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let api_key_id = params.api_key_id;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/api_key/{api_key_id}", local_var_configuration.base_path, api_key_id=crate::apis::urlencode(api_key_id));
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::GET, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<GetApiKeySuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<GetApiKeyError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Get User Api Keys  Get a paginated list of the api keys belonging to the auth'ed user. The secret value of the keys is never returned.
+pub async fn get_api_keys(configuration: &configuration::Configuration, params: GetApiKeysParams) -> Result<ResponseContent<GetApiKeysSuccess>, Error<GetApiKeysError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let page = params.page;
+    let page_size = params.page_size;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/api_key", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::GET, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_str) = page {
+        local_var_req_builder = local_var_req_builder.query(&[("page", &local_var_str.to_string())]);
+    }
+    if let Some(ref local_var_str) = page_size {
+        local_var_req_builder = local_var_req_builder.query(&[("page_size", &local_var_str.to_string())]);
+    }
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<GetApiKeysSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<GetApiKeysError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Get Current Api Key  Get the api key used to authenticate this request. Requests authenticated with a cookie instead of an api key will receive a 404.
+pub async fn get_current_api_key(configuration: &configuration::Configuration) -> Result<ResponseContent<GetCurrentApiKeySuccess>, Error<GetCurrentApiKeyError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/api_key/current", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::GET, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<GetCurrentApiKeySuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<GetCurrentApiKeyError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Revoke Leaked Api Keys  Report api keys which were exposed publicly. Only accepts reports of secret scanning partners, signed like GitHub secret scanning alerts: the Github-Public-Key-Signature header holds an ECDSA signature of the body made with the key named in Github-Public-Key-Identifier, published at `LEAKED_API_KEY_REPORTER_KEYS_URL`. Every reported key that matches an active api key is revoked immediately and the report is recorded. The response is the same whether or not any key matched, so this endpoint cannot be used to check if a key is valid. At most 100 keys can be reported at once.
+pub async fn revoke_leaked_api_keys(configuration: &configuration::Configuration, params: RevokeLeakedApiKeysParams) -> Result<ResponseContent<RevokeLeakedApiKeysSuccess>, Error<RevokeLeakedApiKeysError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let github_public_key_identifier = params.github_public_key_identifier;
+    let github_public_key_signature = params.github_public_key_signature;
+    let leaked_api_key = params.leaked_api_key;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/api_key/revoke-leaked", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::POST, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    local_var_req_builder = local_var_req_builder.header("Github-Public-Key-Identifier", github_public_key_identifier.to_string());
+    local_var_req_builder = local_var_req_builder.header("Github-Public-Key-Signature", github_public_key_signature.to_string());
+    local_var_req_builder = local_var_req_builder.json(&leaked_api_key);
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<RevokeLeakedApiKeysSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<RevokeLeakedApiKeysError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Rotate User Api Key  Replace the secret of an api key belonging to the auth'ed user. The previous secret keeps authenticating until the end of the grace period so that running jobs can be updated without downtime. Rotating a key again ends the grace period of the secret before it. The credential used must have every scope of the rotated key, and api keys restricted to CIDRs or with an expiry can only rotate keys which are restricted as well.
+pub async fn rotate_api_key(configuration: &configuration::Configuration, params: RotateApiKeyParams) -> Result<ResponseContent<RotateApiKeySuccess>, Error<RotateApiKeyError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let api_key_id = params.api_key_id;
+    let rotate_api_key_req_payload = params.rotate_api_key_req_payload;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/api_key/{api_key_id}/rotate", local_var_configuration.base_path, api_key_id=crate::apis::urlencode(api_key_id));
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::POST, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+    local_var_req_builder = local_var_req_builder.json(&rotate_api_key_req_payload);
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<RotateApiKeySuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<RotateApiKeyError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
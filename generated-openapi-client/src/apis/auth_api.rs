@@ -14,29 +14,133 @@ use reqwest;
 use crate::{apis::ResponseContent, models};
 use super::{Error, configuration};
 
+/// struct for passing parameters to the method [`backchannel_logout`]
+#[derive(Clone, Debug)]
+pub struct BackchannelLogoutParams {
+    /// Name of the OpenID provider sending the logout token
+    pub provider: String,
+    /// A JWT signed by the OpenID provider identifying the session (`sid`) or user (`sub`) to log out.
+    pub logout_token: String
+}
+
 /// struct for passing parameters to the method [`callback`]
 #[derive(Clone, Debug)]
 pub struct CallbackParams {
+    /// Name of the OpenID provider the login was started with
+    pub provider: String,
     pub state: String,
-    pub session_state: String,
-    pub code: String
+    pub code: String,
+    pub session_state: Option<String>
+}
+
+/// struct for passing parameters to the method [`confirm_link`]
+#[derive(Clone, Debug)]
+pub struct ConfirmLinkParams {
+    /// JSON request payload to confirm or deny the identity link
+    pub confirm_link_req_payload: models::ConfirmLinkReqPayload
+}
+
+/// struct for passing parameters to the method [`create_cli_session`]
+#[derive(Clone, Debug)]
+pub struct CreateCliSessionParams {
+    /// JSON request payload describing the CLI which logs in
+    pub create_cli_session_req_payload: models::CreateCliSessionReqPayload
+}
+
+/// struct for passing parameters to the method [`create_device_code`]
+#[derive(Clone, Debug)]
+pub struct CreateDeviceCodeParams {
+    /// Name of the client logging in.
+    pub client_id: String,
+    /// Version of the client on the device. Defaults to `unknown`.
+    pub cli_version: Option<String>,
+    /// Hostname of the device, shown to tell sessions apart. Defaults to `unknown`.
+    pub hostname: Option<String>,
+    /// Space separated scopes of the CLI session the device gets. Defaults to every scope.
+    pub scope: Option<String>
+}
+
+/// struct for passing parameters to the method [`delete_identity`]
+#[derive(Clone, Debug)]
+pub struct DeleteIdentityParams {
+    /// The id of the identity to unlink
+    pub identity_id: String
+}
+
+/// struct for passing parameters to the method [`delete_session`]
+#[derive(Clone, Debug)]
+pub struct DeleteSessionParams {
+    /// The id of the session to revoke
+    pub session_id: String
+}
+
+/// struct for passing parameters to the method [`device_token`]
+#[derive(Clone, Debug)]
+pub struct DeviceTokenParams {
+    /// The client_id the device code was requested with.
+    pub client_id: String,
+    /// The device code returned by `/api/auth/device/code`.
+    pub device_code: String,
+    /// Must be `urn:ietf:params:oauth:grant-type:device_code`.
+    pub grant_type: String
+}
+
+/// struct for passing parameters to the method [`get_device_authorization`]
+#[derive(Clone, Debug)]
+pub struct GetDeviceAuthorizationParams {
+    /// The code shown by the device. Case, spaces and dashes are ignored.
+    pub user_code: String
 }
 
 /// struct for passing parameters to the method [`login`]
 #[derive(Clone, Debug)]
 pub struct LoginParams {
-    /// URL to redirect to after successful login
+    /// URL to redirect to after successful login. Must be a path on this server or allowed by `ALLOWED_REDIRECT_URIS`.
     pub redirect_uri: Option<String>,
     /// Code sent via email as a result of successful call to send_invitation
-    pub inv_code: Option<String>
+    pub inv_code: Option<String>,
+    /// Name of the OpenID provider to log in with, see `GET /api/auth/providers`. Defaults to the first configured provider.
+    pub provider: Option<String>,
+    /// Link the identity at `provider` to the account which is logged in with this browser instead of logging in with it. The link has to be confirmed at `/auth/link` after logging in at the provider.
+    pub link: Option<bool>
 }
 
 /// struct for passing parameters to the method [`logout`]
 #[derive(Clone, Debug)]
 pub struct LogoutParams {
+    /// URL to redirect to after logout. Must be a path on this server or allowed by `ALLOWED_REDIRECT_URIS`.
     pub redirect_uri: Option<String>
 }
 
+/// struct for passing parameters to the method [`refresh_cli_session`]
+#[derive(Clone, Debug)]
+pub struct RefreshCliSessionParams {
+    /// JSON request payload with the refresh token
+    pub cli_session_refresh_token_req_payload: models::CliSessionRefreshTokenReqPayload
+}
+
+/// struct for passing parameters to the method [`revoke_cli_session`]
+#[derive(Clone, Debug)]
+pub struct RevokeCliSessionParams {
+    /// JSON request payload with the refresh token
+    pub cli_session_refresh_token_req_payload: models::CliSessionRefreshTokenReqPayload
+}
+
+/// struct for passing parameters to the method [`verify_device_code`]
+#[derive(Clone, Debug)]
+pub struct VerifyDeviceCodeParams {
+    /// JSON request payload with the user code and the decision
+    pub verify_device_code_req_payload: models::VerifyDeviceCodeReqPayload
+}
+
+
+/// struct for typed successes of method [`backchannel_logout`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BackchannelLogoutSuccess {
+    Status200(),
+    UnknownValue(serde_json::Value),
+}
 
 /// struct for typed successes of method [`callback`]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,6 +150,102 @@ pub enum CallbackSuccess {
     UnknownValue(serde_json::Value),
 }
 
+/// struct for typed successes of method [`confirm_link`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ConfirmLinkSuccess {
+    Status200(models::ConfirmLinkRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`create_cli_session`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CreateCliSessionSuccess {
+    Status201(models::CliSessionTokensRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`create_device_code`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CreateDeviceCodeSuccess {
+    Status200(models::DeviceCodeRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`delete_identity`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeleteIdentitySuccess {
+    Status204(),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`delete_session`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeleteSessionSuccess {
+    Status204(),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`delete_sessions`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeleteSessionsSuccess {
+    Status200(models::RevokeSessionsRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`device_token`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeviceTokenSuccess {
+    Status200(models::DeviceTokenRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`get_device_authorization`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetDeviceAuthorizationSuccess {
+    Status200(models::DeviceAuthorizationRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`get_identities`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetIdentitiesSuccess {
+    Status200(Vec<models::UserIdentity>),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`get_oidc_providers`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetOidcProvidersSuccess {
+    Status200(models::GetOidcProvidersRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`get_pending_link`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetPendingLinkSuccess {
+    Status200(models::PendingLinkRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`get_sessions`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetSessionsSuccess {
+    Status200(models::GetSessionsRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
 /// struct for typed successes of method [`login`]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -58,6 +258,30 @@ pub enum LoginSuccess {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum LogoutSuccess {
+    Status200(models::LogoutRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`refresh_cli_session`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RefreshCliSessionSuccess {
+    Status200(models::CliSessionTokensRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`revoke_cli_session`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RevokeCliSessionSuccess {
+    Status204(),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`verify_device_code`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum VerifyDeviceCodeSuccess {
     Status204(),
     UnknownValue(serde_json::Value),
 }
@@ -70,11 +294,134 @@ pub enum WhoamiSuccess {
     UnknownValue(serde_json::Value),
 }
 
+/// struct for typed errors of method [`backchannel_logout`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BackchannelLogoutError {
+    Status400(models::ErrorRespPayload),
+    Status503(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
 /// struct for typed errors of method [`callback`]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum CallbackError {
     Status400(models::ErrorRespPayload),
+    Status401(models::ErrorRespPayload),
+    Status503(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`confirm_link`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ConfirmLinkError {
+    Status400(models::ErrorRespPayload),
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    Status404(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`create_cli_session`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CreateCliSessionError {
+    Status400(models::ErrorRespPayload),
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`create_device_code`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CreateDeviceCodeError {
+    Status400(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`delete_identity`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeleteIdentityError {
+    Status400(models::ErrorRespPayload),
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    Status404(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`delete_session`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeleteSessionError {
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    Status404(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`delete_sessions`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeleteSessionsError {
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`device_token`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeviceTokenError {
+    Status400(models::DeviceTokenErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`get_device_authorization`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetDeviceAuthorizationError {
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    Status404(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`get_identities`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetIdentitiesError {
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`get_oidc_providers`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetOidcProvidersError {
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`get_pending_link`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetPendingLinkError {
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    Status404(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`get_sessions`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetSessionsError {
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
     UnknownValue(serde_json::Value),
 }
 
@@ -83,6 +430,9 @@ pub enum CallbackError {
 #[serde(untagged)]
 pub enum LoginError {
     Status400(models::ErrorRespPayload),
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    Status503(models::ErrorRespPayload),
     UnknownValue(serde_json::Value),
 }
 
@@ -90,6 +440,33 @@ pub enum LoginError {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum LogoutError {
+    Status400(models::ErrorRespPayload),
+    Status401(),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`refresh_cli_session`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RefreshCliSessionError {
+    Status401(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`revoke_cli_session`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RevokeCliSessionError {
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`verify_device_code`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum VerifyDeviceCodeError {
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    Status404(models::ErrorRespPayload),
     UnknownValue(serde_json::Value),
 }
 
@@ -98,31 +475,31 @@ pub enum LogoutError {
 #[serde(untagged)]
 pub enum WhoamiError {
     Status400(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
     UnknownValue(serde_json::Value),
 }
 
 
-/// OpenID Connect callback  This is the callback route for the OAuth provider, it should not be called directly. Redirects to browser with set-cookie header.
-pub async fn callback(configuration: &configuration::Configuration, params: CallbackParams) -> Result<ResponseContent<CallbackSuccess>, Error<CallbackError>> {
+/// OpenID Connect back-channel logout  Called by the OpenID provider when a session ends there, e.g. because an admin terminated it. Every session of this server which was created by logging in with that provider session, or with that user if the logout token has no `sid`, is invalidated. This should not be called directly.
+pub async fn backchannel_logout(configuration: &configuration::Configuration, params: BackchannelLogoutParams) -> Result<ResponseContent<BackchannelLogoutSuccess>, Error<BackchannelLogoutError>> {
     let local_var_configuration = configuration;
 
     // unbox the parameters
-    let state = params.state;
-    let session_state = params.session_state;
-    let code = params.code;
+    let provider = params.provider;
+    let logout_token = params.logout_token;
 
 
     let local_var_client = &local_var_configuration.client;
 
-    let local_var_uri_str = format!("{}/api/auth/callback", local_var_configuration.base_path);
-    let mut local_var_req_builder = local_var_client.request(reqwest::Method::GET, local_var_uri_str.as_str());
+    let local_var_uri_str = format!("{}/api/auth/backchannel-logout/{provider}", local_var_configuration.base_path, provider=crate::apis::urlencode(provider));
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::POST, local_var_uri_str.as_str());
 
-    local_var_req_builder = local_var_req_builder.query(&[("state", &state.to_string())]);
-    local_var_req_builder = local_var_req_builder.query(&[("session_state", &session_state.to_string())]);
-    local_var_req_builder = local_var_req_builder.query(&[("code", &code.to_string())]);
     if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
         local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
     }
+    let mut local_var_form_params = std::collections::HashMap::new();
+    local_var_form_params.insert("logout_token", logout_token.to_string());
+    local_var_req_builder = local_var_req_builder.form(&local_var_form_params);
 
     let local_var_req = local_var_req_builder.build()?;
     let local_var_resp = local_var_client.execute(local_var_req).await?;
@@ -131,35 +508,36 @@ pub async fn callback(configuration: &configuration::Configuration, params: Call
     let local_var_content = local_var_resp.text().await?;
 
     if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
-        let local_var_entity: Option<CallbackSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_entity: Option<BackchannelLogoutSuccess> = serde_json::from_str(&local_var_content).ok();
         let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
         Ok(local_var_result)
     } else {
-        let local_var_entity: Option<CallbackError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_entity: Option<BackchannelLogoutError> = serde_json::from_str(&local_var_content).ok();
         let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
         Err(Error::ResponseError(local_var_error))
     }
 }
 
-/// Login  This will redirect you to the OAuth provider for authentication with email/pass, SSO, Google, Github, etc.
-pub async fn login(configuration: &configuration::Configuration, params: LoginParams) -> Result<ResponseContent<LoginSuccess>, Error<LoginError>> {
+/// OpenID Connect callback  This is the callback route for the OAuth provider, it should not be called directly. Redirects to browser with set-cookie header.
+pub async fn callback(configuration: &configuration::Configuration, params: CallbackParams) -> Result<ResponseContent<CallbackSuccess>, Error<CallbackError>> {
     let local_var_configuration = configuration;
 
     // unbox the parameters
-    let redirect_uri = params.redirect_uri;
-    let inv_code = params.inv_code;
+    let provider = params.provider;
+    let state = params.state;
+    let code = params.code;
+    let session_state = params.session_state;
 
 
     let local_var_client = &local_var_configuration.client;
 
-    let local_var_uri_str = format!("{}/api/auth", local_var_configuration.base_path);
+    let local_var_uri_str = format!("{}/api/auth/callback/{provider}", local_var_configuration.base_path, provider=crate::apis::urlencode(provider));
     let mut local_var_req_builder = local_var_client.request(reqwest::Method::GET, local_var_uri_str.as_str());
 
-    if let Some(ref local_var_str) = redirect_uri {
-        local_var_req_builder = local_var_req_builder.query(&[("redirect_uri", &local_var_str.to_string())]);
-    }
-    if let Some(ref local_var_str) = inv_code {
-        local_var_req_builder = local_var_req_builder.query(&[("inv_code", &local_var_str.to_string())]);
+    local_var_req_builder = local_var_req_builder.query(&[("state", &state.to_string())]);
+    local_var_req_builder = local_var_req_builder.query(&[("code", &code.to_string())]);
+    if let Some(ref local_var_str) = session_state {
+        local_var_req_builder = local_var_req_builder.query(&[("session_state", &local_var_str.to_string())]);
     }
     if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
         local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
@@ -172,35 +550,33 @@ pub async fn login(configuration: &configuration::Configuration, params: LoginPa
     let local_var_content = local_var_resp.text().await?;
 
     if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
-        let local_var_entity: Option<LoginSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_entity: Option<CallbackSuccess> = serde_json::from_str(&local_var_content).ok();
         let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
         Ok(local_var_result)
     } else {
-        let local_var_entity: Option<LoginError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_entity: Option<CallbackError> = serde_json::from_str(&local_var_content).ok();
         let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
         Err(Error::ResponseError(local_var_error))
     }
 }
 
-/// Logout  Invalidate your current auth credential stored typically stored in a cookie. This does not invalidate your API key.
-pub async fn logout(configuration: &configuration::Configuration, params: LogoutParams) -> Result<ResponseContent<LogoutSuccess>, Error<LogoutError>> {
+/// Confirm Identity Link  Link the identity waiting in this browser session to the auth'ed user, or discard it. Only available to the browser session which started the link.
+pub async fn confirm_link(configuration: &configuration::Configuration, params: ConfirmLinkParams) -> Result<ResponseContent<ConfirmLinkSuccess>, Error<ConfirmLinkError>> {
     let local_var_configuration = configuration;
 
     // unbox the parameters
-    let redirect_uri = params.redirect_uri;
+    let confirm_link_req_payload = params.confirm_link_req_payload;
 
 
     let local_var_client = &local_var_configuration.client;
 
-    let local_var_uri_str = format!("{}/api/auth", local_var_configuration.base_path);
-    let mut local_var_req_builder = local_var_client.request(reqwest::Method::DELETE, local_var_uri_str.as_str());
+    let local_var_uri_str = format!("{}/api/auth/link", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::POST, local_var_uri_str.as_str());
 
-    if let Some(ref local_var_str) = redirect_uri {
-        local_var_req_builder = local_var_req_builder.query(&[("redirect_uri", &local_var_str.to_string())]);
-    }
     if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
         local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
     }
+    local_var_req_builder = local_var_req_builder.json(&confirm_link_req_payload);
 
     let local_var_req = local_var_req_builder.build()?;
     let local_var_resp = local_var_client.execute(local_var_req).await?;
@@ -209,31 +585,706 @@ pub async fn logout(configuration: &configuration::Configuration, params: Logout
     let local_var_content = local_var_resp.text().await?;
 
     if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
-        let local_var_entity: Option<LogoutSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_entity: Option<ConfirmLinkSuccess> = serde_json::from_str(&local_var_content).ok();
         let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
         Ok(local_var_result)
     } else {
-        let local_var_entity: Option<LogoutError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_entity: Option<ConfirmLinkError> = serde_json::from_str(&local_var_content).ok();
         let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
         Err(Error::ResponseError(local_var_error))
     }
 }
 
-/// Get Currently Auth'ed User  Get the currently auth'ed user. This will return the user object for the currently auth'ed user.
-pub async fn whoami(configuration: &configuration::Configuration) -> Result<ResponseContent<WhoamiSuccess>, Error<WhoamiError>> {
+/// Create CLI Session  Log in the CLI with the auth'ed user. Instead of a long-lived api key the CLI gets a short-lived access token and a refresh token to renew it, which stop working when the CLI logs out. Called by the page at `/auth/cli`.
+pub async fn create_cli_session(configuration: &configuration::Configuration, params: CreateCliSessionParams) -> Result<ResponseContent<CreateCliSessionSuccess>, Error<CreateCliSessionError>> {
     let local_var_configuration = configuration;
 
     // unbox the parameters
+    let create_cli_session_req_payload = params.create_cli_session_req_payload;
 
 
     let local_var_client = &local_var_configuration.client;
 
-    let local_var_uri_str = format!("{}/api/auth/whoami", local_var_configuration.base_path);
-    let mut local_var_req_builder = local_var_client.request(reqwest::Method::GET, local_var_uri_str.as_str());
+    let local_var_uri_str = format!("{}/api/auth/cli/session", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::POST, local_var_uri_str.as_str());
 
     if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
         local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
     }
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+    local_var_req_builder = local_var_req_builder.json(&create_cli_session_req_payload);
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<CreateCliSessionSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<CreateCliSessionError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Start Device Login  Starts the OAuth device authorization flow (RFC 8628) for clients which can not receive a browser redirect, e.g. a CLI over SSH. Show the user_code and verification_uri to the user and poll `/api/auth/device/token` with the device_code until the user approved the login.
+pub async fn create_device_code(configuration: &configuration::Configuration, params: CreateDeviceCodeParams) -> Result<ResponseContent<CreateDeviceCodeSuccess>, Error<CreateDeviceCodeError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let client_id = params.client_id;
+    let cli_version = params.cli_version;
+    let hostname = params.hostname;
+    let scope = params.scope;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/auth/device/code", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::POST, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    let mut local_var_form_params = std::collections::HashMap::new();
+    local_var_form_params.insert("client_id", client_id.to_string());
+    if let Some(local_var_param_value) = cli_version {
+        local_var_form_params.insert("cli_version", local_var_param_value.to_string());
+    }
+    if let Some(local_var_param_value) = hostname {
+        local_var_form_params.insert("hostname", local_var_param_value.to_string());
+    }
+    if let Some(local_var_param_value) = scope {
+        local_var_form_params.insert("scope", local_var_param_value.to_string());
+    }
+    local_var_req_builder = local_var_req_builder.form(&local_var_form_params);
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<CreateDeviceCodeSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<CreateDeviceCodeError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Unlink Identity  Unlink an OpenID provider identity from the auth'ed user. The last identity of an account can not be unlinked.
+pub async fn delete_identity(configuration: &configuration::Configuration, params: DeleteIdentityParams) -> Result<ResponseContent<DeleteIdentitySuccess>, Error<DeleteIdentityError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let identity_id = params.identity_id;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/auth/identities/{identity_id}", local_var_configuration.base_path, identity_id=crate::apis::urlencode(identity_id));
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::DELETE, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<DeleteIdentitySuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<DeleteIdentityError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Revoke Session  Log out one session of the auth'ed user. A browser session stops authenticating with its next request, a CLI session can no longer use or refresh its tokens.
+pub async fn delete_session(configuration: &configuration::Configuration, params: DeleteSessionParams) -> Result<ResponseContent<DeleteSessionSuccess>, Error<DeleteSessionError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let session_id = params.session_id;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/auth/sessions/{session_id}", local_var_configuration.base_path, session_id=crate::apis::urlencode(session_id));
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::DELETE, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<DeleteSessionSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<DeleteSessionError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Revoke All Sessions  Sign out everywhere: log out every browser and CLI session of the auth'ed user, including the one used for this request. Api keys keep working and have to be deleted separately.
+pub async fn delete_sessions(configuration: &configuration::Configuration) -> Result<ResponseContent<DeleteSessionsSuccess>, Error<DeleteSessionsError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/auth/sessions", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::DELETE, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<DeleteSessionsSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<DeleteSessionsError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Poll Device Login  Exchange the device code for the tokens of a CLI session once the user approved the login. Until then this fails with `authorization_pending`. Poll at most once per interval returned by `/api/auth/device/code`, polling faster fails with `slow_down`.
+pub async fn device_token(configuration: &configuration::Configuration, params: DeviceTokenParams) -> Result<ResponseContent<DeviceTokenSuccess>, Error<DeviceTokenError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let client_id = params.client_id;
+    let device_code = params.device_code;
+    let grant_type = params.grant_type;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/auth/device/token", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::POST, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    let mut local_var_form_params = std::collections::HashMap::new();
+    local_var_form_params.insert("client_id", client_id.to_string());
+    local_var_form_params.insert("device_code", device_code.to_string());
+    local_var_form_params.insert("grant_type", grant_type.to_string());
+    local_var_req_builder = local_var_req_builder.form(&local_var_form_params);
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<DeviceTokenSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<DeviceTokenError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Get Device Login  Look up the device login waiting for the user code, so that the user can check which client asks for which scopes before approving it. Called by the page at `/auth/device`.
+pub async fn get_device_authorization(configuration: &configuration::Configuration, params: GetDeviceAuthorizationParams) -> Result<ResponseContent<GetDeviceAuthorizationSuccess>, Error<GetDeviceAuthorizationError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let user_code = params.user_code;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/auth/device/verify", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::GET, local_var_uri_str.as_str());
+
+    local_var_req_builder = local_var_req_builder.query(&[("user_code", &user_code.to_string())]);
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<GetDeviceAuthorizationSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<GetDeviceAuthorizationError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Get Identities  List the OpenID provider identities linked to the auth'ed user. Link another one by logging in with `link=true`.
+pub async fn get_identities(configuration: &configuration::Configuration) -> Result<ResponseContent<GetIdentitiesSuccess>, Error<GetIdentitiesError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/auth/identities", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::GET, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<GetIdentitiesSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<GetIdentitiesError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Get OpenID Providers  List the OpenID providers which can be passed as `provider` to log in.
+pub async fn get_oidc_providers(configuration: &configuration::Configuration) -> Result<ResponseContent<GetOidcProvidersSuccess>, Error<GetOidcProvidersError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/auth/providers", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::GET, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<GetOidcProvidersSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<GetOidcProvidersError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Get Pending Identity Link  Show the identity waiting to be linked to the auth'ed user after logging in with `link=true`, so that the user can check it before confirming. Only available to the browser session which started the link.
+pub async fn get_pending_link(configuration: &configuration::Configuration) -> Result<ResponseContent<GetPendingLinkSuccess>, Error<GetPendingLinkError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/auth/link", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::GET, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<GetPendingLinkSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<GetPendingLinkError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Get Sessions  List the active sessions of the auth'ed user: every browser logged in with the session cookie and every logged in CLI. Api keys are listed at `/api/api_key`.
+pub async fn get_sessions(configuration: &configuration::Configuration) -> Result<ResponseContent<GetSessionsSuccess>, Error<GetSessionsError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/auth/sessions", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::GET, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<GetSessionsSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<GetSessionsError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Login  This will redirect you to the OAuth provider for authentication with email/pass, SSO, Google, Github, etc.
+pub async fn login(configuration: &configuration::Configuration, params: LoginParams) -> Result<ResponseContent<LoginSuccess>, Error<LoginError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let redirect_uri = params.redirect_uri;
+    let inv_code = params.inv_code;
+    let provider = params.provider;
+    let link = params.link;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/auth", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::GET, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_str) = redirect_uri {
+        local_var_req_builder = local_var_req_builder.query(&[("redirect_uri", &local_var_str.to_string())]);
+    }
+    if let Some(ref local_var_str) = inv_code {
+        local_var_req_builder = local_var_req_builder.query(&[("inv_code", &local_var_str.to_string())]);
+    }
+    if let Some(ref local_var_str) = provider {
+        local_var_req_builder = local_var_req_builder.query(&[("provider", &local_var_str.to_string())]);
+    }
+    if let Some(ref local_var_str) = link {
+        local_var_req_builder = local_var_req_builder.query(&[("link", &local_var_str.to_string())]);
+    }
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<LoginSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<LoginError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Logout  Invalidate your current auth credential stored typically stored in a cookie. This does not invalidate your API key. The returned `logout_url` ends the session at the OpenID provider if it supports RP-initiated logout, otherwise it is the `redirect_uri`.
+pub async fn logout(configuration: &configuration::Configuration, params: LogoutParams) -> Result<ResponseContent<LogoutSuccess>, Error<LogoutError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let redirect_uri = params.redirect_uri;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/auth", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::DELETE, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_str) = redirect_uri {
+        local_var_req_builder = local_var_req_builder.query(&[("redirect_uri", &local_var_str.to_string())]);
+    }
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<LogoutSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<LogoutError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Refresh CLI Session  Exchange the refresh token of a CLI session for a new access token and refresh token. The session is extended and the previous refresh token stops working. Presenting a refresh token which was already exchanged revokes the session.
+pub async fn refresh_cli_session(configuration: &configuration::Configuration, params: RefreshCliSessionParams) -> Result<ResponseContent<RefreshCliSessionSuccess>, Error<RefreshCliSessionError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let cli_session_refresh_token_req_payload = params.cli_session_refresh_token_req_payload;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/auth/cli/session/refresh", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::POST, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    local_var_req_builder = local_var_req_builder.json(&cli_session_refresh_token_req_payload);
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<RefreshCliSessionSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<RefreshCliSessionError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Revoke CLI Session  Log out the CLI session of the refresh token. Its access token and refresh token stop working immediately. Unknown refresh tokens are ignored, so the response does not reveal whether the token was valid.
+pub async fn revoke_cli_session(configuration: &configuration::Configuration, params: RevokeCliSessionParams) -> Result<ResponseContent<RevokeCliSessionSuccess>, Error<RevokeCliSessionError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let cli_session_refresh_token_req_payload = params.cli_session_refresh_token_req_payload;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/auth/cli/session/revoke", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::POST, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    local_var_req_builder = local_var_req_builder.json(&cli_session_refresh_token_req_payload);
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<RevokeCliSessionSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<RevokeCliSessionError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Approve Device Login  Approve or deny the device login with the user code shown by the device. Approving lets the device exchange its device code for a CLI session of the auth'ed user. Called by the page at `/auth/device`.
+pub async fn verify_device_code(configuration: &configuration::Configuration, params: VerifyDeviceCodeParams) -> Result<ResponseContent<VerifyDeviceCodeSuccess>, Error<VerifyDeviceCodeError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let verify_device_code_req_payload = params.verify_device_code_req_payload;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/auth/device/verify", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::POST, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+    local_var_req_builder = local_var_req_builder.json(&verify_device_code_req_payload);
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<VerifyDeviceCodeSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<VerifyDeviceCodeError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Get Currently Auth'ed User  Get the currently auth'ed user. This will return the user object for the currently auth'ed user.
+pub async fn whoami(configuration: &configuration::Configuration) -> Result<ResponseContent<WhoamiSuccess>, Error<WhoamiError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/auth/whoami", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::GET, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
     if let Some(ref local_var_apikey) = local_var_configuration.api_key {
         let local_var_key = local_var_apikey.key.clone();
         let local_var_value = match local_var_apikey.prefix {
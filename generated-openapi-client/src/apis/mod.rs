@@ -93,5 +93,6 @@ pub fn parse_deep_object(prefix: &str, value: &serde_json::Value) -> Vec<(String
 pub mod api_key_api;
 pub mod auth_api;
 pub mod health_api;
+pub mod organization_api;
 
 pub mod configuration;
@@ -0,0 +1,1015 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+
+use reqwest;
+
+use crate::{apis::ResponseContent, models};
+use super::{Error, configuration};
+
+/// struct for passing parameters to the method [`accept_invitation`]
+#[derive(Clone, Debug)]
+pub struct AcceptInvitationParams {
+    /// The id of the invitation to accept
+    pub invitation_id: String
+}
+
+/// struct for passing parameters to the method [`create_organization`]
+#[derive(Clone, Debug)]
+pub struct CreateOrganizationParams {
+    /// JSON request payload to create an organization
+    pub organization_req_payload: models::OrganizationReqPayload
+}
+
+/// struct for passing parameters to the method [`decline_invitation`]
+#[derive(Clone, Debug)]
+pub struct DeclineInvitationParams {
+    /// The id of the invitation to decline
+    pub invitation_id: String
+}
+
+/// struct for passing parameters to the method [`delete_invitation`]
+#[derive(Clone, Debug)]
+pub struct DeleteInvitationParams {
+    /// The id of the organization to act on
+    pub x_organization: String,
+    /// The id of the invitation to withdraw
+    pub invitation_id: String
+}
+
+/// struct for passing parameters to the method [`delete_organization`]
+#[derive(Clone, Debug)]
+pub struct DeleteOrganizationParams {
+    /// The id of the organization to act on
+    pub x_organization: String
+}
+
+/// struct for passing parameters to the method [`get_invitations`]
+#[derive(Clone, Debug)]
+pub struct GetInvitationsParams {
+    /// The id of the organization to act on
+    pub x_organization: String
+}
+
+/// struct for passing parameters to the method [`get_members`]
+#[derive(Clone, Debug)]
+pub struct GetMembersParams {
+    /// The id of the organization to act on
+    pub x_organization: String
+}
+
+/// struct for passing parameters to the method [`get_organization`]
+#[derive(Clone, Debug)]
+pub struct GetOrganizationParams {
+    /// The id of the organization to act on
+    pub x_organization: String
+}
+
+/// struct for passing parameters to the method [`invite_member`]
+#[derive(Clone, Debug)]
+pub struct InviteMemberParams {
+    /// The id of the organization to act on
+    pub x_organization: String,
+    /// JSON request payload with the email to invite
+    pub invite_organization_user_req_payload: models::InviteOrganizationUserReqPayload
+}
+
+/// struct for passing parameters to the method [`remove_member`]
+#[derive(Clone, Debug)]
+pub struct RemoveMemberParams {
+    /// The id of the organization to act on
+    pub x_organization: String,
+    /// The id of the member to remove
+    pub user_id: String
+}
+
+/// struct for passing parameters to the method [`update_member`]
+#[derive(Clone, Debug)]
+pub struct UpdateMemberParams {
+    /// The id of the organization to act on
+    pub x_organization: String,
+    /// The id of the member to update
+    pub user_id: String,
+    /// JSON request payload with the new role
+    pub update_organization_user_req_payload: models::UpdateOrganizationUserReqPayload
+}
+
+/// struct for passing parameters to the method [`update_organization`]
+#[derive(Clone, Debug)]
+pub struct UpdateOrganizationParams {
+    /// The id of the organization to act on
+    pub x_organization: String,
+    /// JSON request payload with the new name of the organization
+    pub organization_req_payload: models::OrganizationReqPayload
+}
+
+
+/// struct for typed successes of method [`accept_invitation`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AcceptInvitationSuccess {
+    Status201(models::OrganizationRespBody),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`create_organization`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CreateOrganizationSuccess {
+    Status201(models::OrganizationRespBody),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`decline_invitation`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeclineInvitationSuccess {
+    Status204(),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`delete_invitation`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeleteInvitationSuccess {
+    Status204(),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`delete_organization`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeleteOrganizationSuccess {
+    Status204(),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`get_invitations`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetInvitationsSuccess {
+    Status200(models::GetOrganizationInvitationsRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`get_members`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetMembersSuccess {
+    Status200(models::GetOrganizationUsersRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`get_organization`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetOrganizationSuccess {
+    Status200(models::OrganizationRespBody),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`get_organizations`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetOrganizationsSuccess {
+    Status200(models::GetOrganizationsRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`get_user_invitations`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetUserInvitationsSuccess {
+    Status200(models::GetUserInvitationsRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`invite_member`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum InviteMemberSuccess {
+    Status202(),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`remove_member`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RemoveMemberSuccess {
+    Status204(),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`update_member`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum UpdateMemberSuccess {
+    Status200(models::OrganizationUserRespBody),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed successes of method [`update_organization`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum UpdateOrganizationSuccess {
+    Status200(models::OrganizationRespBody),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`accept_invitation`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AcceptInvitationError {
+    Status400(models::ErrorRespPayload),
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    Status404(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`create_organization`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CreateOrganizationError {
+    Status400(models::ErrorRespPayload),
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`decline_invitation`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeclineInvitationError {
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    Status404(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`delete_invitation`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeleteInvitationError {
+    Status400(models::ErrorRespPayload),
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    Status404(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`delete_organization`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeleteOrganizationError {
+    Status400(models::ErrorRespPayload),
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`get_invitations`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetInvitationsError {
+    Status400(models::ErrorRespPayload),
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`get_members`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetMembersError {
+    Status400(models::ErrorRespPayload),
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`get_organization`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetOrganizationError {
+    Status400(models::ErrorRespPayload),
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`get_organizations`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetOrganizationsError {
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`get_user_invitations`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GetUserInvitationsError {
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`invite_member`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum InviteMemberError {
+    Status400(models::ErrorRespPayload),
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`remove_member`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RemoveMemberError {
+    Status400(models::ErrorRespPayload),
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    Status404(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`update_member`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum UpdateMemberError {
+    Status400(models::ErrorRespPayload),
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    Status404(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+/// struct for typed errors of method [`update_organization`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum UpdateOrganizationError {
+    Status400(models::ErrorRespPayload),
+    Status401(models::ErrorRespPayload),
+    Status403(models::ErrorRespPayload),
+    UnknownValue(serde_json::Value),
+}
+
+
+/// Accept Invitation  Accept an invitation sent to a verified email of the auth'ed user, joining the organization with the invited role.
+pub async fn accept_invitation(configuration: &configuration::Configuration, params: AcceptInvitationParams) -> Result<ResponseContent<AcceptInvitationSuccess>, Error<AcceptInvitationError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let invitation_id = params.invitation_id;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/organization/invitations/{invitation_id}/accept", local_var_configuration.base_path, invitation_id=crate::apis::urlencode(invitation_id));
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::POST, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<AcceptInvitationSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<AcceptInvitationError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Create Organization  Create an organization with the auth'ed user as its owner. Select it with the `X-Organization` header to act on it.
+pub async fn create_organization(configuration: &configuration::Configuration, params: CreateOrganizationParams) -> Result<ResponseContent<CreateOrganizationSuccess>, Error<CreateOrganizationError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let organization_req_payload = params.organization_req_payload;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/organization", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::POST, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+    local_var_req_builder = local_var_req_builder.json(&organization_req_payload);
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<CreateOrganizationSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<CreateOrganizationError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Decline Invitation  Decline an invitation sent to a verified email of the auth'ed user.
+pub async fn decline_invitation(configuration: &configuration::Configuration, params: DeclineInvitationParams) -> Result<ResponseContent<DeclineInvitationSuccess>, Error<DeclineInvitationError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let invitation_id = params.invitation_id;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/organization/invitations/{invitation_id}", local_var_configuration.base_path, invitation_id=crate::apis::urlencode(invitation_id));
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::DELETE, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<DeclineInvitationSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<DeclineInvitationError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Delete Organization Invitation  Withdraw a pending invitation of the organization selected with the `X-Organization` header. Requires the admin role.
+pub async fn delete_invitation(configuration: &configuration::Configuration, params: DeleteInvitationParams) -> Result<ResponseContent<DeleteInvitationSuccess>, Error<DeleteInvitationError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let x_organization = params.x_organization;
+    let invitation_id = params.invitation_id;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/organization/current/invitations/{invitation_id}", local_var_configuration.base_path, invitation_id=crate::apis::urlencode(invitation_id));
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::DELETE, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    local_var_req_builder = local_var_req_builder.header("X-Organization", x_organization.to_string());
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<DeleteInvitationSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<DeleteInvitationError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Delete Current Organization  Delete the organization selected with the `X-Organization` header and all of its memberships. Requires the owner role.
+pub async fn delete_organization(configuration: &configuration::Configuration, params: DeleteOrganizationParams) -> Result<ResponseContent<DeleteOrganizationSuccess>, Error<DeleteOrganizationError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let x_organization = params.x_organization;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/organization/current", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::DELETE, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    local_var_req_builder = local_var_req_builder.header("X-Organization", x_organization.to_string());
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<DeleteOrganizationSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<DeleteOrganizationError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Get Organization Invitations  Get the pending invitations of the organization selected with the `X-Organization` header. Requires the admin role.
+pub async fn get_invitations(configuration: &configuration::Configuration, params: GetInvitationsParams) -> Result<ResponseContent<GetInvitationsSuccess>, Error<GetInvitationsError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let x_organization = params.x_organization;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/organization/current/invitations", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::GET, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    local_var_req_builder = local_var_req_builder.header("X-Organization", x_organization.to_string());
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<GetInvitationsSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<GetInvitationsError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Get Organization Users  Get the members of the organization selected with the `X-Organization` header and their roles.
+pub async fn get_members(configuration: &configuration::Configuration, params: GetMembersParams) -> Result<ResponseContent<GetMembersSuccess>, Error<GetMembersError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let x_organization = params.x_organization;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/organization/current/users", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::GET, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    local_var_req_builder = local_var_req_builder.header("X-Organization", x_organization.to_string());
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<GetMembersSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<GetMembersError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Get Current Organization  Get the organization selected with the `X-Organization` header along with the role of the auth'ed user in it.
+pub async fn get_organization(configuration: &configuration::Configuration, params: GetOrganizationParams) -> Result<ResponseContent<GetOrganizationSuccess>, Error<GetOrganizationError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let x_organization = params.x_organization;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/organization/current", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::GET, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    local_var_req_builder = local_var_req_builder.header("X-Organization", x_organization.to_string());
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<GetOrganizationSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<GetOrganizationError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Get Organizations  Get the organizations the auth'ed user belongs to along with its role in each of them.
+pub async fn get_organizations(configuration: &configuration::Configuration) -> Result<ResponseContent<GetOrganizationsSuccess>, Error<GetOrganizationsError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/organization", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::GET, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<GetOrganizationsSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<GetOrganizationsError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Get User Invitations  Get the pending invitations sent to the verified emails of the auth'ed user. An email is verified when the provider of one of the user's logins asserted it with the `email_verified` claim.
+pub async fn get_user_invitations(configuration: &configuration::Configuration) -> Result<ResponseContent<GetUserInvitationsSuccess>, Error<GetUserInvitationsError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/organization/invitations", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::GET, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<GetUserInvitationsSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<GetUserInvitationsError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Invite Organization User  Invite an email to the organization selected with the `X-Organization` header. The user with the email joins by accepting the invitation, inviting the same email again replaces its pending invitation. The response is the same whether or not a user with the email exists. Requires the admin role, and the owner role to invite owners.
+pub async fn invite_member(configuration: &configuration::Configuration, params: InviteMemberParams) -> Result<ResponseContent<InviteMemberSuccess>, Error<InviteMemberError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let x_organization = params.x_organization;
+    let invite_organization_user_req_payload = params.invite_organization_user_req_payload;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/organization/current/invitations", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::POST, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    local_var_req_builder = local_var_req_builder.header("X-Organization", x_organization.to_string());
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+    local_var_req_builder = local_var_req_builder.json(&invite_organization_user_req_payload);
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<InviteMemberSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<InviteMemberError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Remove Organization User  Remove a member from the organization selected with the `X-Organization` header. Every member can remove itself to leave the organization, removing others requires the admin role and the owner role to remove owners. The last owner can not be removed.
+pub async fn remove_member(configuration: &configuration::Configuration, params: RemoveMemberParams) -> Result<ResponseContent<RemoveMemberSuccess>, Error<RemoveMemberError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let x_organization = params.x_organization;
+    let user_id = params.user_id;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/organization/current/users/{user_id}", local_var_configuration.base_path, user_id=crate::apis::urlencode(user_id));
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::DELETE, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    local_var_req_builder = local_var_req_builder.header("X-Organization", x_organization.to_string());
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<RemoveMemberSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<RemoveMemberError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Update Organization User  Change the role of a member of the organization selected with the `X-Organization` header. Requires the admin role, and the owner role to change the role of owners or make someone an owner. The last owner can not be demoted.
+pub async fn update_member(configuration: &configuration::Configuration, params: UpdateMemberParams) -> Result<ResponseContent<UpdateMemberSuccess>, Error<UpdateMemberError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let x_organization = params.x_organization;
+    let user_id = params.user_id;
+    let update_organization_user_req_payload = params.update_organization_user_req_payload;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/organization/current/users/{user_id}", local_var_configuration.base_path, user_id=crate::apis::urlencode(user_id));
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::PUT, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    local_var_req_builder = local_var_req_builder.header("X-Organization", x_organization.to_string());
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+    local_var_req_builder = local_var_req_builder.json(&update_organization_user_req_payload);
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<UpdateMemberSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<UpdateMemberError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
+/// Update Current Organization  Rename the organization selected with the `X-Organization` header. Requires the admin role.
+pub async fn update_organization(configuration: &configuration::Configuration, params: UpdateOrganizationParams) -> Result<ResponseContent<UpdateOrganizationSuccess>, Error<UpdateOrganizationError>> {
+    let local_var_configuration = configuration;
+
+    // unbox the parameters
+    let x_organization = params.x_organization;
+    let organization_req_payload = params.organization_req_payload;
+
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/api/organization/current", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::PUT, local_var_uri_str.as_str());
+
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    local_var_req_builder = local_var_req_builder.header("X-Organization", x_organization.to_string());
+    if let Some(ref local_var_token) = local_var_configuration.bearer_access_token {
+        local_var_req_builder = local_var_req_builder.bearer_auth(local_var_token.to_owned());
+    };
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("Authorization", local_var_value);
+    };
+    local_var_req_builder = local_var_req_builder.json(&organization_req_payload);
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+    let local_var_content = local_var_resp.text().await?;
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        let local_var_entity: Option<UpdateOrganizationSuccess> = serde_json::from_str(&local_var_content).ok();
+        let local_var_result = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Ok(local_var_result)
+    } else {
+        let local_var_entity: Option<UpdateOrganizationError> = serde_json::from_str(&local_var_content).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: local_var_content, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
+
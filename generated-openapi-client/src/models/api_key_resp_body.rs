@@ -0,0 +1,72 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ApiKeyRespBody {
+    /// The CIDRs the key can be used from. Empty if the key can be used from any IP address.
+    #[serde(rename = "allowed_cidrs")]
+    pub allowed_cidrs: Vec<String>,
+    #[serde(rename = "created_at")]
+    pub created_at: String,
+    #[serde(rename = "expires_at", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<Option<String>>,
+    #[serde(rename = "id")]
+    pub id: uuid::Uuid,
+    /// Public identifier embedded in the key. It is not secret and can be used to match a leaked key to its record. Null for legacy `tr-` keys.
+    #[serde(rename = "key_id", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<Option<String>>,
+    /// The last time (UTC) the key was used to authenticate a request. Usage is recorded in batches, so this can lag behind by up to a minute.
+    #[serde(rename = "last_used_at", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<Option<String>>,
+    /// The client IP of the last request authenticated with the key.
+    #[serde(rename = "last_used_ip", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub last_used_ip: Option<Option<String>>,
+    #[serde(rename = "name")]
+    pub name: String,
+    /// If the key was rotated, the time (UTC) until which the previous secret keeps authenticating.
+    #[serde(rename = "previous_key_expires_at", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub previous_key_expires_at: Option<Option<String>>,
+    /// The time (UTC) at which the key was revoked after being reported as leaked. Revoked keys no longer authenticate.
+    #[serde(rename = "revoked_at", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<Option<String>>,
+    #[serde(rename = "scopes")]
+    pub scopes: Vec<models::ApiKeyScope>,
+    #[serde(rename = "updated_at")]
+    pub updated_at: String,
+    /// The number of requests authenticated with the key.
+    #[serde(rename = "usage_count")]
+    pub usage_count: i64,
+    #[serde(rename = "user_id")]
+    pub user_id: uuid::Uuid,
+}
+
+impl ApiKeyRespBody {
+    pub fn new(allowed_cidrs: Vec<String>, created_at: String, id: uuid::Uuid, name: String, scopes: Vec<models::ApiKeyScope>, updated_at: String, usage_count: i64, user_id: uuid::Uuid) -> ApiKeyRespBody {
+        ApiKeyRespBody {
+            allowed_cidrs,
+            created_at,
+            expires_at: None,
+            id,
+            key_id: None,
+            last_used_at: None,
+            last_used_ip: None,
+            name,
+            previous_key_expires_at: None,
+            revoked_at: None,
+            scopes,
+            updated_at,
+            usage_count,
+            user_id,
+        }
+    }
+}
+
@@ -0,0 +1,43 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+/// 
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum ApiKeyScope {
+    #[serde(rename = "api_key:read")]
+    ApiKeyRead,
+    #[serde(rename = "api_key:write")]
+    ApiKeyWrite,
+    #[serde(rename = "user:read")]
+    UserRead,
+    #[serde(rename = "user:write")]
+    UserWrite,
+
+}
+
+impl ToString for ApiKeyScope {
+    fn to_string(&self) -> String {
+        match self {
+            Self::ApiKeyRead => String::from("api_key:read"),
+            Self::ApiKeyWrite => String::from("api_key:write"),
+            Self::UserRead => String::from("user:read"),
+            Self::UserWrite => String::from("user:write"),
+        }
+    }
+}
+
+impl Default for ApiKeyScope {
+    fn default() -> ApiKeyScope {
+        Self::ApiKeyRead
+    }
+}
+
@@ -0,0 +1,27 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BackchannelLogoutReqPayload {
+    /// A JWT signed by the OpenID provider identifying the session (`sid`) or user (`sub`) to log out.
+    #[serde(rename = "logout_token")]
+    pub logout_token: String,
+}
+
+impl BackchannelLogoutReqPayload {
+    pub fn new(logout_token: String) -> BackchannelLogoutReqPayload {
+        BackchannelLogoutReqPayload {
+            logout_token,
+        }
+    }
+}
+
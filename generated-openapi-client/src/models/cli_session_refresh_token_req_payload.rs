@@ -0,0 +1,27 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CliSessionRefreshTokenReqPayload {
+    /// The refresh token of the session.
+    #[serde(rename = "refresh_token")]
+    pub refresh_token: String,
+}
+
+impl CliSessionRefreshTokenReqPayload {
+    pub fn new(refresh_token: String) -> CliSessionRefreshTokenReqPayload {
+        CliSessionRefreshTokenReqPayload {
+            refresh_token,
+        }
+    }
+}
+
@@ -0,0 +1,45 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CliSessionTokensRespPayload {
+    /// Send it in the Authorization header without a prefix until it expires.
+    #[serde(rename = "access_token")]
+    pub access_token: String,
+    /// The time (UTC) at which the access token stops authenticating.
+    #[serde(rename = "access_token_expires_at")]
+    pub access_token_expires_at: String,
+    /// The time (UTC) at which the session expires unless it is refreshed before.
+    #[serde(rename = "expires_at")]
+    pub expires_at: String,
+    /// Exchanged at `/api/auth/cli/session/refresh` for a new pair of tokens. Every refresh token can only be used once.
+    #[serde(rename = "refresh_token")]
+    pub refresh_token: String,
+    #[serde(rename = "scopes")]
+    pub scopes: Vec<models::ApiKeyScope>,
+    #[serde(rename = "session_id")]
+    pub session_id: uuid::Uuid,
+}
+
+impl CliSessionTokensRespPayload {
+    pub fn new(access_token: String, access_token_expires_at: String, expires_at: String, refresh_token: String, scopes: Vec<models::ApiKeyScope>, session_id: uuid::Uuid) -> CliSessionTokensRespPayload {
+        CliSessionTokensRespPayload {
+            access_token,
+            access_token_expires_at,
+            expires_at,
+            refresh_token,
+            scopes,
+            session_id,
+        }
+    }
+}
+
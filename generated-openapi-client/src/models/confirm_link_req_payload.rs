@@ -0,0 +1,27 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ConfirmLinkReqPayload {
+    /// Whether to link the identity. The pending link is discarded either way.
+    #[serde(rename = "approve")]
+    pub approve: bool,
+}
+
+impl ConfirmLinkReqPayload {
+    pub fn new(approve: bool) -> ConfirmLinkReqPayload {
+        ConfirmLinkReqPayload {
+            approve,
+        }
+    }
+}
+
@@ -0,0 +1,30 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ConfirmLinkRespPayload {
+    #[serde(rename = "identity", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub identity: Option<Option<Box<models::UserIdentity>>>,
+    /// Where to send the user next, the `redirect_uri` the link was started with.
+    #[serde(rename = "redirect_uri")]
+    pub redirect_uri: String,
+}
+
+impl ConfirmLinkRespPayload {
+    pub fn new(redirect_uri: String) -> ConfirmLinkRespPayload {
+        ConfirmLinkRespPayload {
+            identity: None,
+            redirect_uri,
+        }
+    }
+}
+
@@ -12,15 +12,27 @@ use crate::models;
 
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub struct CreateApiKeyReqPayload {
+    /// The CIDRs (e.g. `203.0.113.0/24`) the api key can be used from. Requests from other addresses are rejected with a 403. If not provided, the key can be used from any address.
+    #[serde(rename = "allowed_cidrs", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub allowed_cidrs: Option<Option<Vec<String>>>,
+    /// The time (UTC) at which the api key will stop authenticating. If not provided, the key does not expire unless the server enforces a maximum lifetime.
+    #[serde(rename = "expires_at", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<Option<String>>,
     /// The name which will be assigned to the new api key.
     #[serde(rename = "name")]
     pub name: String,
+    /// The scopes granted to the new api key. Must be non-empty and a subset of the scopes of the credential used to create it.
+    #[serde(rename = "scopes")]
+    pub scopes: Vec<models::ApiKeyScope>,
 }
 
 impl CreateApiKeyReqPayload {
-    pub fn new(name: String) -> CreateApiKeyReqPayload {
+    pub fn new(name: String, scopes: Vec<models::ApiKeyScope>) -> CreateApiKeyReqPayload {
         CreateApiKeyReqPayload {
+            allowed_cidrs: None,
+            expires_at: None,
             name,
+            scopes,
         }
     }
 }
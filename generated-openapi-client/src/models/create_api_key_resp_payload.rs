@@ -12,15 +12,27 @@ use crate::models;
 
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub struct CreateApiKeyRespPayload {
+    /// The CIDRs the api key can be used from. Empty if the key can be used from any address.
+    #[serde(rename = "allowed_cidrs")]
+    pub allowed_cidrs: Vec<String>,
     /// The api key which was created. This is the value which should be used in the Authorization header.
     #[serde(rename = "api_key")]
     pub api_key: String,
+    /// The time (UTC) at which the api key will stop authenticating. Null if the key does not expire.
+    #[serde(rename = "expires_at", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<Option<String>>,
+    /// The scopes granted to the api key.
+    #[serde(rename = "scopes")]
+    pub scopes: Vec<models::ApiKeyScope>,
 }
 
 impl CreateApiKeyRespPayload {
-    pub fn new(api_key: String) -> CreateApiKeyRespPayload {
+    pub fn new(allowed_cidrs: Vec<String>, api_key: String, scopes: Vec<models::ApiKeyScope>) -> CreateApiKeyRespPayload {
         CreateApiKeyRespPayload {
+            allowed_cidrs,
             api_key,
+            expires_at: None,
+            scopes,
         }
     }
 }
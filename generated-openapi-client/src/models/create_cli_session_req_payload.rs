@@ -0,0 +1,35 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CreateCliSessionReqPayload {
+    /// Version of the CLI which is logging in.
+    #[serde(rename = "cli_version")]
+    pub cli_version: String,
+    /// Hostname of the machine the CLI runs on, shown to tell sessions apart.
+    #[serde(rename = "hostname")]
+    pub hostname: String,
+    /// The scopes granted to the session. Must be non-empty and a subset of the scopes of the credential used to create it.
+    #[serde(rename = "scopes")]
+    pub scopes: Vec<models::ApiKeyScope>,
+}
+
+impl CreateCliSessionReqPayload {
+    pub fn new(cli_version: String, hostname: String, scopes: Vec<models::ApiKeyScope>) -> CreateCliSessionReqPayload {
+        CreateCliSessionReqPayload {
+            cli_version,
+            hostname,
+            scopes,
+        }
+    }
+}
+
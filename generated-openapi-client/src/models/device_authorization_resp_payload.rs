@@ -0,0 +1,39 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeviceAuthorizationRespPayload {
+    /// Version the client on the device reported.
+    #[serde(rename = "cli_version")]
+    pub cli_version: String,
+    /// Name of the client which started the login.
+    #[serde(rename = "client_id")]
+    pub client_id: String,
+    /// Hostname the device reported for itself.
+    #[serde(rename = "hostname")]
+    pub hostname: String,
+    /// The scopes the device gets when the login is approved.
+    #[serde(rename = "scopes")]
+    pub scopes: Vec<models::ApiKeyScope>,
+}
+
+impl DeviceAuthorizationRespPayload {
+    pub fn new(cli_version: String, client_id: String, hostname: String, scopes: Vec<models::ApiKeyScope>) -> DeviceAuthorizationRespPayload {
+        DeviceAuthorizationRespPayload {
+            cli_version,
+            client_id,
+            hostname,
+            scopes,
+        }
+    }
+}
+
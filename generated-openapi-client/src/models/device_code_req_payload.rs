@@ -0,0 +1,39 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeviceCodeReqPayload {
+    /// Version of the client on the device. Defaults to `unknown`.
+    #[serde(rename = "cli_version", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub cli_version: Option<Option<String>>,
+    /// Name of the client logging in.
+    #[serde(rename = "client_id")]
+    pub client_id: String,
+    /// Hostname of the device, shown to tell sessions apart. Defaults to `unknown`.
+    #[serde(rename = "hostname", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<Option<String>>,
+    /// Space separated scopes of the CLI session the device gets. Defaults to every scope.
+    #[serde(rename = "scope", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub scope: Option<Option<String>>,
+}
+
+impl DeviceCodeReqPayload {
+    pub fn new(client_id: String) -> DeviceCodeReqPayload {
+        DeviceCodeReqPayload {
+            cli_version: None,
+            client_id,
+            hostname: None,
+            scope: None,
+        }
+    }
+}
+
@@ -0,0 +1,47 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeviceCodeRespPayload {
+    /// Secret the device polls `/api/auth/device/token` with. Do not show it to the user.
+    #[serde(rename = "device_code")]
+    pub device_code: String,
+    /// Seconds until the device code expires.
+    #[serde(rename = "expires_in")]
+    pub expires_in: i64,
+    /// Seconds the device must wait between polls.
+    #[serde(rename = "interval")]
+    pub interval: i64,
+    /// The code the user enters at the verification URI.
+    #[serde(rename = "user_code")]
+    pub user_code: String,
+    /// The page where the user logs in and enters the user code.
+    #[serde(rename = "verification_uri")]
+    pub verification_uri: String,
+    /// The verification URI with the user code filled in.
+    #[serde(rename = "verification_uri_complete")]
+    pub verification_uri_complete: String,
+}
+
+impl DeviceCodeRespPayload {
+    pub fn new(device_code: String, expires_in: i64, interval: i64, user_code: String, verification_uri: String, verification_uri_complete: String) -> DeviceCodeRespPayload {
+        DeviceCodeRespPayload {
+            device_code,
+            expires_in,
+            interval,
+            user_code,
+            verification_uri,
+            verification_uri_complete,
+        }
+    }
+}
+
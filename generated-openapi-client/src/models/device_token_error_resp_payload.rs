@@ -0,0 +1,32 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+/// DeviceTokenErrorRespPayload : Error response of the device token endpoint as defined by RFC 8628 section 3.5.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeviceTokenErrorRespPayload {
+    /// One of `authorization_pending`, `slow_down`, `access_denied`, `expired_token`, `invalid_grant` or `unsupported_grant_type`.
+    #[serde(rename = "error")]
+    pub error: String,
+    #[serde(rename = "error_description")]
+    pub error_description: String,
+}
+
+impl DeviceTokenErrorRespPayload {
+    /// Error response of the device token endpoint as defined by RFC 8628 section 3.5.
+    pub fn new(error: String, error_description: String) -> DeviceTokenErrorRespPayload {
+        DeviceTokenErrorRespPayload {
+            error,
+            error_description,
+        }
+    }
+}
+
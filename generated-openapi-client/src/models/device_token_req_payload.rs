@@ -0,0 +1,35 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeviceTokenReqPayload {
+    /// The client_id the device code was requested with.
+    #[serde(rename = "client_id")]
+    pub client_id: String,
+    /// The device code returned by `/api/auth/device/code`.
+    #[serde(rename = "device_code")]
+    pub device_code: String,
+    /// Must be `urn:ietf:params:oauth:grant-type:device_code`.
+    #[serde(rename = "grant_type")]
+    pub grant_type: String,
+}
+
+impl DeviceTokenReqPayload {
+    pub fn new(client_id: String, device_code: String, grant_type: String) -> DeviceTokenReqPayload {
+        DeviceTokenReqPayload {
+            client_id,
+            device_code,
+            grant_type,
+        }
+    }
+}
+
@@ -0,0 +1,47 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DeviceTokenRespPayload {
+    /// The access token of a CLI session of the user who approved the login. Send it in the Authorization header.
+    #[serde(rename = "access_token")]
+    pub access_token: String,
+    /// Seconds until the access token expires.
+    #[serde(rename = "expires_in")]
+    pub expires_in: i64,
+    /// Exchanged at `/api/auth/cli/session/refresh` for a new pair of tokens. Every refresh token can only be used once.
+    #[serde(rename = "refresh_token")]
+    pub refresh_token: String,
+    /// Space separated scopes of the session.
+    #[serde(rename = "scope")]
+    pub scope: String,
+    /// The id of the CLI session, listed at `/api/auth/sessions`.
+    #[serde(rename = "session_id")]
+    pub session_id: uuid::Uuid,
+    /// Always `Bearer`.
+    #[serde(rename = "token_type")]
+    pub token_type: String,
+}
+
+impl DeviceTokenRespPayload {
+    pub fn new(access_token: String, expires_in: i64, refresh_token: String, scope: String, session_id: uuid::Uuid, token_type: String) -> DeviceTokenRespPayload {
+        DeviceTokenRespPayload {
+            access_token,
+            expires_in,
+            refresh_token,
+            scope,
+            session_id,
+            token_type,
+        }
+    }
+}
+
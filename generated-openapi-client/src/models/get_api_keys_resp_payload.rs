@@ -0,0 +1,31 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GetApiKeysRespPayload {
+    /// The api keys on the requested page. Key hashes are never returned.
+    #[serde(rename = "api_keys")]
+    pub api_keys: Vec<models::ApiKeyRespBody>,
+    /// Total number of pages of api keys for the auth'ed user.
+    #[serde(rename = "total_pages")]
+    pub total_pages: i64,
+}
+
+impl GetApiKeysRespPayload {
+    pub fn new(api_keys: Vec<models::ApiKeyRespBody>, total_pages: i64) -> GetApiKeysRespPayload {
+        GetApiKeysRespPayload {
+            api_keys,
+            total_pages,
+        }
+    }
+}
+
@@ -0,0 +1,27 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GetOidcProvidersRespPayload {
+    /// Names of the configured OpenID providers, the first one is the default.
+    #[serde(rename = "providers")]
+    pub providers: Vec<String>,
+}
+
+impl GetOidcProvidersRespPayload {
+    pub fn new(providers: Vec<String>) -> GetOidcProvidersRespPayload {
+        GetOidcProvidersRespPayload {
+            providers,
+        }
+    }
+}
+
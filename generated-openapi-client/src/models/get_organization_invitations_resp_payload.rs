@@ -0,0 +1,27 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GetOrganizationInvitationsRespPayload {
+    /// The pending invitations of the organization, newest first.
+    #[serde(rename = "invitations")]
+    pub invitations: Vec<models::OrganizationInvitationRespBody>,
+}
+
+impl GetOrganizationInvitationsRespPayload {
+    pub fn new(invitations: Vec<models::OrganizationInvitationRespBody>) -> GetOrganizationInvitationsRespPayload {
+        GetOrganizationInvitationsRespPayload {
+            invitations,
+        }
+    }
+}
+
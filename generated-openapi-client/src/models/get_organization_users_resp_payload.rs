@@ -0,0 +1,27 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GetOrganizationUsersRespPayload {
+    /// The members of the organization, in the order they were added.
+    #[serde(rename = "users")]
+    pub users: Vec<models::OrganizationUserRespBody>,
+}
+
+impl GetOrganizationUsersRespPayload {
+    pub fn new(users: Vec<models::OrganizationUserRespBody>) -> GetOrganizationUsersRespPayload {
+        GetOrganizationUsersRespPayload {
+            users,
+        }
+    }
+}
+
@@ -0,0 +1,27 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GetSessionsRespPayload {
+    /// The active sessions of the auth'ed user, browser sessions first and newest first.
+    #[serde(rename = "sessions")]
+    pub sessions: Vec<models::SessionRespBody>,
+}
+
+impl GetSessionsRespPayload {
+    pub fn new(sessions: Vec<models::SessionRespBody>) -> GetSessionsRespPayload {
+        GetSessionsRespPayload {
+            sessions,
+        }
+    }
+}
+
@@ -0,0 +1,27 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GetUserInvitationsRespPayload {
+    /// The pending invitations sent to the verified emails of the auth'ed user, newest first.
+    #[serde(rename = "invitations")]
+    pub invitations: Vec<models::UserInvitationRespBody>,
+}
+
+impl GetUserInvitationsRespPayload {
+    pub fn new(invitations: Vec<models::UserInvitationRespBody>) -> GetUserInvitationsRespPayload {
+        GetUserInvitationsRespPayload {
+            invitations,
+        }
+    }
+}
+
@@ -0,0 +1,30 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InviteOrganizationUserReqPayload {
+    /// The email to invite. The user with this email joins the organization by accepting the invitation.
+    #[serde(rename = "email")]
+    pub email: String,
+    #[serde(rename = "role")]
+    pub role: models::OrganizationRole,
+}
+
+impl InviteOrganizationUserReqPayload {
+    pub fn new(email: String, role: models::OrganizationRole) -> InviteOrganizationUserReqPayload {
+        InviteOrganizationUserReqPayload {
+            email,
+            role,
+        }
+    }
+}
+
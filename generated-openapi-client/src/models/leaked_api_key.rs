@@ -0,0 +1,39 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LeakedApiKey {
+    /// The kind of location the api key was found in, e.g. `content` or `commit`.
+    #[serde(rename = "source", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub source: Option<Option<String>>,
+    /// The raw api key which was found.
+    #[serde(rename = "token")]
+    pub token: String,
+    /// The type of secret the scanner matched.
+    #[serde(rename = "type", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<Option<String>>,
+    /// Where the api key was found.
+    #[serde(rename = "url", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub url: Option<Option<String>>,
+}
+
+impl LeakedApiKey {
+    pub fn new(token: String) -> LeakedApiKey {
+        LeakedApiKey {
+            source: None,
+            token,
+            r#type: None,
+            url: None,
+        }
+    }
+}
+
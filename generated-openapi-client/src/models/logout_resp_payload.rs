@@ -0,0 +1,27 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LogoutRespPayload {
+    /// Where to send the browser next. Ends the session at the OpenID provider if it supports RP-initiated logout, otherwise it is the `redirect_uri`.
+    #[serde(rename = "logout_url")]
+    pub logout_url: String,
+}
+
+impl LogoutRespPayload {
+    pub fn new(logout_url: String) -> LogoutRespPayload {
+        LogoutRespPayload {
+            logout_url,
+        }
+    }
+}
+
@@ -1,8 +1,88 @@
+pub mod api_key_resp_body;
+pub use self::api_key_resp_body::ApiKeyRespBody;
+pub mod api_key_scope;
+pub use self::api_key_scope::ApiKeyScope;
+pub mod backchannel_logout_req_payload;
+pub use self::backchannel_logout_req_payload::BackchannelLogoutReqPayload;
+pub mod cli_session_refresh_token_req_payload;
+pub use self::cli_session_refresh_token_req_payload::CliSessionRefreshTokenReqPayload;
+pub mod cli_session_tokens_resp_payload;
+pub use self::cli_session_tokens_resp_payload::CliSessionTokensRespPayload;
+pub mod confirm_link_req_payload;
+pub use self::confirm_link_req_payload::ConfirmLinkReqPayload;
+pub mod confirm_link_resp_payload;
+pub use self::confirm_link_resp_payload::ConfirmLinkRespPayload;
 pub mod create_api_key_req_payload;
 pub use self::create_api_key_req_payload::CreateApiKeyReqPayload;
 pub mod create_api_key_resp_payload;
 pub use self::create_api_key_resp_payload::CreateApiKeyRespPayload;
+pub mod create_cli_session_req_payload;
+pub use self::create_cli_session_req_payload::CreateCliSessionReqPayload;
+pub mod device_authorization_resp_payload;
+pub use self::device_authorization_resp_payload::DeviceAuthorizationRespPayload;
+pub mod device_code_req_payload;
+pub use self::device_code_req_payload::DeviceCodeReqPayload;
+pub mod device_code_resp_payload;
+pub use self::device_code_resp_payload::DeviceCodeRespPayload;
+pub mod device_token_error_resp_payload;
+pub use self::device_token_error_resp_payload::DeviceTokenErrorRespPayload;
+pub mod device_token_req_payload;
+pub use self::device_token_req_payload::DeviceTokenReqPayload;
+pub mod device_token_resp_payload;
+pub use self::device_token_resp_payload::DeviceTokenRespPayload;
 pub mod error_resp_payload;
 pub use self::error_resp_payload::ErrorRespPayload;
+pub mod get_api_keys_resp_payload;
+pub use self::get_api_keys_resp_payload::GetApiKeysRespPayload;
+pub mod get_oidc_providers_resp_payload;
+pub use self::get_oidc_providers_resp_payload::GetOidcProvidersRespPayload;
+pub mod get_organization_invitations_resp_payload;
+pub use self::get_organization_invitations_resp_payload::GetOrganizationInvitationsRespPayload;
+pub mod get_organization_users_resp_payload;
+pub use self::get_organization_users_resp_payload::GetOrganizationUsersRespPayload;
+pub mod get_organizations_resp_payload;
+pub use self::get_organizations_resp_payload::GetOrganizationsRespPayload;
+pub mod get_sessions_resp_payload;
+pub use self::get_sessions_resp_payload::GetSessionsRespPayload;
+pub mod get_user_invitations_resp_payload;
+pub use self::get_user_invitations_resp_payload::GetUserInvitationsRespPayload;
+pub mod invite_organization_user_req_payload;
+pub use self::invite_organization_user_req_payload::InviteOrganizationUserReqPayload;
+pub mod leaked_api_key;
+pub use self::leaked_api_key::LeakedApiKey;
+pub mod logout_resp_payload;
+pub use self::logout_resp_payload::LogoutRespPayload;
+pub mod organization_invitation_resp_body;
+pub use self::organization_invitation_resp_body::OrganizationInvitationRespBody;
+pub mod organization_req_payload;
+pub use self::organization_req_payload::OrganizationReqPayload;
+pub mod organization_resp_body;
+pub use self::organization_resp_body::OrganizationRespBody;
+pub mod organization_role;
+pub use self::organization_role::OrganizationRole;
+pub mod organization_user_resp_body;
+pub use self::organization_user_resp_body::OrganizationUserRespBody;
+pub mod pending_link_resp_payload;
+pub use self::pending_link_resp_payload::PendingLinkRespPayload;
+pub mod revoke_leaked_api_keys_resp_payload;
+pub use self::revoke_leaked_api_keys_resp_payload::RevokeLeakedApiKeysRespPayload;
+pub mod revoke_sessions_resp_payload;
+pub use self::revoke_sessions_resp_payload::RevokeSessionsRespPayload;
+pub mod rotate_api_key_req_payload;
+pub use self::rotate_api_key_req_payload::RotateApiKeyReqPayload;
+pub mod rotate_api_key_resp_payload;
+pub use self::rotate_api_key_resp_payload::RotateApiKeyRespPayload;
+pub mod session_kind;
+pub use self::session_kind::SessionKind;
+pub mod session_resp_body;
+pub use self::session_resp_body::SessionRespBody;
+pub mod update_organization_user_req_payload;
+pub use self::update_organization_user_req_payload::UpdateOrganizationUserReqPayload;
 pub mod user;
 pub use self::user::User;
+pub mod user_identity;
+pub use self::user_identity::UserIdentity;
+pub mod user_invitation_resp_body;
+pub use self::user_invitation_resp_body::UserInvitationRespBody;
+pub mod verify_device_code_req_payload;
+pub use self::verify_device_code_req_payload::VerifyDeviceCodeReqPayload;
@@ -0,0 +1,40 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OrganizationInvitationRespBody {
+    #[serde(rename = "created_at")]
+    pub created_at: String,
+    /// The email the invitation was sent to.
+    #[serde(rename = "email")]
+    pub email: String,
+    /// The time (UTC) after which the invitation can no longer be accepted.
+    #[serde(rename = "expires_at")]
+    pub expires_at: String,
+    #[serde(rename = "id")]
+    pub id: uuid::Uuid,
+    #[serde(rename = "role")]
+    pub role: models::OrganizationRole,
+}
+
+impl OrganizationInvitationRespBody {
+    pub fn new(created_at: String, email: String, expires_at: String, id: uuid::Uuid, role: models::OrganizationRole) -> OrganizationInvitationRespBody {
+        OrganizationInvitationRespBody {
+            created_at,
+            email,
+            expires_at,
+            id,
+            role,
+        }
+    }
+}
+
@@ -0,0 +1,38 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OrganizationRespBody {
+    #[serde(rename = "created_at")]
+    pub created_at: String,
+    #[serde(rename = "id")]
+    pub id: uuid::Uuid,
+    #[serde(rename = "name")]
+    pub name: String,
+    #[serde(rename = "role")]
+    pub role: models::OrganizationRole,
+    #[serde(rename = "updated_at")]
+    pub updated_at: String,
+}
+
+impl OrganizationRespBody {
+    pub fn new(created_at: String, id: uuid::Uuid, name: String, role: models::OrganizationRole, updated_at: String) -> OrganizationRespBody {
+        OrganizationRespBody {
+            created_at,
+            id,
+            name,
+            role,
+            updated_at,
+        }
+    }
+}
+
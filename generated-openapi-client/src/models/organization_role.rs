@@ -0,0 +1,41 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+/// OrganizationRole : The role of a user in an organization. Each role can do everything the roles before it can.
+/// The role of a user in an organization. Each role can do everything the roles before it can.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum OrganizationRole {
+    #[serde(rename = "member")]
+    Member,
+    #[serde(rename = "admin")]
+    Admin,
+    #[serde(rename = "owner")]
+    Owner,
+
+}
+
+impl ToString for OrganizationRole {
+    fn to_string(&self) -> String {
+        match self {
+            Self::Member => String::from("member"),
+            Self::Admin => String::from("admin"),
+            Self::Owner => String::from("owner"),
+        }
+    }
+}
+
+impl Default for OrganizationRole {
+    fn default() -> OrganizationRole {
+        Self::Member
+    }
+}
+
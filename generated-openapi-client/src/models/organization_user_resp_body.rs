@@ -0,0 +1,43 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OrganizationUserRespBody {
+    /// The time (UTC) at which the user was added to the organization.
+    #[serde(rename = "created_at")]
+    pub created_at: String,
+    #[serde(rename = "email")]
+    pub email: String,
+    #[serde(rename = "name", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub name: Option<Option<String>>,
+    #[serde(rename = "role")]
+    pub role: models::OrganizationRole,
+    /// The time (UTC) at which the role of the user was last changed.
+    #[serde(rename = "updated_at")]
+    pub updated_at: String,
+    #[serde(rename = "user_id")]
+    pub user_id: uuid::Uuid,
+}
+
+impl OrganizationUserRespBody {
+    pub fn new(created_at: String, email: String, role: models::OrganizationRole, updated_at: String, user_id: uuid::Uuid) -> OrganizationUserRespBody {
+        OrganizationUserRespBody {
+            created_at,
+            email,
+            name: None,
+            role,
+            updated_at,
+            user_id,
+        }
+    }
+}
+
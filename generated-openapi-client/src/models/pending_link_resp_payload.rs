@@ -0,0 +1,35 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PendingLinkRespPayload {
+    /// The email the provider reported for the identity.
+    #[serde(rename = "email")]
+    pub email: String,
+    /// The name the provider reported for the identity.
+    #[serde(rename = "name", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub name: Option<Option<String>>,
+    /// Name of the OpenID provider the identity belongs to.
+    #[serde(rename = "provider")]
+    pub provider: String,
+}
+
+impl PendingLinkRespPayload {
+    pub fn new(email: String, provider: String) -> PendingLinkRespPayload {
+        PendingLinkRespPayload {
+            email,
+            name: None,
+            provider,
+        }
+    }
+}
+
@@ -0,0 +1,27 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RevokeLeakedApiKeysRespPayload {
+    /// The number of reported api keys which were received. This does not reveal whether any of them were valid.
+    #[serde(rename = "received")]
+    pub received: i32,
+}
+
+impl RevokeLeakedApiKeysRespPayload {
+    pub fn new(received: i32) -> RevokeLeakedApiKeysRespPayload {
+        RevokeLeakedApiKeysRespPayload {
+            received,
+        }
+    }
+}
+
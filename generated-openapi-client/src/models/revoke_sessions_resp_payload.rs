@@ -0,0 +1,31 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RevokeSessionsRespPayload {
+    /// The number of browser sessions which were logged out.
+    #[serde(rename = "browser_sessions")]
+    pub browser_sessions: i32,
+    /// The number of CLI sessions which were logged out.
+    #[serde(rename = "cli_sessions")]
+    pub cli_sessions: i32,
+}
+
+impl RevokeSessionsRespPayload {
+    pub fn new(browser_sessions: i32, cli_sessions: i32) -> RevokeSessionsRespPayload {
+        RevokeSessionsRespPayload {
+            browser_sessions,
+            cli_sessions,
+        }
+    }
+}
+
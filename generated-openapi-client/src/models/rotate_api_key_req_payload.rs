@@ -0,0 +1,27 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RotateApiKeyReqPayload {
+    /// Number of seconds the previous secret keeps authenticating after the rotation. Defaults to `API_KEY_ROTATION_GRACE_PERIOD_SECONDS` or one hour, and must not exceed `API_KEY_ROTATION_MAX_GRACE_PERIOD_SECONDS` (one week by default).
+    #[serde(rename = "grace_period_seconds", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub grace_period_seconds: Option<Option<i64>>,
+}
+
+impl RotateApiKeyReqPayload {
+    pub fn new() -> RotateApiKeyReqPayload {
+        RotateApiKeyReqPayload {
+            grace_period_seconds: None,
+        }
+    }
+}
+
@@ -0,0 +1,35 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RotateApiKeyRespPayload {
+    /// The new secret for the api key. This is the value which should be used in the Authorization header from now on.
+    #[serde(rename = "api_key")]
+    pub api_key: String,
+    /// The time (UTC) at which the new secret will stop authenticating. Null if the key does not expire.
+    #[serde(rename = "expires_at", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<Option<String>>,
+    /// The time (UTC) until which the previous secret keeps authenticating.
+    #[serde(rename = "previous_key_expires_at")]
+    pub previous_key_expires_at: String,
+}
+
+impl RotateApiKeyRespPayload {
+    pub fn new(api_key: String, previous_key_expires_at: String) -> RotateApiKeyRespPayload {
+        RotateApiKeyRespPayload {
+            api_key,
+            expires_at: None,
+            previous_key_expires_at,
+        }
+    }
+}
+
@@ -0,0 +1,37 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+/// 
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum SessionKind {
+    #[serde(rename = "browser")]
+    Browser,
+    #[serde(rename = "cli")]
+    Cli,
+
+}
+
+impl ToString for SessionKind {
+    fn to_string(&self) -> String {
+        match self {
+            Self::Browser => String::from("browser"),
+            Self::Cli => String::from("cli"),
+        }
+    }
+}
+
+impl Default for SessionKind {
+    fn default() -> SessionKind {
+        Self::Browser
+    }
+}
+
@@ -0,0 +1,60 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SessionRespBody {
+    /// The version of the CLI which logged in.
+    #[serde(rename = "cli_version", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub cli_version: Option<Option<String>>,
+    #[serde(rename = "created_at")]
+    pub created_at: String,
+    /// Whether the session authenticated this request.
+    #[serde(rename = "current")]
+    pub current: bool,
+    /// The hostname of the machine a CLI session was logged in on.
+    #[serde(rename = "hostname", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<Option<String>>,
+    #[serde(rename = "id")]
+    pub id: uuid::Uuid,
+    /// The client IP of the request which logged in the browser.
+    #[serde(rename = "ip", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub ip: Option<Option<String>>,
+    #[serde(rename = "kind")]
+    pub kind: models::SessionKind,
+    /// The last time (UTC) the session authenticated a request. CLI sessions are only seen when they are refreshed.
+    #[serde(rename = "last_seen_at")]
+    pub last_seen_at: String,
+    /// The OpenID provider a browser session logged in with.
+    #[serde(rename = "provider", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub provider: Option<Option<String>>,
+    /// The user agent of the browser which logged in.
+    #[serde(rename = "user_agent", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<Option<String>>,
+}
+
+impl SessionRespBody {
+    pub fn new(created_at: String, current: bool, id: uuid::Uuid, kind: models::SessionKind, last_seen_at: String) -> SessionRespBody {
+        SessionRespBody {
+            cli_version: None,
+            created_at,
+            current,
+            hostname: None,
+            id,
+            ip: None,
+            kind,
+            last_seen_at,
+            provider: None,
+            user_agent: None,
+        }
+    }
+}
+
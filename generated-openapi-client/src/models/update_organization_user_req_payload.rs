@@ -0,0 +1,26 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UpdateOrganizationUserReqPayload {
+    #[serde(rename = "role")]
+    pub role: models::OrganizationRole,
+}
+
+impl UpdateOrganizationUserReqPayload {
+    pub fn new(role: models::OrganizationRole) -> UpdateOrganizationUserReqPayload {
+        UpdateOrganizationUserReqPayload {
+            role,
+        }
+    }
+}
+
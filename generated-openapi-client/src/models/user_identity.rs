@@ -0,0 +1,55 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UserIdentity {
+    #[serde(rename = "created_at")]
+    pub created_at: String,
+    /// The email the provider reported for the identity at the last login.
+    #[serde(rename = "email", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub email: Option<Option<String>>,
+    /// Whether the provider asserted `email` with the `email_verified` claim at the last login.
+    #[serde(rename = "email_verified")]
+    pub email_verified: bool,
+    #[serde(rename = "id")]
+    pub id: uuid::Uuid,
+    /// The name the provider reported for the identity at the last login.
+    #[serde(rename = "name", default, with = "::serde_with::rust::double_option", skip_serializing_if = "Option::is_none")]
+    pub name: Option<Option<String>>,
+    /// Name of the OpenID provider the identity belongs to.
+    #[serde(rename = "provider")]
+    pub provider: String,
+    /// The `sub` claim the provider identifies the user with.
+    #[serde(rename = "subject")]
+    pub subject: String,
+    #[serde(rename = "updated_at")]
+    pub updated_at: String,
+    #[serde(rename = "user_id")]
+    pub user_id: uuid::Uuid,
+}
+
+impl UserIdentity {
+    pub fn new(created_at: String, email_verified: bool, id: uuid::Uuid, provider: String, subject: String, updated_at: String, user_id: uuid::Uuid) -> UserIdentity {
+        UserIdentity {
+            created_at,
+            email: None,
+            email_verified,
+            id,
+            name: None,
+            provider,
+            subject,
+            updated_at,
+            user_id,
+        }
+    }
+}
+
@@ -0,0 +1,39 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UserInvitationRespBody {
+    /// The time (UTC) after which the invitation can no longer be accepted.
+    #[serde(rename = "expires_at")]
+    pub expires_at: String,
+    #[serde(rename = "id")]
+    pub id: uuid::Uuid,
+    #[serde(rename = "organization_id")]
+    pub organization_id: uuid::Uuid,
+    #[serde(rename = "organization_name")]
+    pub organization_name: String,
+    #[serde(rename = "role")]
+    pub role: models::OrganizationRole,
+}
+
+impl UserInvitationRespBody {
+    pub fn new(expires_at: String, id: uuid::Uuid, organization_id: uuid::Uuid, organization_name: String, role: models::OrganizationRole) -> UserInvitationRespBody {
+        UserInvitationRespBody {
+            expires_at,
+            id,
+            organization_id,
+            organization_name,
+            role,
+        }
+    }
+}
+
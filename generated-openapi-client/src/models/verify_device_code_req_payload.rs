@@ -0,0 +1,31 @@
+/*
+ * Trieve API
+ *
+ * Trieve OpenAPI Specification. This document describes all of the operations available through the Trieve API.
+ *
+ * The version of the OpenAPI document: 0.0.1
+ * Contact: developers@trieve.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VerifyDeviceCodeReqPayload {
+    /// Whether to log the device in with the auth'ed user or to deny the login.
+    #[serde(rename = "approve")]
+    pub approve: bool,
+    /// The code shown by the device. Case, spaces and dashes are ignored.
+    #[serde(rename = "user_code")]
+    pub user_code: String,
+}
+
+impl VerifyDeviceCodeReqPayload {
+    pub fn new(approve: bool, user_code: String) -> VerifyDeviceCodeReqPayload {
+        VerifyDeviceCodeReqPayload {
+            approve,
+            user_code,
+        }
+    }
+}
+